@@ -1,10 +1,13 @@
-use std::cmp::max;
-
 use criterion::{criterion_group, criterion_main, Criterion};
-use examples::{fast_fourier_transform, fibonacci, ExampleOptions, ExampleType};
+use arithmetization_benchmarks::air::{build_program_example, AirOptions};
+use arithmetization_benchmarks::registry::PROGRAM_REGISTRY;
+use examples::{ExampleOptions, ExampleType};
 
-fn run_benchmarks(crit: &mut Criterion, program: ExampleType) {
-    let mut options = ExampleOptions {
+fn run_benchmarks(crit: &mut Criterion, program_name: &str, size: u64) {
+    let spec = PROGRAM_REGISTRY.lookup(program_name).unwrap();
+    let layout = spec.air_layout(None).unwrap();
+    let program = spec.air_example_type(layout, size).unwrap();
+    let options = ExampleOptions {
         example: program,
         hash_fn: "blake3_256".to_string(),
         num_queries: Some(16),
@@ -15,23 +18,18 @@ fn run_benchmarks(crit: &mut Criterion, program: ExampleType) {
     };
 
     // Build a pretty test name.
-    let testname;
-    let example = match options.example {
-        ExampleType::Fib { sequence_length } => {
-            testname = format!("Fib-{sequence_length}");
-            fibonacci::mulfib2::get_example(&options, sequence_length).unwrap()
-        }
-        ExampleType::FFT { degree } => {
-            testname = format!("FFT-{degree}");
-            let b = max(degree, 64);
-            options.blowup_factor = Some(b);
-            fast_fourier_transform::get_example(&options, degree).unwrap()
-        }
-        other => {
+    let testname = match options.example {
+        ExampleType::Fib { sequence_length } => format!("Fib-{sequence_length}"),
+        ExampleType::Mulfib8 { sequence_length } => format!("Mulfib8-{sequence_length}"),
+        ExampleType::FFT { degree } => format!("FFT-{degree}"),
+        ref other => {
             println!("Example type {other:?} not supported");
             return;
         }
     };
+    // The blowup is raised to the smallest one which covers the constraint degrees.
+    let air_options = AirOptions::from_example_options(&options);
+    let example = build_program_example(spec, layout, size, None, &air_options, true).unwrap();
 
     // Build and run the prover benchmarks.
     let mut prover_bench = crit.benchmark_group("prover");
@@ -51,11 +49,11 @@ fn run_benchmarks(crit: &mut Criterion, program: ExampleType) {
     verifier_bench.finish();
 }
 fn criterion_benchmark(c: &mut Criterion) {
-    // This is how you instantiate a benchmark for AIR
-    // run_benchmarks(c, ExampleType::Fib{sequence_length: 1 << 15});
-    // run_benchmarks(c, ExampleType::Fib{sequence_length: 1 << 20});
-    // run_benchmarks(c, ExampleType::Fib{sequence_length: 1 << 25});
-    run_benchmarks(c, ExampleType::FFT { degree: 1 << 7 });
+    // This is how you instantiate a benchmark for AIR, sizes are log2 as listed in src/registry.rs.
+    // run_benchmarks(c, "fib", 15);
+    // run_benchmarks(c, "fib", 20);
+    // run_benchmarks(c, "fib", 25);
+    run_benchmarks(c, "fft", 7);
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,204 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    address, bit_reversed, get_periodic_columns, get_trace_length, BUTTERFLY_FLAG, TRACE_WIDTH,
+    U, V, X, Y,
+};
+use winterfell::{
+    math::{fields::f64::BaseElement, ExtensionOf, FieldElement},
+    Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter, EvaluationFrame, ProofOptions,
+    Serializable, TraceInfo, TransitionConstraintDegree,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+pub struct PublicInputs {
+    pub inputs: Vec<BaseElement>,
+    pub outputs: Vec<BaseElement>,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u64(self.inputs.len() as u64);
+        for value in self.inputs.iter().chain(self.outputs.iter()) {
+            target.write(*value);
+        }
+    }
+}
+
+// CONSTRAINT DEGREES
+// ================================================================================================
+
+/// Degrees of the transition constraints over the main trace segment, for a trace of
+/// `trace_length` rows.
+pub fn main_constraint_degrees(trace_length: usize) -> Vec<TransitionConstraintDegree> {
+    // both butterfly outputs are linear in the trace, times the periodic twiddle; padding rows,
+    // selected by a periodic column, read the same value twice
+    vec![TransitionConstraintDegree::with_cycles(1, vec![trace_length]); 3]
+}
+
+/// Degrees of the transition constraints over the auxiliary trace segment.
+pub fn aux_constraint_degrees() -> Vec<TransitionConstraintDegree> {
+    // each permutation step multiplies an auxiliary column by a read or write term
+    vec![TransitionConstraintDegree::new(2); 2]
+}
+
+// FFT ROWS AIR
+// ================================================================================================
+
+pub struct FftRowsAir {
+    context: AirContext<BaseElement>,
+    log_size: u32,
+    inputs: Vec<BaseElement>,
+    outputs: Vec<BaseElement>,
+}
+
+impl Air for FftRowsAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        assert_eq!(TRACE_WIDTH, trace_info.layout().main_trace_width());
+        let size = pub_inputs.inputs.len();
+        assert!(
+            size.is_power_of_two() && size > 1,
+            "FFT size must be a power of two greater than 1"
+        );
+        assert_eq!(
+            size,
+            pub_inputs.outputs.len(),
+            "FFT needs as many outputs as inputs"
+        );
+        let log_size = size.trailing_zeros();
+        let trace_length = trace_info.length();
+        assert_eq!(
+            get_trace_length(log_size),
+            trace_length,
+            "unexpected trace length for the FFT size"
+        );
+
+        let main_degrees = main_constraint_degrees(trace_length);
+        let aux_degrees = aux_constraint_degrees();
+
+        // the two outputs of the first butterfly of the last stage; the permutation product binds
+        // the rest
+        let num_main_assertions = 2;
+        let num_aux_assertions = 2;
+
+        FftRowsAir {
+            context: AirContext::new_multi_segment(
+                trace_info,
+                main_degrees,
+                aux_degrees,
+                num_main_assertions,
+                num_aux_assertions,
+                options,
+            ),
+            log_size,
+            inputs: pub_inputs.inputs,
+            outputs: pub_inputs.outputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+
+        let t = periodic_values[0] * current[V];
+        result[0] = current[X] - current[U] - t;
+        result[1] = current[Y] - current[U] + t;
+
+        // with a zero twiddle a padding row writes U twice, so it has to read U twice for its
+        // terms to cancel in the permutation product
+        let padding_flag = E::ONE - periodic_values[BUTTERFLY_FLAG];
+        result[2] = padding_flag * (current[V] - current[U]);
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        main_frame: &EvaluationFrame<F>,
+        aux_frame: &EvaluationFrame<E>,
+        periodic_values: &[F],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let main_current = main_frame.current();
+        let aux_current = aux_frame.current();
+        let aux_next = aux_frame.next();
+
+        let random_elements = aux_rand_elements.get_segment_elements(0);
+        let alpha = random_elements[0];
+        let beta = random_elements[1];
+
+        // periodic values 1..=4 are the addresses of U, V, X and Y
+        let term = |column: usize| {
+            alpha - E::from(periodic_values[1 + column]) - beta.mul_base(main_current[column])
+        };
+
+        // the product goes from p to t over the first read and write, and from t to the next p
+        // over the second
+        result[0] = aux_current[1] * term(X) - aux_current[0] * term(U);
+        result[1] = aux_next[0] * term(Y) - aux_current[1] * term(V);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let size = self.inputs.len();
+        let last_stage_step = (self.log_size as usize - 1) * size / 2;
+        vec![
+            Assertion::single(X, last_stage_step, self.outputs[0]),
+            Assertion::single(Y, last_stage_step, self.outputs[size / 2]),
+        ]
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        let random_elements = aux_rand_elements.get_segment_elements(0);
+        let alpha = random_elements[0];
+        let beta = random_elements[1];
+        let size = self.inputs.len();
+        let last_stage = self.log_size as usize;
+
+        // every value but the inputs and outputs is read once and written once, so the product
+        // of the reads over the writes is the product of the input terms over the output terms
+        let mut numerator = E::ONE;
+        let mut denominator = E::ONE;
+        for (position, (input, output)) in bit_reversed(&self.inputs)
+            .into_iter()
+            .zip(self.outputs.iter())
+            .enumerate()
+        {
+            numerator *= alpha - E::from(address(0, position, size)) - beta.mul_base(input);
+            denominator *=
+                alpha - E::from(address(last_stage, position, size)) - beta.mul_base(*output);
+        }
+
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, E::ONE),
+            Assertion::single(0, last_step, numerator / denominator),
+        ]
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        get_periodic_columns(self.log_size)
+    }
+}
@@ -0,0 +1,339 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! A radix-2 FFT with one butterfly per row, proven with a randomized AIR (RAP).
+//!
+//! The winterfell FFT example keeps every value of the FFT in its own column, so its width grows
+//! with the FFT size and its constraint degree with the number of stages. This layout instead
+//! spends one row on each of the `n/2 * log2(n)` butterflies, in the same order as the R1CS
+//! circuit, and keeps the trace four columns wide for any size. A row reads `U` and `V` and
+//! writes `X = U + w*V` and `Y = U - w*V`; the twiddle `w` and the addresses of the four values
+//! are periodic columns, so all constraints have degree 2.
+//!
+//! Value `j` of the FFT after stage `s` lives at address `s*n + j`, so every address is written
+//! once and read once. Two auxiliary columns accumulate a permutation product over the reads and
+//! the writes. The inputs only appear as reads and the outputs only as writes, so the product
+//! ends at the ratio of the inputs' and outputs' terms, which the verifier computes from the
+//! public inputs. Padding rows read `U` and `V` and write them back at address zero; a periodic
+//! selector, zero on those rows, forces `V = U` there, so their reads and writes cancel out.
+
+use std::time::Instant;
+
+use examples::Example;
+use log::debug;
+use winterfell::{
+    math::{fields::f64::BaseElement, FieldElement, StarkField},
+    ProofOptions, Prover, StarkProof, Trace, TransitionConstraintDegree, VerifierError,
+};
+
+use crate::circuit::programs::bit_reverse;
+
+mod air;
+use air::{aux_constraint_degrees, main_constraint_degrees, FftRowsAir, PublicInputs};
+
+mod prover;
+use prover::FftRowsProver;
+
+// CONSTANTS
+// ================================================================================================
+
+const TRACE_WIDTH: usize = 4;
+
+// Column layout of the main trace segment.
+const U: usize = 0;
+const V: usize = 1;
+const X: usize = 2;
+const Y: usize = 3;
+
+// Index of the periodic column which selects butterfly rows, after the twiddle and the addresses.
+const BUTTERFLY_FLAG: usize = TRACE_WIDTH + 1;
+
+// FFT ROWS EXAMPLE
+// ================================================================================================
+
+pub struct FftRowsExample {
+    options: ProofOptions,
+    inputs: Vec<BaseElement>,
+    outputs: Vec<BaseElement>,
+}
+
+impl FftRowsExample {
+    pub fn new(size: usize, options: ProofOptions) -> Self {
+        assert!(
+            size.is_power_of_two() && size > 1,
+            "FFT size must be a power of two greater than 1"
+        );
+        let inputs = fft_inputs(size);
+
+        let now = Instant::now();
+        let outputs = compute_fft(&inputs);
+        debug!(
+            "Computed an FFT of size {} in {} ms",
+            size,
+            now.elapsed().as_millis(),
+        );
+
+        FftRowsExample {
+            options,
+            inputs,
+            outputs,
+        }
+    }
+
+    /// The FFT of the inputs, in the order the R1CS circuit outputs it.
+    pub fn outputs(&self) -> &[BaseElement] {
+        &self.outputs
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl Example for FftRowsExample {
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for an FFT of size {}, one butterfly per row\n\
+            ---------------------",
+            self.inputs.len(),
+        );
+
+        // create a prover
+        let prover = FftRowsProver::new(self.options.clone(), self.inputs.clone());
+
+        // generate execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace();
+        let trace_width = trace.width();
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace_width,
+            trace_length.trailing_zeros(),
+            now.elapsed().as_millis()
+        );
+
+        // generate the proof
+        prover.prove(trace).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = PublicInputs {
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+        };
+        winterfell::verify::<FftRowsAir>(proof, pub_inputs)
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let mut outputs = self.outputs.clone();
+        outputs[0] += BaseElement::ONE;
+        let pub_inputs = PublicInputs {
+            inputs: self.inputs.clone(),
+            outputs,
+        };
+        winterfell::verify::<FftRowsAir>(proof, pub_inputs)
+    }
+}
+
+// BUTTERFLY SCHEDULE
+// ================================================================================================
+
+/// One butterfly of the FFT: it reads values `top` and `bottom` after stage `stage - 1`, and
+/// writes the same positions after stage `stage`.
+#[derive(Debug, Clone, Copy)]
+struct Butterfly {
+    stage: usize,
+    top: usize,
+    bottom: usize,
+    twiddle: BaseElement,
+}
+
+/// The butterflies of an FFT of size `2^log_size`, in the order the R1CS circuit computes them:
+/// iterative Cooley-Tukey on inputs in bit-reversed order.
+fn butterflies(log_size: u32) -> Vec<Butterfly> {
+    let size = 1usize << log_size;
+    let mut result = Vec::with_capacity(size / 2 * log_size as usize);
+    for stage in 1..=log_size {
+        let half = 1usize << (stage - 1);
+        let root = BaseElement::get_root_of_unity(stage);
+        for start in (0..size).step_by(2 * half) {
+            let mut twiddle = BaseElement::ONE;
+            for top in start..start + half {
+                result.push(Butterfly {
+                    stage: stage as usize,
+                    top,
+                    bottom: top + half,
+                    twiddle,
+                });
+                twiddle *= root;
+            }
+        }
+    }
+    result
+}
+
+/// Address of value `position` after stage `stage` in an FFT of size `size`; stage 0 holds the
+/// bit-reversed inputs.
+fn address(stage: usize, position: usize, size: usize) -> BaseElement {
+    BaseElement::new((stage * size + position) as u64)
+}
+
+/// The inputs of an FFT of size `size`: `5, 10, 15, ...`, the same as in the R1CS circuit.
+fn fft_inputs(size: usize) -> Vec<BaseElement> {
+    (0..size as u64)
+        .map(|i| BaseElement::new(5 * (i + 1)))
+        .collect()
+}
+
+/// The outputs the example proves for an FFT of size `size`, in the order the R1CS circuit
+/// outputs them.
+pub fn fft_outputs(size: usize) -> Vec<BaseElement> {
+    compute_fft(&fft_inputs(size))
+}
+
+/// The FFT of `inputs`, computed with the butterflies of the trace.
+fn compute_fft(inputs: &[BaseElement]) -> Vec<BaseElement> {
+    let log_size = inputs.len().trailing_zeros();
+    let mut values = bit_reversed(inputs);
+    for butterfly in butterflies(log_size) {
+        let u = values[butterfly.top];
+        let t = values[butterfly.bottom] * butterfly.twiddle;
+        values[butterfly.top] = u + t;
+        values[butterfly.bottom] = u - t;
+    }
+    values
+}
+
+/// `values` in bit-reversed order: the FFT values before the first stage.
+fn bit_reversed(values: &[BaseElement]) -> Vec<BaseElement> {
+    let log_size = values.len().trailing_zeros();
+    (0..values.len())
+        .map(|i| values[bit_reverse(i, log_size)])
+        .collect()
+}
+
+/// Number of rows needed for the butterflies of an FFT of size `2^log_size`. One spare row is
+/// kept at the end because the permutation product only covers the rows before the last.
+fn get_trace_length(log_size: u32) -> usize {
+    let num_butterflies = (1usize << (log_size - 1)) * log_size as usize;
+    (num_butterflies + 1).next_power_of_two().max(8)
+}
+
+/// Degrees of the transition constraints of the AIR for an FFT of size `size`, over the main and
+/// then the auxiliary trace segment. The AIR is built with these, and the registry derives the
+/// blowup factor of the `rows` layout from them.
+pub fn transition_constraint_degrees(size: usize) -> Vec<TransitionConstraintDegree> {
+    let trace_length = get_trace_length(size.trailing_zeros());
+    let mut degrees = main_constraint_degrees(trace_length);
+    degrees.extend(aux_constraint_degrees());
+    degrees
+}
+
+/// The periodic columns of the AIR: the twiddle of each row's butterfly, then the addresses of
+/// `U`, `V`, `X` and `Y`, then a selector which is one on butterfly rows. Padding rows have a
+/// zero twiddle, zero addresses and a zero selector.
+fn get_periodic_columns(log_size: u32) -> Vec<Vec<BaseElement>> {
+    let size = 1usize << log_size;
+    let trace_length = get_trace_length(log_size);
+    let mut columns = vec![vec![BaseElement::ZERO; trace_length]; TRACE_WIDTH + 2];
+    for (step, butterfly) in butterflies(log_size).into_iter().enumerate() {
+        let stage = butterfly.stage;
+        columns[0][step] = butterfly.twiddle;
+        columns[1 + U][step] = address(stage - 1, butterfly.top, size);
+        columns[1 + V][step] = address(stage - 1, butterfly.bottom, size);
+        columns[1 + X][step] = address(stage, butterfly.top, size);
+        columns[1 + Y][step] = address(stage, butterfly.bottom, size);
+        columns[BUTTERFLY_FLAG][step] = BaseElement::ONE;
+    }
+    columns
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::prover::FftTraceTable;
+    use super::*;
+    use crate::air::AirOptions;
+
+    /// FFT sizes beyond the 2^7 the winterfell example supports.
+    const SIZES: [usize; 2] = [1 << 8, 1 << 10];
+
+    fn proof_options() -> ProofOptions {
+        AirOptions {
+            grinding_factor: 0,
+            ..AirOptions::default()
+        }
+        .to_proof_options()
+    }
+
+    /// Whether a proof of `trace` verifies against the true inputs and outputs of an FFT of size
+    /// `size`. In debug builds the prover checks the trace against the AIR and panics on a
+    /// forged one, so a panic counts as a rejection.
+    fn verifies(size: usize, trace: FftTraceTable) -> bool {
+        let prover = FftRowsProver::new(proof_options(), fft_inputs(size));
+        let pub_inputs = PublicInputs {
+            inputs: fft_inputs(size),
+            outputs: fft_outputs(size),
+        };
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let proof = prover.prove(trace).unwrap();
+            winterfell::verify::<FftRowsAir>(proof, pub_inputs).is_ok()
+        }))
+        .unwrap_or(false)
+    }
+
+    fn honest_trace(size: usize) -> FftTraceTable {
+        FftRowsProver::new(proof_options(), fft_inputs(size)).build_trace()
+    }
+
+    #[test]
+    fn proves_ffts_beyond_the_columns_layout() {
+        for size in SIZES {
+            let example = FftRowsExample::new(size, proof_options());
+            let proof = example.prove();
+            assert!(
+                example.verify(proof.clone()).is_ok(),
+                "FFT of size {} does not verify",
+                size
+            );
+            assert!(
+                example.verify_with_wrong_inputs(proof).is_err(),
+                "FFT of size {} verifies against wrong outputs",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn honest_trace_verifies() {
+        assert!(verifies(SIZES[0], honest_trace(SIZES[0])));
+    }
+
+    #[test]
+    fn rejects_a_wrong_butterfly_output() {
+        let size = SIZES[0];
+        let mut trace = honest_trace(size);
+        trace.set(X, 0, trace.get(X, 0) + BaseElement::ONE);
+        assert!(!verifies(size, trace));
+    }
+
+    #[test]
+    fn rejects_a_butterfly_on_a_forged_read() {
+        // the butterfly is computed correctly, but from a value no earlier row wrote, which only
+        // the permutation argument catches
+        let size = SIZES[0];
+        let butterfly = butterflies(size.trailing_zeros())[0];
+        let mut trace = honest_trace(size);
+        let u = trace.get(U, 0) + BaseElement::ONE;
+        let t = trace.get(V, 0) * butterfly.twiddle;
+        trace.set(U, 0, u);
+        trace.set(X, 0, u + t);
+        trace.set(Y, 0, u - t);
+        assert!(!verifies(size, trace));
+    }
+}
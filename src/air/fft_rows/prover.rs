@@ -0,0 +1,214 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    bit_reversed, butterflies, get_periodic_columns, get_trace_length, FftRowsAir, PublicInputs,
+    TRACE_WIDTH, U, V, X, Y,
+};
+use winterfell::{
+    math::{batch_inversion, fields::f64::BaseElement, FieldElement},
+    EvaluationFrame, Matrix, ProofOptions, Prover, Trace, TraceLayout,
+};
+
+// FFT ROWS PROVER
+// ================================================================================================
+
+pub struct FftRowsProver {
+    options: ProofOptions,
+    inputs: Vec<BaseElement>,
+}
+
+impl FftRowsProver {
+    pub fn new(options: ProofOptions, inputs: Vec<BaseElement>) -> Self {
+        Self { options, inputs }
+    }
+
+    /// Builds the main trace segment: the inputs and outputs of one butterfly per row, followed
+    /// by padding rows of zeros.
+    pub fn build_trace(&self) -> FftTraceTable {
+        let log_size = self.inputs.len().trailing_zeros();
+        let trace_length = get_trace_length(log_size);
+
+        let mut columns = vec![vec![BaseElement::ZERO; trace_length]; TRACE_WIDTH];
+        let mut values = bit_reversed(&self.inputs);
+        for (step, butterfly) in butterflies(log_size).into_iter().enumerate() {
+            let u = values[butterfly.top];
+            let v = values[butterfly.bottom];
+            let t = v * butterfly.twiddle;
+            values[butterfly.top] = u + t;
+            values[butterfly.bottom] = u - t;
+
+            columns[U][step] = u;
+            columns[V][step] = v;
+            columns[X][step] = values[butterfly.top];
+            columns[Y][step] = values[butterfly.bottom];
+        }
+
+        FftTraceTable::init(columns, get_periodic_columns(log_size))
+    }
+}
+
+impl Prover for FftRowsProver {
+    type BaseField = BaseElement;
+    type Air = FftRowsAir;
+    type Trace = FftTraceTable;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let size = self.inputs.len();
+        let log_size = size.trailing_zeros() as usize;
+
+        // the last stage writes output j and j + size/2 in its j-th row
+        let last_stage_step = (log_size - 1) * size / 2;
+        let mut outputs = vec![BaseElement::ZERO; size];
+        for position in 0..size / 2 {
+            outputs[position] = trace.get(X, last_stage_step + position);
+            outputs[position + size / 2] = trace.get(Y, last_stage_step + position);
+        }
+        PublicInputs {
+            inputs: self.inputs.clone(),
+            outputs,
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// FFT TRACE TABLE
+// ================================================================================================
+
+/// Execution trace with two auxiliary columns: the running permutation product of the reads over
+/// the writes at the start of each row, and the same product after the row's first read and
+/// write. Keeps the periodic address columns of the AIR to build them.
+pub struct FftTraceTable {
+    layout: TraceLayout,
+    trace: Matrix<BaseElement>,
+    addresses: Vec<Vec<BaseElement>>,
+    meta: Vec<u8>,
+}
+
+impl FftTraceTable {
+    pub fn init(columns: Vec<Vec<BaseElement>>, periodic_columns: Vec<Vec<BaseElement>>) -> Self {
+        assert_eq!(
+            TRACE_WIDTH,
+            columns.len(),
+            "unexpected number of trace columns"
+        );
+        let trace_length = columns[0].len();
+        assert!(
+            trace_length.is_power_of_two(),
+            "trace length must be a power of two"
+        );
+        for column in columns.iter() {
+            assert_eq!(
+                trace_length,
+                column.len(),
+                "all columns must have the same length"
+            );
+        }
+
+        Self {
+            // two auxiliary columns built from two random elements
+            layout: TraceLayout::new(TRACE_WIDTH, [2], [2]),
+            trace: Matrix::new(columns),
+            // the first periodic column holds the twiddles, and the last one the butterfly
+            // selector
+            addresses: periodic_columns
+                .into_iter()
+                .skip(1)
+                .take(TRACE_WIDTH)
+                .collect(),
+            meta: vec![],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.trace.num_cols()
+    }
+
+    pub fn get(&self, column: usize, step: usize) -> BaseElement {
+        self.trace.get(column, step)
+    }
+
+    /// Overwrites one cell of the main segment, to check that forged traces are rejected.
+    #[cfg(test)]
+    pub fn set(&mut self, column: usize, step: usize, value: BaseElement) {
+        self.trace.set(column, step, value);
+    }
+
+    fn term<E>(&self, column: usize, step: usize, alpha: E, beta: E) -> E
+    where
+        E: FieldElement<BaseField = BaseElement>,
+    {
+        alpha - E::from(self.addresses[column][step]) - beta.mul_base(self.trace.get(column, step))
+    }
+}
+
+impl Trace for FftTraceTable {
+    type BaseField = BaseElement;
+
+    fn layout(&self) -> &TraceLayout {
+        &self.layout
+    }
+
+    fn length(&self) -> usize {
+        self.trace.num_rows()
+    }
+
+    fn meta(&self) -> &[u8] {
+        &self.meta
+    }
+
+    fn main_segment(&self) -> &Matrix<BaseElement> {
+        &self.trace
+    }
+
+    fn build_aux_segment<E>(
+        &mut self,
+        aux_segments: &[Matrix<E>],
+        rand_elements: &[E],
+    ) -> Option<Matrix<E>>
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        // there is only one auxiliary segment
+        if !aux_segments.is_empty() {
+            return None;
+        }
+
+        let alpha = rand_elements[0];
+        let beta = rand_elements[1];
+        let trace_length = self.length();
+
+        // write terms are the denominators, at even positions for X and odd ones for Y
+        let mut denominators = Vec::with_capacity(2 * (trace_length - 1));
+        for step in 0..trace_length - 1 {
+            denominators.push(self.term(X, step, alpha, beta));
+            denominators.push(self.term(Y, step, alpha, beta));
+        }
+        let inv_denominators = batch_inversion(&denominators);
+
+        let mut product = Vec::with_capacity(trace_length);
+        let mut halfway = Vec::with_capacity(trace_length);
+        product.push(E::ONE);
+        for step in 0..trace_length - 1 {
+            halfway
+                .push(product[step] * self.term(U, step, alpha, beta) * inv_denominators[2 * step]);
+            product.push(
+                halfway[step] * self.term(V, step, alpha, beta) * inv_denominators[2 * step + 1],
+            );
+        }
+        // the last row is not covered by the product
+        halfway.push(product[trace_length - 1]);
+
+        Some(Matrix::new(vec![product, halfway]))
+    }
+
+    fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<BaseElement>) {
+        let next_row_idx = (row_idx + 1) % self.length();
+        self.trace.read_row_into(row_idx, frame.current_mut());
+        self.trace.read_row_into(next_row_idx, frame.next_mut());
+    }
+}
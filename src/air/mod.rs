@@ -0,0 +1,489 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Builds winterfell AIR examples, and the AIRs implemented in this crate, for the programs we
+//! benchmark.
+//!
+//! The blowup factor of an AIR proof has to cover the degree of its transition constraints, and
+//! the FFT example's degree grows with the FFT size. [min_blowup_factor] computes the smallest
+//! legal blowup from the constraints the registry records for a layout.
+
+use std::time::Instant;
+
+use examples::{fast_fourier_transform, fibonacci, Example, ExampleOptions, ExampleType};
+use thiserror::Error;
+use winter_math::log2;
+use winterfell::{
+    FieldExtension, HashFunction, ProofOptions, StarkProof, TransitionConstraintDegree,
+    VerifierError,
+};
+
+use crate::hash::HashError;
+use crate::proof_file::{ProofFile, ProofFileError};
+use crate::registry::{
+    AirConstraints, AirConstructor, AirLayout, ProgramSpec, RegistryError, PROGRAM_REGISTRY,
+};
+use crate::report::RunReport;
+use crate::security::{SecurityCeiling, SecurityError, SecurityParams};
+
+pub mod fft_rows;
+pub mod pointer_chasing;
+
+/// Smallest blowup factor winterfell accepts.
+pub const AIR_MIN_BLOWUP_FACTOR: usize = 2;
+/// Largest blowup factor winterfell accepts.
+pub const AIR_MAX_BLOWUP_FACTOR: usize = 128;
+/// Largest blowup factor which keeps the LDE from dominating the cost of a proof. Examples whose
+/// constraint degrees need more are reported when they are built.
+pub const AIR_REASONABLE_BLOWUP_FACTOR: usize = 16;
+/// Largest size of the FRI remainder. The winterfell examples build their proof options with it,
+/// so it cannot be changed per run.
+pub const AIR_FRI_MAX_REMAINDER_SIZE: usize = 256;
+
+#[derive(Debug, Error)]
+pub enum AirError {
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error("{example} needs a blowup factor above {max}, the largest winterfell accepts, to cover its constraint degrees")]
+    BlowupTooLarge { example: String, max: usize },
+}
+
+pub fn get_num_main_trace_rows(num_fft_inputs: usize) -> usize {
+    let log_num_fft_terms: usize = log2(num_fft_inputs).try_into().unwrap();
+    log_num_fft_terms + 2
+}
+
+pub fn get_num_cols(num_fft_inputs: usize) -> usize {
+    let log_num_fft_terms: usize = log2(num_fft_inputs).try_into().unwrap();
+    // the first num_fft_inputs are for keeping the actual values at eachs step
+    // the next value is for keeping the local omegas
+    // Then, we store log_num_fft_terms + 1 bits which help select the function to apply
+    // Finally, the additional position is to keep the power of 2 represented by the aforementioned bits
+    num_fft_inputs + 1 + 1 + (log_num_fft_terms + 1) + 2
+}
+
+/// Hash functions winterfell proves with.
+pub const AIR_HASH_FNS: [&str; 3] = ["blake3_256", "blake3_192", "sha3_256"];
+
+/// Checks that winterfell can prove with `hash_fn`.
+pub fn check_air_hash_fn(hash_fn: &str) -> Result<(), HashError> {
+    if AIR_HASH_FNS.contains(&hash_fn) {
+        Ok(())
+    } else {
+        Err(HashError::unsupported(hash_fn, "air", &AIR_HASH_FNS))
+    }
+}
+
+/// Proof parameters for an AIR run, independent of the program being proven.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirOptions {
+    pub hash_fn: String,
+    pub num_queries: usize,
+    pub blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub field_extension: u32,
+    pub folding_factor: usize,
+}
+
+impl Default for AirOptions {
+    /// The options used by the benches.
+    fn default() -> Self {
+        AirOptions {
+            hash_fn: "blake3_256".to_string(),
+            num_queries: 16,
+            blowup_factor: 4,
+            grinding_factor: 16,
+            field_extension: 1,
+            folding_factor: 8,
+        }
+    }
+}
+
+impl AirOptions {
+    /// The default options with blowup, queries, grinding and folding derived from a target
+    /// security level in bits, which the field and hash function of the run must reach up to
+    /// `ceiling`.
+    pub fn for_security_level(bits: u32, ceiling: &SecurityCeiling) -> Result<Self, SecurityError> {
+        let params = SecurityParams::for_target(bits, true, ceiling)?;
+        Ok(AirOptions {
+            num_queries: params.num_queries,
+            blowup_factor: params.blowup_factor,
+            grinding_factor: params.grinding_factor,
+            folding_factor: params.folding_factor,
+            ..AirOptions::default()
+        })
+    }
+
+    /// The proof parameters of winterfell example options; unset ones take their defaults.
+    pub fn from_example_options(options: &ExampleOptions) -> Self {
+        let defaults = AirOptions::default();
+        AirOptions {
+            hash_fn: options.hash_fn.clone(),
+            num_queries: options.num_queries.unwrap_or(defaults.num_queries),
+            blowup_factor: options.blowup_factor.unwrap_or(defaults.blowup_factor),
+            grinding_factor: options.grinding_factor,
+            field_extension: options.field_extension,
+            folding_factor: options.folding_factor,
+        }
+    }
+
+    pub fn to_example_options(&self, example: ExampleType) -> ExampleOptions {
+        ExampleOptions {
+            example,
+            hash_fn: self.hash_fn.clone(),
+            num_queries: Some(self.num_queries),
+            blowup_factor: Some(self.blowup_factor),
+            grinding_factor: self.grinding_factor,
+            field_extension: self.field_extension,
+            folding_factor: self.folding_factor,
+        }
+    }
+
+    pub fn to_proof_options(&self) -> ProofOptions {
+        let hash_fn = match self.hash_fn.as_str() {
+            "blake3_192" => HashFunction::Blake3_192,
+            "blake3_256" => HashFunction::Blake3_256,
+            "sha3_256" => HashFunction::Sha3_256,
+            other => panic!("Unsupported hash function: {}", other),
+        };
+        let field_extension = match self.field_extension {
+            1 => FieldExtension::None,
+            2 => FieldExtension::Quadratic,
+            3 => FieldExtension::Cubic,
+            other => panic!("Unsupported field extension degree: {}", other),
+        };
+        ProofOptions::new(
+            self.num_queries,
+            self.blowup_factor,
+            self.grinding_factor,
+            hash_fn,
+            field_extension,
+            self.folding_factor,
+            AIR_FRI_MAX_REMAINDER_SIZE,
+        )
+    }
+}
+
+/// Default proof options for an AIR example, matching the ones used by the benches.
+pub fn default_air_options(example: ExampleType) -> ExampleOptions {
+    AirOptions::default().to_example_options(example)
+}
+
+/// Instantiates the AIR side of `spec` under `layout`, at the given size and iteration count.
+/// When `adjust_blowup` is set, the blowup factor is raised to the smallest one which covers the
+/// layout's constraint degrees, see [min_blowup_factor], and a blowup above
+/// [AIR_REASONABLE_BLOWUP_FACTOR] is reported.
+pub fn build_program_example(
+    spec: &ProgramSpec,
+    layout: &AirLayout,
+    size: u64,
+    iterations: Option<u64>,
+    options: &AirOptions,
+    adjust_blowup: bool,
+) -> Result<Box<dyn Example>, AirError> {
+    layout.check_size(spec.name, size)?;
+    let mut options = options.clone();
+    if adjust_blowup {
+        let min_blowup = min_blowup_factor(spec, layout, size, iterations)?;
+        options.blowup_factor = options.blowup_factor.max(min_blowup);
+        if min_blowup > AIR_REASONABLE_BLOWUP_FACTOR {
+            println!(
+                "Warning: {} under layout {} at size {} needs a blowup factor of {} to cover its constraint degrees, so its LDE is {} times the trace",
+                spec.name, layout.name, size, min_blowup, options.blowup_factor
+            );
+        }
+    }
+
+    let instance = spec.instance(size, iterations)?;
+    match layout.air {
+        AirConstructor::Example(build) => {
+            let example_options = options.to_example_options(build(instance.air_parameter));
+            Ok(build_air_example(&example_options)
+                .expect("registered layouts only use winterfell examples built here"))
+        }
+        AirConstructor::Native(build) => Ok(build(
+            instance.air_parameter,
+            instance.iterations.unwrap_or(0),
+            options.to_proof_options(),
+        )),
+    }
+}
+
+/// The smallest blowup factor which covers the transition constraints of `layout` for `spec` at
+/// `size`: the largest [TransitionConstraintDegree::min_blowup_factor] of its constraints, which
+/// is the next power of two of a constraint's degree minus one, and at least
+/// [AIR_MIN_BLOWUP_FACTOR]. This is the bound winterfell checks when it builds the AIR's context.
+/// Layouts which need more than [AIR_MAX_BLOWUP_FACTOR] are an error.
+pub fn min_blowup_factor(
+    spec: &ProgramSpec,
+    layout: &AirLayout,
+    size: u64,
+    iterations: Option<u64>,
+) -> Result<usize, AirError> {
+    layout.check_size(spec.name, size)?;
+    let instance = spec.instance(size, iterations)?;
+    let blowup_factor = match layout.constraints {
+        AirConstraints::Degrees(degrees) => {
+            degrees(instance.air_parameter, instance.iterations.unwrap_or(0))
+                .iter()
+                .map(TransitionConstraintDegree::min_blowup_factor)
+                .max()
+                .unwrap_or(AIR_MIN_BLOWUP_FACTOR)
+        }
+        AirConstraints::MinBlowup(min_blowup) => min_blowup(instance.air_parameter),
+    }
+    .max(AIR_MIN_BLOWUP_FACTOR);
+    if blowup_factor > AIR_MAX_BLOWUP_FACTOR {
+        return Err(AirError::BlowupTooLarge {
+            example: format!(
+                "{} under layout {} at size {}",
+                spec.name, layout.name, size
+            ),
+            max: AIR_MAX_BLOWUP_FACTOR,
+        });
+    }
+    Ok(blowup_factor)
+}
+
+/// Instantiates the winterfell example described by `options`, or `None` if the example type
+/// has no AIR implementation here. The blowup factor is used as given; see
+/// [build_program_example] to raise it to what the example's constraints need.
+pub fn build_air_example(options: &ExampleOptions) -> Option<Box<dyn Example>> {
+    match options.example {
+        ExampleType::Fib { sequence_length } => {
+            Some(fibonacci::mulfib2::get_example(options, sequence_length).unwrap())
+        }
+        ExampleType::Mulfib8 { sequence_length } => {
+            Some(fibonacci::mulfib8::get_example(options, sequence_length).unwrap())
+        }
+        ExampleType::FFT { degree } => {
+            let num_cols = get_num_cols(degree);
+            let num_rows = get_num_main_trace_rows(degree);
+            println!(
+                "FFT size {} has {} columns and {} rows",
+                degree, num_cols, num_rows
+            );
+            Some(fast_fourier_transform::get_example(options, degree).unwrap())
+        }
+        _ => None,
+    }
+}
+
+/// Records the shape of the trace `proof` was generated for in `report`, with the name of its
+/// `layout` and the degrees of its transition constraints if they are known.
+pub fn record_air_shape(
+    proof: &StarkProof,
+    layout: Option<&AirLayout>,
+    degrees: Option<&[TransitionConstraintDegree]>,
+    report: &mut RunReport,
+) {
+    let trace_layout = proof.context.trace_layout();
+    let trace_length = proof.context.trace_length();
+    report.air_layout = layout.map(|layout| layout.name.to_string());
+    report.trace_width = Some(trace_layout.main_trace_width());
+    report.aux_trace_width = Some(trace_layout.aux_trace_width());
+    report.trace_length = Some(trace_length);
+    if let Some(degrees) = degrees {
+        report.num_transition_constraints = Some(degrees.len());
+        report.max_constraint_degree = degrees
+            .iter()
+            .map(|degree| constraint_degree(degree, trace_length))
+            .max();
+    }
+}
+
+/// Degree of a transition constraint in the trace columns, counting each periodic column it
+/// multiplies as one more: its degree over a trace of `trace_length` rows, divided by that of a
+/// single column.
+fn constraint_degree(degree: &TransitionConstraintDegree, trace_length: usize) -> usize {
+    let column_degree = trace_length - 1;
+    (degree.get_evaluation_degree(trace_length) + column_degree - 1) / column_degree
+}
+
+/// Proves `example`, laid out as `layout` with transition constraints of `degrees` if they are
+/// known, and, if `verify` is set, verifies the proof and checks that it is rejected against
+/// wrong public inputs. Timings, proof size and the shape of the execution trace are recorded in
+/// `report`.
+pub fn run_air_example(
+    example: &dyn Example,
+    layout: Option<&AirLayout>,
+    degrees: Option<&[TransitionConstraintDegree]>,
+    verify: bool,
+    report: &mut RunReport,
+) -> Result<(), VerifierError> {
+    let now = Instant::now();
+    let proof = example.prove();
+    report.prove_ms = Some(now.elapsed().as_millis());
+    println!(
+        "---------------------\nProof generated in {} ms",
+        now.elapsed().as_millis()
+    );
+
+    report.proof_size_bytes = Some(proof.to_bytes().len());
+    record_air_shape(&proof, layout, degrees, report);
+    report.conjectured_security_bits = Some(proof.security_level(true));
+    report.proven_security_bits = Some(proof.security_level(false));
+    println!("Proof size: {}", report.proof_size_bytes.unwrap());
+
+    if !verify {
+        return Ok(());
+    }
+
+    println!("---------------------");
+    let now = Instant::now();
+    example.verify(proof.clone())?;
+    report.verify_ms = Some(now.elapsed().as_millis());
+    println!(
+        "Proof verified in {:.1} ms",
+        now.elapsed().as_micros() as f64 / 1000f64
+    );
+    assert!(
+        example.verify_with_wrong_inputs(proof).is_err(),
+        "Proof also verified against wrong public inputs"
+    );
+    Ok(())
+}
+
+/// Verifies the AIR proof in `file`. The example is rebuilt from the program, size and options in
+/// the header, which recomputes the public inputs the proof must match; that is timed as
+/// `parse_ms`, and verification as `verify_ms`.
+pub fn verify_air_proof_file(
+    file: &ProofFile,
+    report: &mut RunReport,
+) -> Result<(), ProofFileError> {
+    let header = &file.header;
+    let now = Instant::now();
+    let spec = PROGRAM_REGISTRY.lookup(&header.program)?;
+    let layout = spec.air_layout(header.layout.as_deref())?;
+    // the proof records the blowup it was generated with, so it is not adjusted again
+    let example = build_program_example(
+        spec,
+        layout,
+        header.size,
+        header.iterations,
+        &header.air_options(),
+        false,
+    )?;
+    let proof = StarkProof::from_bytes(&file.proof)
+        .map_err(|err| ProofFileError::Deserialization(format!("proof: {}", err)))?;
+    report.parse_ms = Some(now.elapsed().as_millis());
+    report.proof_size_bytes = Some(file.proof.len());
+
+    let now = Instant::now();
+    example
+        .verify(proof)
+        .map_err(|err| ProofFileError::Verification(err.to_string()))?;
+    report.verify_ms = Some(now.elapsed().as_millis());
+    println!(
+        "Proof verified in {:.1} ms",
+        now.elapsed().as_micros() as f64 / 1000f64
+    );
+    Ok(())
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+    use crate::circuit::build_program_circuit;
+    use crate::field::{canonical_ints, f64};
+    use crate::registry::{AirOutput, SystemTag};
+
+    /// Largest size at which each layout is proven once per candidate blowup factor.
+    const MAX_PROBED_SIZE: u64 = 7;
+
+    /// Whether winterfell proves and verifies `layout` of `spec` at `size` with `blowup_factor`.
+    /// Winterfell panics when it builds the context of an AIR whose constraint degrees need a
+    /// larger blowup.
+    fn accepts_blowup(
+        spec: &ProgramSpec,
+        layout: &AirLayout,
+        size: u64,
+        blowup_factor: usize,
+    ) -> bool {
+        let options = AirOptions {
+            blowup_factor,
+            grinding_factor: 0,
+            ..AirOptions::default()
+        };
+        let example = build_program_example(spec, layout, size, None, &options, false).unwrap();
+        panic::catch_unwind(AssertUnwindSafe(|| example.verify(example.prove()).is_ok()))
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn min_blowup_factor_is_the_smallest_winterfell_accepts() {
+        for spec in PROGRAM_REGISTRY.programs() {
+            for layout in spec.air_layouts.iter() {
+                for size in layout.sizes.start..layout.sizes.end.min(MAX_PROBED_SIZE + 1) {
+                    let blowup_factor = min_blowup_factor(spec, layout, size, None).unwrap();
+                    assert!(
+                        accepts_blowup(spec, layout, size, blowup_factor),
+                        "{} under layout {} at size {} does not prove with blowup factor {}",
+                        spec.name,
+                        layout.name,
+                        size,
+                        blowup_factor
+                    );
+                    if blowup_factor > AIR_MIN_BLOWUP_FACTOR {
+                        assert!(
+                            !accepts_blowup(spec, layout, size, blowup_factor / 2),
+                            "{} under layout {} at size {} also proves with blowup factor {}",
+                            spec.name,
+                            layout.name,
+                            size,
+                            blowup_factor / 2
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layouts_agree_on_the_output_of_a_program() {
+        // every layout is proven at these sizes by the test above, so they are only built here
+        let options = AirOptions::default();
+        for spec in PROGRAM_REGISTRY.programs() {
+            let sizes = spec.supported_sizes(SystemTag::AIR);
+            for size in sizes.start..sizes.end.min(MAX_PROBED_SIZE + 1) {
+                let circuit = build_program_circuit::<f64::BaseElement>(spec, size, None)
+                    .ok()
+                    .map(|circuit| canonical_ints(&circuit.output_values()));
+                let mut outputs = vec![];
+                for layout in spec.air_layouts.iter() {
+                    if !layout.sizes.contains(&size) {
+                        continue;
+                    }
+                    build_program_example(spec, layout, size, None, &options, true).unwrap();
+                    let output = spec.air_output(layout, size, None).unwrap();
+                    let run = format!(
+                        "{} under layout {} at size {}",
+                        spec.name, layout.name, size
+                    );
+                    match layout.output {
+                        AirOutput::Computed(_) => {
+                            assert!(circuit.is_some(), "{} has no R1CS circuit", run);
+                            assert_eq!(output, circuit, "{} disagrees with the circuit", run);
+                        }
+                        AirOutput::Differs { .. } => assert!(output.is_some(), "{}", run),
+                        AirOutput::Private(_) => assert!(output.is_none(), "{}", run),
+                    }
+                    if let Some(output) = output {
+                        outputs.push((run, output));
+                    }
+                }
+                if let Some(((first_run, first), rest)) = outputs.split_first() {
+                    for (run, output) in rest {
+                        assert_eq!(first, output, "{} disagrees with {}", run, first_run);
+                    }
+                }
+            }
+        }
+    }
+}
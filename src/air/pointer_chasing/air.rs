@@ -0,0 +1,293 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    ADDR, NEW_VALUE, OLD_VALUE, SORTED_ADDR, SORTED_NEW_VALUE, SORTED_OLD_VALUE, SORTED_TIME, TIME,
+    TIME_BITS, TIME_DELTA_BITS, TRACE_WIDTH,
+};
+use winterfell::{
+    math::{fields::f64::BaseElement, ExtensionOf, FieldElement},
+    Air, AirContext, Assertion, AuxTraceRandElements, ByteWriter, EvaluationFrame, ProofOptions,
+    Serializable, TraceInfo, TransitionConstraintDegree,
+};
+
+// PUBLIC INPUTS
+// ================================================================================================
+
+pub struct PublicInputs {
+    pub memory: Vec<BaseElement>,
+    pub start: BaseElement,
+    pub num_steps: usize,
+    pub result_address: BaseElement,
+    pub result: BaseElement,
+}
+
+impl Serializable for PublicInputs {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u64(self.num_steps as u64);
+        target.write(self.start);
+        target.write(self.result_address);
+        target.write(self.result);
+        for value in self.memory.iter() {
+            target.write(*value);
+        }
+    }
+}
+
+// CONSTRAINT DEGREES
+// ================================================================================================
+
+/// Degrees of the transition constraints over the main trace segment, for a trace of
+/// `trace_length` rows.
+pub fn main_constraint_degrees(trace_length: usize) -> Vec<TransitionConstraintDegree> {
+    let mut degrees = vec![
+        // timestamps count the rows
+        TransitionConstraintDegree::new(1),
+        // a step reads the cell addressed by the value written in the row before it, except
+        // right after the last step; the rows are selected by periodic columns spanning the
+        // whole trace
+        TransitionConstraintDegree::with_cycles(1, vec![trace_length]),
+        // a step writes the value read plus the address, reduced modulo the memory size
+        TransitionConstraintDegree::with_cycles(2, vec![trace_length]),
+        // the output row reads without writing
+        TransitionConstraintDegree::with_cycles(1, vec![trace_length]),
+        // sorted addresses increase by 0 or 1
+        TransitionConstraintDegree::new(2),
+        // an access finds the value written by the access before it to the same cell
+        TransitionConstraintDegree::new(2),
+        // timestamps of the same cell increase
+        TransitionConstraintDegree::new(2),
+        // the first access to a cell is the initial write, whose timestamp is the address
+        TransitionConstraintDegree::new(2),
+    ];
+    // the timestamp differences are decomposed into bits
+    degrees.extend((0..TIME_BITS).map(|_| TransitionConstraintDegree::new(2)));
+    degrees
+}
+
+/// Degrees of the transition constraints over the auxiliary trace segment.
+pub fn aux_constraint_degrees() -> Vec<TransitionConstraintDegree> {
+    // each access multiplies the permutation product by its term in both lists
+    vec![TransitionConstraintDegree::new(2)]
+}
+
+// POINTER CHASING AIR
+// ================================================================================================
+
+pub struct PointerChasingAir {
+    context: AirContext<BaseElement>,
+    memory: Vec<BaseElement>,
+    start: BaseElement,
+    num_steps: usize,
+    result_address: BaseElement,
+    result: BaseElement,
+}
+
+impl Air for PointerChasingAir {
+    type BaseField = BaseElement;
+    type PublicInputs = PublicInputs;
+
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+    fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        assert_eq!(TRACE_WIDTH, trace_info.layout().main_trace_width());
+        let trace_length = trace_info.length();
+        assert!(
+            pub_inputs.memory.len() + pub_inputs.num_steps + 2 < trace_length,
+            "trace is too short for the memory size and number of steps"
+        );
+        assert!(
+            trace_length <= 1 << TIME_BITS,
+            "trace is too long for the timestamp differences to fit in {} bits",
+            TIME_BITS
+        );
+
+        let main_degrees = main_constraint_degrees(trace_length);
+        let aux_degrees = aux_constraint_degrees();
+
+        // an address and a value per memory cell, the first timestamp, the start address, the
+        // output address and value, and the first and last sorted addresses and first sorted
+        // timestamp
+        let num_main_assertions = 2 * pub_inputs.memory.len() + 7;
+        let num_aux_assertions = 2;
+
+        PointerChasingAir {
+            context: AirContext::new_multi_segment(
+                trace_info,
+                main_degrees,
+                aux_degrees,
+                num_main_assertions,
+                num_aux_assertions,
+                options,
+            ),
+            memory: pub_inputs.memory,
+            start: pub_inputs.start,
+            num_steps: pub_inputs.num_steps,
+            result_address: pub_inputs.result_address,
+            result: pub_inputs.result,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let current = frame.current();
+        let next = frame.next();
+        debug_assert_eq!(TRACE_WIDTH, current.len());
+        debug_assert_eq!(TRACE_WIDTH, next.len());
+
+        let chain_flag = periodic_values[0];
+        let step_flag = periodic_values[1];
+        let read_flag = periodic_values[2];
+        let memory_size = E::from(BaseElement::new(self.memory.len() as u64));
+
+        result[0] = next[TIME] - current[TIME] - E::ONE;
+        result[1] = chain_flag * (next[ADDR] - current[NEW_VALUE]);
+        // the sum is either below the memory size or reduced by it once
+        let carry = current[OLD_VALUE] + current[ADDR] - current[NEW_VALUE];
+        result[2] = step_flag * carry * (carry - memory_size);
+        result[3] = read_flag * (current[NEW_VALUE] - current[OLD_VALUE]);
+
+        let delta = next[SORTED_ADDR] - current[SORTED_ADDR];
+        let same_cell = E::ONE - delta;
+        let mut time_delta = E::ZERO;
+        let mut power = E::ONE;
+        for bit in current[TIME_DELTA_BITS..TIME_DELTA_BITS + TIME_BITS].iter() {
+            time_delta += *bit * power;
+            power = power + power;
+        }
+        result[4] = delta * (delta - E::ONE);
+        result[5] = same_cell * (next[SORTED_OLD_VALUE] - current[SORTED_NEW_VALUE]);
+        result[6] = same_cell * (next[SORTED_TIME] - current[SORTED_TIME] - E::ONE - time_delta);
+        result[7] = delta * (next[SORTED_TIME] - next[SORTED_ADDR]);
+        for (i, bit) in current[TIME_DELTA_BITS..TIME_DELTA_BITS + TIME_BITS]
+            .iter()
+            .enumerate()
+        {
+            result[8 + i] = *bit * (*bit - E::ONE);
+        }
+    }
+
+    fn evaluate_aux_transition<F, E>(
+        &self,
+        main_frame: &EvaluationFrame<F>,
+        aux_frame: &EvaluationFrame<E>,
+        _periodic_values: &[F],
+        aux_rand_elements: &AuxTraceRandElements<E>,
+        result: &mut [E],
+    ) where
+        F: FieldElement<BaseField = Self::BaseField>,
+        E: FieldElement<BaseField = Self::BaseField> + ExtensionOf<F>,
+    {
+        let main_current = main_frame.current();
+        let aux_current = aux_frame.current();
+        let aux_next = aux_frame.next();
+
+        let random_elements = aux_rand_elements.get_segment_elements(0);
+        let unsorted = combine_access(
+            main_current,
+            [ADDR, TIME, OLD_VALUE, NEW_VALUE],
+            random_elements,
+        );
+        let sorted = combine_access(
+            main_current,
+            [SORTED_ADDR, SORTED_TIME, SORTED_OLD_VALUE, SORTED_NEW_VALUE],
+            random_elements,
+        );
+
+        result[0] = aux_next[0] * sorted - aux_current[0] * unsorted;
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let memory_size = self.memory.len();
+        let read_step = memory_size + self.num_steps;
+        let last_step = self.trace_length() - 1;
+
+        let mut assertions = Vec::with_capacity(2 * memory_size + 7);
+        for (address, value) in self.memory.iter().enumerate() {
+            assertions.push(Assertion::single(
+                ADDR,
+                address,
+                BaseElement::new(address as u64),
+            ));
+            assertions.push(Assertion::single(NEW_VALUE, address, *value));
+        }
+        assertions.push(Assertion::single(TIME, 0, BaseElement::ZERO));
+        assertions.push(Assertion::single(ADDR, memory_size, self.start));
+        assertions.push(Assertion::single(ADDR, read_step, self.result_address));
+        assertions.push(Assertion::single(NEW_VALUE, read_step, self.result));
+        assertions.push(Assertion::single(SORTED_ADDR, 0, BaseElement::ZERO));
+        assertions.push(Assertion::single(SORTED_TIME, 0, BaseElement::ZERO));
+        assertions.push(Assertion::single(
+            SORTED_ADDR,
+            last_step,
+            BaseElement::new(memory_size as u64 - 1),
+        ));
+        assertions
+    }
+
+    fn get_aux_assertions<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        _aux_rand_elements: &AuxTraceRandElements<E>,
+    ) -> Vec<Assertion<E>> {
+        let last_step = self.trace_length() - 1;
+        vec![
+            Assertion::single(0, 0, E::ONE),
+            Assertion::single(0, last_step, E::ONE),
+        ]
+    }
+
+    /// Flags for the rows whose address is the value written in the row before, the step rows,
+    /// and the row reading the output cell. The row after the output row takes one more step
+    /// from the value read, which keeps that value below the memory size.
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let memory_size = self.memory.len();
+        let read_step = memory_size + self.num_steps;
+        let trace_length = self.trace_length();
+        let flag = |set: bool| {
+            if set {
+                BaseElement::ONE
+            } else {
+                BaseElement::ZERO
+            }
+        };
+
+        let chain_flags = (0..trace_length)
+            .map(|step| {
+                flag(step >= memory_size && step < trace_length - 1 && step != read_step - 1)
+            })
+            .collect();
+        let step_flags = (0..trace_length)
+            .map(|step| flag(step >= memory_size && step != read_step))
+            .collect();
+        let read_flags = (0..trace_length)
+            .map(|step| flag(step == read_step))
+            .collect();
+        vec![chain_flags, step_flags, read_flags]
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Random linear combination of the (address, timestamp, old value, new value) access held in
+/// `columns` of `row`, subtracted from the first random element.
+fn combine_access<F, E>(row: &[F], columns: [usize; 4], random_elements: &[E]) -> E
+where
+    F: FieldElement,
+    E: FieldElement + ExtensionOf<F>,
+{
+    random_elements[0]
+        - E::from(row[columns[0]])
+        - random_elements[1].mul_base(row[columns[1]])
+        - random_elements[2].mul_base(row[columns[2]])
+        - random_elements[3].mul_base(row[columns[3]])
+}
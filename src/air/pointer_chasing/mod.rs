@@ -0,0 +1,380 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Pointer chasing over a read-write memory, as in the jsnark pointer chasing generator, proven
+//! with a randomized AIR (RAP).
+//!
+//! Cell `i` of a memory of `M` cells starts out holding `3i mod M`, and the two program inputs are
+//! added to cells 0 and `3^-1 mod M`. From the pointer `M - 1`, every step adds the pointer to the
+//! cell it points to, writes the sum modulo `M` back to the cell and follows it as the next
+//! pointer. The output is the final value of the cell after the one the second input is added
+//! to.
+//!
+//! Every row of the trace is one memory access: the first `M` rows write the initial memory, the
+//! next rows are the steps, followed by one row which reads the output cell and more steps to fill
+//! the trace. Each row records the address, a timestamp, and the values of the cell before and
+//! after the access. Next to these, the trace keeps the same accesses sorted by address and then
+//! timestamp, and an auxiliary column accumulates a permutation product which shows both lists
+//! hold the same accesses. In the sorted list, every access must find the value left by the
+//! access before it to the same cell, and the timestamps of a cell must increase, which is shown
+//! by decomposing their differences into bits.
+
+use std::time::Instant;
+
+use examples::Example;
+use log::debug;
+use winterfell::{
+    math::{fields::f64::BaseElement, FieldElement},
+    ProofOptions, Prover, StarkProof, Trace, TransitionConstraintDegree, VerifierError,
+};
+
+mod air;
+use air::{aux_constraint_degrees, main_constraint_degrees, PointerChasingAir, PublicInputs};
+
+mod prover;
+use prover::PointerChasingProver;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of bits the difference of two timestamps of the same cell is decomposed into; traces
+/// may have up to 2^TIME_BITS rows.
+const TIME_BITS: usize = 16;
+
+const TRACE_WIDTH: usize = 8 + TIME_BITS;
+
+// Column layout of the main trace segment.
+const ADDR: usize = 0;
+const TIME: usize = 1;
+const OLD_VALUE: usize = 2;
+const NEW_VALUE: usize = 3;
+const SORTED_ADDR: usize = 4;
+const SORTED_TIME: usize = 5;
+const SORTED_OLD_VALUE: usize = 6;
+const SORTED_NEW_VALUE: usize = 7;
+const TIME_DELTA_BITS: usize = 8;
+
+// Cell i of the memory starts out holding (MEM_MULTIPLIER * i) mod memory_size, as in the jsnark
+// generator.
+const MEM_MULTIPLIER: u64 = 3;
+
+/// The program inputs `init1` and `init2`, as in the `.in` files of the jsnark fixtures.
+pub const CHASE_INPUTS: [u64; 2] = [1, 1];
+
+// POINTER CHASING EXAMPLE
+// ================================================================================================
+
+pub struct PointerChasingExample {
+    options: ProofOptions,
+    memory: Vec<BaseElement>,
+    start: BaseElement,
+    num_steps: usize,
+    result_address: BaseElement,
+    result: BaseElement,
+}
+
+impl PointerChasingExample {
+    pub fn new(memory_size: usize, num_steps: usize, options: ProofOptions) -> Self {
+        assert!(
+            memory_size.is_power_of_two() && memory_size > 1,
+            "memory size must be a power of two greater than 1"
+        );
+        assert!(num_steps > 0, "number of steps must be greater than 0");
+
+        let memory = build_memory(memory_size, CHASE_INPUTS);
+        let start = start_address(memory_size as u64);
+        let result_address = result_address(memory_size as u64);
+
+        // compute the final value of the output cell
+        let now = Instant::now();
+        let final_memory = compute_pointer_chase(&memory, start, num_steps);
+        let result = BaseElement::new(final_memory[result_address as usize]);
+        debug!(
+            "Computed a chase of {} steps over {} memory cells in {} ms",
+            num_steps,
+            memory_size,
+            now.elapsed().as_millis(),
+        );
+
+        PointerChasingExample {
+            options,
+            memory: memory.into_iter().map(BaseElement::new).collect(),
+            start: BaseElement::new(start),
+            num_steps,
+            result_address: BaseElement::new(result_address),
+            result,
+        }
+    }
+}
+
+// EXAMPLE IMPLEMENTATION
+// ================================================================================================
+
+impl Example for PointerChasingExample {
+    fn prove(&self) -> StarkProof {
+        debug!(
+            "Generating proof for a chase of {} steps over {} memory cells\n\
+            ---------------------",
+            self.num_steps,
+            self.memory.len(),
+        );
+
+        // create a prover
+        let prover =
+            PointerChasingProver::new(self.options.clone(), self.memory.clone(), self.num_steps);
+
+        // generate execution trace
+        let now = Instant::now();
+        let trace = prover.build_trace(self.start);
+        let trace_width = trace.width();
+        let trace_length = trace.length();
+        debug!(
+            "Generated execution trace of {} registers and 2^{} steps in {} ms",
+            trace_width,
+            trace_length.trailing_zeros(),
+            now.elapsed().as_millis()
+        );
+
+        // generate the proof
+        prover.prove(trace).unwrap()
+    }
+
+    fn verify(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = PublicInputs {
+            memory: self.memory.clone(),
+            start: self.start,
+            num_steps: self.num_steps,
+            result_address: self.result_address,
+            result: self.result,
+        };
+        winterfell::verify::<PointerChasingAir>(proof, pub_inputs)
+    }
+
+    fn verify_with_wrong_inputs(&self, proof: StarkProof) -> Result<(), VerifierError> {
+        let pub_inputs = PublicInputs {
+            memory: self.memory.clone(),
+            start: self.start,
+            num_steps: self.num_steps,
+            result_address: self.result_address,
+            result: self.result + BaseElement::ONE,
+        };
+        winterfell::verify::<PointerChasingAir>(proof, pub_inputs)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Initial contents of a memory of `memory_size` cells, with `inputs` added to the cells at
+/// [input_addresses].
+pub fn build_memory(memory_size: usize, inputs: [u64; 2]) -> Vec<u64> {
+    let mut memory: Vec<u64> = (0..memory_size as u64)
+        .map(|address| memory_cell(address, memory_size as u64))
+        .collect();
+    for (address, input) in input_addresses(memory_size as u64).iter().zip(inputs) {
+        memory[*address as usize] += input;
+    }
+    assert!(
+        memory.iter().all(|value| *value < memory_size as u64),
+        "inputs must keep every memory cell below the memory size"
+    );
+    memory
+}
+
+/// Value of the cell at `address` in a memory of `memory_size` cells, before the inputs are
+/// added. The R1CS circuit starts from the same memory.
+pub fn memory_cell(address: u64, memory_size: u64) -> u64 {
+    (MEM_MULTIPLIER * address) % memory_size
+}
+
+/// Addresses of the cells the two inputs are added to: 0, and the cell which starts out holding
+/// 1.
+pub fn input_addresses(memory_size: u64) -> [u64; 2] {
+    let one = (0..memory_size)
+        .find(|address| memory_cell(*address, memory_size) == 1)
+        .expect("3 is invertible modulo a power of two");
+    [0, one]
+}
+
+/// Address the chase starts at: the last cell.
+pub fn start_address(memory_size: u64) -> u64 {
+    memory_size - 1
+}
+
+/// Address of the cell whose final value is the output: the one after the cell the second input
+/// is added to.
+pub fn result_address(memory_size: u64) -> u64 {
+    (input_addresses(memory_size)[1] + 1) % memory_size
+}
+
+/// Applies `num_steps` steps of the chase to `memory` starting at address `start`, and returns
+/// the memory after the last step.
+pub fn compute_pointer_chase(memory: &[u64], start: u64, num_steps: usize) -> Vec<u64> {
+    let memory_size = memory.len() as u64;
+    let mut memory = memory.to_vec();
+    let mut pointer = start;
+    for _ in 0..num_steps {
+        let value = (memory[pointer as usize] + pointer) % memory_size;
+        memory[pointer as usize] = value;
+        pointer = value;
+    }
+    memory
+}
+
+/// Final value of the output cell after `num_steps` steps of the chase over a memory of
+/// `memory_size` cells, with the inputs of the fixtures: the result the example proves.
+pub fn chase_result(memory_size: usize, num_steps: usize) -> BaseElement {
+    let memory = build_memory(memory_size, CHASE_INPUTS);
+    let memory = compute_pointer_chase(&memory, start_address(memory_size as u64), num_steps);
+    BaseElement::new(memory[result_address(memory_size as u64) as usize])
+}
+
+/// Degrees of the transition constraints of the AIR for a chase of `num_steps` steps over a
+/// memory of `memory_size` cells, over the main and then the auxiliary trace segment. The AIR is
+/// built with these, and the registry derives the blowup factor of the `sorted` layout from them.
+pub fn transition_constraint_degrees(
+    memory_size: usize,
+    num_steps: usize,
+) -> Vec<TransitionConstraintDegree> {
+    let trace_length = get_trace_length(memory_size, num_steps);
+    let mut degrees = main_constraint_degrees(trace_length);
+    degrees.extend(aux_constraint_degrees());
+    degrees
+}
+
+/// Number of rows needed to write `memory_size` cells, take `num_steps` steps, read the output
+/// cell and take one more step, which bounds the value read. One spare row is kept at the end
+/// because the permutation product only covers the rows before the last.
+fn get_trace_length(memory_size: usize, num_steps: usize) -> usize {
+    (memory_size + num_steps + 3).next_power_of_two().max(8)
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use winterfell::math::StarkField;
+
+    use super::prover::RapTraceTable;
+    use super::*;
+    use crate::air::AirOptions;
+    use crate::binary::load_instance;
+    use crate::registry::{ProgramTag, PROGRAM_REGISTRY};
+
+    const MEMORY_SIZE: usize = 16;
+    const NUM_STEPS: usize = 64;
+
+    const SORTED_COLUMNS: [usize; 4] =
+        [SORTED_ADDR, SORTED_TIME, SORTED_OLD_VALUE, SORTED_NEW_VALUE];
+
+    fn proof_options() -> ProofOptions {
+        AirOptions {
+            grinding_factor: 0,
+            ..AirOptions::default()
+        }
+        .to_proof_options()
+    }
+
+    fn prover() -> PointerChasingProver {
+        let memory = build_memory(MEMORY_SIZE, CHASE_INPUTS)
+            .into_iter()
+            .map(BaseElement::new)
+            .collect();
+        PointerChasingProver::new(proof_options(), memory, NUM_STEPS)
+    }
+
+    fn honest_trace() -> RapTraceTable<BaseElement> {
+        prover().build_trace(BaseElement::new(start_address(MEMORY_SIZE as u64)))
+    }
+
+    /// Whether a proof of `trace` verifies against the true public inputs of the chase. In debug
+    /// builds the prover checks the trace against the AIR and panics on a forged one, so a panic
+    /// counts as a rejection.
+    fn verifies(trace: RapTraceTable<BaseElement>) -> bool {
+        let pub_inputs = PublicInputs {
+            memory: build_memory(MEMORY_SIZE, CHASE_INPUTS)
+                .into_iter()
+                .map(BaseElement::new)
+                .collect(),
+            start: BaseElement::new(start_address(MEMORY_SIZE as u64)),
+            num_steps: NUM_STEPS,
+            result_address: BaseElement::new(result_address(MEMORY_SIZE as u64)),
+            result: chase_result(MEMORY_SIZE, NUM_STEPS),
+        };
+        let prover = prover();
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let proof = prover.prove(trace).unwrap();
+            winterfell::verify::<PointerChasingAir>(proof, pub_inputs).is_ok()
+        }))
+        .unwrap_or(false)
+    }
+
+    /// Row of the sorted columns holding the access in `step` of the unsorted ones.
+    fn sorted_row(trace: &RapTraceTable<BaseElement>, step: usize) -> usize {
+        (0..trace.length())
+            .find(|row| {
+                trace.get(SORTED_ADDR, *row) == trace.get(ADDR, step)
+                    && trace.get(SORTED_TIME, *row) == trace.get(TIME, step)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn result_matches_the_jsnark_fixtures() {
+        let spec = PROGRAM_REGISTRY.get(ProgramTag::PtrChase);
+        for iterations in spec.iterations.clone().unwrap() {
+            for size in spec.r1cs_sizes.clone() {
+                let path = spec.r1cs_path(size, Some(iterations)).unwrap();
+                let instance = load_instance::<BaseElement>(&path, None).unwrap();
+                assert_eq!(
+                    instance.public_io().outputs,
+                    vec![chase_result(1 << size, 1 << iterations)],
+                    "{} disagrees with the AIR",
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn honest_trace_verifies() {
+        assert!(verifies(honest_trace()));
+    }
+
+    #[test]
+    fn rejects_unsorted_accesses() {
+        // the sorted columns still hold the same accesses, so only the order is wrong
+        let mut trace = honest_trace();
+        let other = (1..trace.length())
+            .find(|row| trace.get(SORTED_ADDR, *row) != trace.get(SORTED_ADDR, 0))
+            .unwrap();
+        for column in SORTED_COLUMNS {
+            let (first, second) = (trace.get(column, 0), trace.get(column, other));
+            trace.set(column, 0, second);
+            trace.set(column, other, first);
+        }
+        assert!(!verifies(trace));
+    }
+
+    #[test]
+    fn rejects_a_read_of_a_value_never_written() {
+        // a step which reads its cell's value plus the memory size writes the same value and
+        // takes the same next step, so only the memory check catches it
+        let mut trace = honest_trace();
+        let memory_size = MEMORY_SIZE as u64;
+        let step = (MEMORY_SIZE..MEMORY_SIZE + NUM_STEPS)
+            .find(|step| {
+                trace.get(OLD_VALUE, *step).as_int() + trace.get(ADDR, *step).as_int() < memory_size
+            })
+            .unwrap();
+        let row = sorted_row(&trace, step);
+        let forged = trace.get(OLD_VALUE, step) + BaseElement::new(memory_size);
+        trace.set(OLD_VALUE, step, forged);
+        trace.set(SORTED_OLD_VALUE, row, forged);
+        assert!(!verifies(trace));
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use super::{
+    get_trace_length, result_address, PointerChasingAir, PublicInputs, ADDR, NEW_VALUE, OLD_VALUE,
+    SORTED_ADDR, SORTED_NEW_VALUE, SORTED_OLD_VALUE, SORTED_TIME, TIME, TIME_BITS,
+    TIME_DELTA_BITS, TRACE_WIDTH,
+};
+use winterfell::{
+    math::{batch_inversion, fields::f64::BaseElement, FieldElement, StarkField},
+    EvaluationFrame, Matrix, ProofOptions, Prover, Trace, TraceLayout,
+};
+
+// POINTER CHASING PROVER
+// ================================================================================================
+
+pub struct PointerChasingProver {
+    options: ProofOptions,
+    memory: Vec<BaseElement>,
+    num_steps: usize,
+}
+
+impl PointerChasingProver {
+    pub fn new(options: ProofOptions, memory: Vec<BaseElement>, num_steps: usize) -> Self {
+        Self {
+            options,
+            memory,
+            num_steps,
+        }
+    }
+
+    /// Builds the main trace segment: the initial memory writes, the steps of the chase starting
+    /// at `start`, the read of the output cell, and all accesses sorted by address and timestamp
+    /// next to the bits of the timestamp differences.
+    pub fn build_trace(&self, start: BaseElement) -> RapTraceTable<BaseElement> {
+        let memory_size = self.memory.len();
+        let trace_length = get_trace_length(memory_size, self.num_steps);
+        let read_step = memory_size + self.num_steps;
+        let result_address = result_address(memory_size as u64);
+
+        // accesses as (address, timestamp, old value, new value); the timestamp is the row
+        let mut memory: Vec<u64> = self.memory.iter().map(|value| value.as_int()).collect();
+        let mut accesses: Vec<[u64; 4]> = Vec::with_capacity(trace_length);
+        for (address, value) in memory.iter().enumerate() {
+            accesses.push([address as u64, address as u64, 0, *value]);
+        }
+
+        // keep stepping past the output row to fill the trace; the extra steps are valid, so
+        // they do not disturb the memory check
+        let mut pointer = start.as_int();
+        while accesses.len() < trace_length {
+            let time = accesses.len() as u64;
+            if accesses.len() == read_step {
+                let value = memory[result_address as usize];
+                accesses.push([result_address, time, value, value]);
+                pointer = value;
+            } else {
+                let old = memory[pointer as usize];
+                let new = (old + pointer) % memory_size as u64;
+                accesses.push([pointer, time, old, new]);
+                memory[pointer as usize] = new;
+                pointer = new;
+            }
+        }
+
+        // the permutation product covers all rows but the last, so sort those and end with an
+        // access to the last cell which continues the sorted list
+        let mut sorted = accesses[..trace_length - 1].to_vec();
+        sorted.sort_unstable();
+        let [_, last_time, _, last_value] = *sorted.last().unwrap();
+        sorted.push([memory_size as u64 - 1, last_time + 1, last_value, last_value]);
+
+        // the difference between consecutive timestamps of a cell, minus one, in bits; the bits
+        // of the last row are not constrained, and setting them keeps every bit column from
+        // being constant, which would fail the constraint degree checks of debug builds
+        let mut time_delta_bits = vec![Vec::with_capacity(trace_length); TIME_BITS];
+        for (step, access) in sorted.iter().enumerate() {
+            let time_delta = match sorted.get(step + 1) {
+                Some(next) if next[0] == access[0] => next[1] - access[1] - 1,
+                Some(_) => 0,
+                None => (1 << TIME_BITS) - 1,
+            };
+            for (bit, column) in time_delta_bits.iter_mut().enumerate() {
+                column.push(BaseElement::new((time_delta >> bit) & 1));
+            }
+        }
+
+        let column = |accesses: &[[u64; 4]], field: usize| -> Vec<BaseElement> {
+            accesses
+                .iter()
+                .map(|access| BaseElement::new(access[field]))
+                .collect()
+        };
+        let mut columns = vec![
+            column(&accesses, 0),
+            column(&accesses, 1),
+            column(&accesses, 2),
+            column(&accesses, 3),
+            column(&sorted, 0),
+            column(&sorted, 1),
+            column(&sorted, 2),
+            column(&sorted, 3),
+        ];
+        debug_assert_eq!(TIME_DELTA_BITS, columns.len());
+        columns.extend(time_delta_bits);
+
+        RapTraceTable::init(columns)
+    }
+}
+
+impl Prover for PointerChasingProver {
+    type BaseField = BaseElement;
+    type Air = PointerChasingAir;
+    type Trace = RapTraceTable<BaseElement>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> PublicInputs {
+        let memory_size = self.memory.len();
+        let read_step = memory_size + self.num_steps;
+        PublicInputs {
+            memory: self.memory.clone(),
+            start: trace.get(ADDR, memory_size),
+            num_steps: self.num_steps,
+            result_address: trace.get(ADDR, read_step),
+            result: trace.get(NEW_VALUE, read_step),
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+}
+
+// RAP TRACE TABLE
+// ================================================================================================
+
+/// Execution trace with a single auxiliary column holding the running permutation product of the
+/// unsorted and sorted memory accesses.
+pub struct RapTraceTable<B: StarkField> {
+    layout: TraceLayout,
+    trace: Matrix<B>,
+    meta: Vec<u8>,
+}
+
+impl<B: StarkField> RapTraceTable<B> {
+    pub fn init(columns: Vec<Vec<B>>) -> Self {
+        assert_eq!(
+            TRACE_WIDTH,
+            columns.len(),
+            "unexpected number of trace columns"
+        );
+        let trace_length = columns[0].len();
+        assert!(
+            trace_length.is_power_of_two(),
+            "trace length must be a power of two"
+        );
+        for column in columns.iter() {
+            assert_eq!(
+                trace_length,
+                column.len(),
+                "all columns must have the same length"
+            );
+        }
+
+        Self {
+            // one auxiliary column built from four random elements
+            layout: TraceLayout::new(TRACE_WIDTH, [1], [4]),
+            trace: Matrix::new(columns),
+            meta: vec![],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.trace.num_cols()
+    }
+
+    pub fn get(&self, column: usize, step: usize) -> B {
+        self.trace.get(column, step)
+    }
+
+    /// Overwrites one cell of the main segment, to check that forged traces are rejected.
+    #[cfg(test)]
+    pub fn set(&mut self, column: usize, step: usize, value: B) {
+        self.trace.set(column, step, value);
+    }
+}
+
+impl<B: StarkField> Trace for RapTraceTable<B> {
+    type BaseField = B;
+
+    fn layout(&self) -> &TraceLayout {
+        &self.layout
+    }
+
+    fn length(&self) -> usize {
+        self.trace.num_rows()
+    }
+
+    fn meta(&self) -> &[u8] {
+        &self.meta
+    }
+
+    fn main_segment(&self) -> &Matrix<B> {
+        &self.trace
+    }
+
+    fn build_aux_segment<E>(
+        &mut self,
+        aux_segments: &[Matrix<E>],
+        rand_elements: &[E],
+    ) -> Option<Matrix<E>>
+    where
+        E: FieldElement<BaseField = Self::BaseField>,
+    {
+        // there is only one auxiliary segment
+        if !aux_segments.is_empty() {
+            return None;
+        }
+
+        let trace_length = self.length();
+        let combine_access = |step: usize, columns: [usize; 4]| {
+            rand_elements[0]
+                - E::from(self.trace.get(columns[0], step))
+                - rand_elements[1].mul_base(self.trace.get(columns[1], step))
+                - rand_elements[2].mul_base(self.trace.get(columns[2], step))
+                - rand_elements[3].mul_base(self.trace.get(columns[3], step))
+        };
+
+        let mut numerators = Vec::with_capacity(trace_length - 1);
+        let mut denominators = Vec::with_capacity(trace_length - 1);
+        for step in 0..trace_length - 1 {
+            numerators.push(combine_access(step, [ADDR, TIME, OLD_VALUE, NEW_VALUE]));
+            denominators.push(combine_access(
+                step,
+                [SORTED_ADDR, SORTED_TIME, SORTED_OLD_VALUE, SORTED_NEW_VALUE],
+            ));
+        }
+        let inv_denominators = batch_inversion(&denominators);
+
+        let mut product = Vec::with_capacity(trace_length);
+        product.push(E::ONE);
+        for step in 0..trace_length - 1 {
+            product.push(product[step] * numerators[step] * inv_denominators[step]);
+        }
+        debug_assert_eq!(E::ONE, product[trace_length - 1]);
+
+        Some(Matrix::new(vec![product]))
+    }
+
+    fn read_main_frame(&self, row_idx: usize, frame: &mut EvaluationFrame<B>) {
+        let next_row_idx = (row_idx + 1) % self.length();
+        self.trace.read_row_into(row_idx, frame.current_mut());
+        self.trace.read_row_into(next_row_idx, frame.next_mut());
+    }
+}
@@ -0,0 +1,503 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! A compact binary format for an R1CS instance together with its witness.
+//!
+//! All integers are little-endian. A file is laid out as
+//!
+//! | field              | encoding                                              |
+//! |--------------------|-------------------------------------------------------|
+//! | magic              | `b"R1CB"`                                             |
+//! | version            | u16, currently 2                                      |
+//! | field modulus      | u8 length, then the modulus as little-endian bytes    |
+//! | element size       | u8, bytes per serialized field element                |
+//! | columns, rows      | u64, u64                                              |
+//! | A, B, C            | per matrix: u64 non-zeros, rows + 1 u64 row offsets,  |
+//! |                    | then (u32 column, element) per non-zero               |
+//! | witness            | u64 length, then the elements                         |
+//! | public wires       | u64 inputs, u64 outputs, then their u64 wire ids      |
+//! | checksum           | u64 FNV-1a of every preceding byte                    |
+//!
+//! Files are read and written in a single streaming pass; the checksum is verified before the
+//! instance is returned. Version 1 files, which predate the public wires, are not read;
+//! [load_instance] falls back to the jsnark files for them.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use thiserror::Error;
+use winter_math::StarkField;
+use winter_utils::{Deserializable, Serializable, SliceReader};
+
+use crate::jsnark::{parse_arith_file, parse_arith_file_with_inputs, read_wires_file, JsnarkError};
+use crate::public::{PublicIo, PublicWires};
+use crate::sparse::{SparseMatrix, SparseR1CS};
+
+pub const MAGIC: [u8; 4] = *b"R1CB";
+pub const VERSION: u16 = 2;
+/// Extension of binary instance files, next to the `.arith`, `.wires` and `.in` fixtures.
+pub const EXTENSION: &str = "r1bin";
+/// Most matrix entries reserved up front when reading; the lengths in a file are not trusted
+/// before its checksum is verified, so larger matrices grow as their entries are read.
+const MAX_RESERVED_ENTRIES: usize = 1 << 20;
+
+#[derive(Debug, Error)]
+pub enum BinaryFormatError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Jsnark(#[from] JsnarkError),
+    #[error("Not a binary R1CS file")]
+    BadMagic,
+    #[error("Unsupported binary R1CS version {0}, expected {VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("Instance was built for field modulus 0x{found}, expected 0x{expected}")]
+    FieldMismatch { expected: String, found: String },
+    #[error("Checksum mismatch: file is corrupted")]
+    ChecksumMismatch,
+    #[error("Malformed binary R1CS file: {0}")]
+    Malformed(String),
+}
+
+/// An R1CS instance, a satisfying assignment of its wires, and which of them are public.
+#[derive(Debug, Clone)]
+pub struct R1csInstance<B: StarkField> {
+    pub r1cs: SparseR1CS<B>,
+    pub wires: Vec<B>,
+    pub public_wires: PublicWires,
+}
+
+impl<B: StarkField> R1csInstance<B> {
+    /// The public inputs and outputs of the assignment, which proofs are bound to.
+    pub fn public_io(&self) -> PublicIo<B> {
+        self.public_wires.values(&self.wires)
+    }
+
+    /// Moves the public wires of the instance to the front of its variables, where Fractal reads
+    /// its public input, see [PublicWires::expose], and returns their values. The instance must be
+    /// indexed after this for its proofs to be bound to them.
+    pub fn expose_public_io(&mut self) -> PublicIo<B> {
+        let public = self.public_io();
+        self.public_wires = self.public_wires.expose(&mut self.r1cs, &mut self.wires);
+        public
+    }
+}
+
+// LOADING
+// ================================================================================================
+
+/// Path of the binary instance for a fixture path without extension.
+pub fn binary_path(fixture_path: &str) -> String {
+    format!("{fixture_path}.{EXTENSION}")
+}
+
+/// Loads the instance at `fixture_path` (without extension). With an `inputs_file`, the witness
+/// is computed from those inputs. Otherwise the binary file is preferred when one exists and was
+/// converted for the field `B` by this version, then the `.arith` file is parsed together with its `.wires` file,
+/// or its `.in` file when the fixture ships no `.wires`.
+pub fn load_instance<B: StarkField>(
+    fixture_path: &str,
+    inputs_file: Option<&str>,
+) -> Result<R1csInstance<B>, BinaryFormatError> {
+    let arith_file = format!("{fixture_path}.arith");
+    if let Some(inputs_file) = inputs_file {
+        return convert_jsnark_files(&arith_file, inputs_file);
+    }
+    let binary_file = binary_path(fixture_path);
+    let wires_file = format!("{fixture_path}.wires");
+    if Path::new(&binary_file).exists() {
+        match read_instance(&binary_file) {
+            Err(BinaryFormatError::FieldMismatch { .. })
+            | Err(BinaryFormatError::UnsupportedVersion(_)) => {}
+            result => return result,
+        }
+    }
+    if Path::new(&wires_file).exists() {
+        convert_jsnark_files(&arith_file, &wires_file)
+    } else {
+        convert_jsnark_files(&arith_file, &format!("{fixture_path}.in"))
+    }
+}
+
+/// Parses a jsnark `.arith` file into an instance. The assignment is read from `assignment_file`
+/// when it is a `.wires` file, and computed from it when it is an `.in` file.
+pub fn convert_jsnark_files<B: StarkField>(
+    arith_file: &str,
+    assignment_file: &str,
+) -> Result<R1csInstance<B>, BinaryFormatError> {
+    if assignment_file.ends_with(".in") {
+        let (r1cs, wires, public_wires) =
+            parse_arith_file_with_inputs::<B>(arith_file, assignment_file)?;
+        return Ok(R1csInstance {
+            r1cs,
+            wires,
+            public_wires,
+        });
+    }
+    let (r1cs, public_wires) = parse_arith_file::<B>(arith_file)?;
+    let wires = read_wires_file::<B>(assignment_file, r1cs.num_cols())?;
+    Ok(R1csInstance {
+        r1cs,
+        wires,
+        public_wires,
+    })
+}
+
+// WRITING
+// ================================================================================================
+
+pub fn write_instance<B: StarkField>(
+    path: &str,
+    instance: &R1csInstance<B>,
+) -> Result<(), BinaryFormatError> {
+    let mut writer = ChecksumWriter::new(BufWriter::new(File::create(path)?));
+    let r1cs = &instance.r1cs;
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    let modulus = B::get_modulus_le_bytes();
+    writer.write_all(&[modulus.len() as u8])?;
+    writer.write_all(&modulus)?;
+    writer.write_all(&[B::ELEMENT_BYTES as u8])?;
+    write_u64(&mut writer, r1cs.num_cols())?;
+    write_u64(&mut writer, r1cs.num_rows())?;
+
+    for matrix in [&r1cs.a, &r1cs.b, &r1cs.c] {
+        write_u64(&mut writer, matrix.num_non_zero())?;
+        for offset in matrix.row_offsets() {
+            write_u64(&mut writer, *offset)?;
+        }
+        for (col, value) in matrix.entries() {
+            let col = u32::try_from(*col).map_err(|_| {
+                BinaryFormatError::Malformed(format!("column {} does not fit in 32 bits", col))
+            })?;
+            writer.write_all(&col.to_le_bytes())?;
+            writer.write_all(&Serializable::to_bytes(value))?;
+        }
+    }
+
+    write_u64(&mut writer, instance.wires.len())?;
+    for value in instance.wires.iter() {
+        writer.write_all(&Serializable::to_bytes(value))?;
+    }
+
+    let public_wires = &instance.public_wires;
+    write_u64(&mut writer, public_wires.inputs.len())?;
+    write_u64(&mut writer, public_wires.outputs.len())?;
+    for wire in public_wires
+        .inputs
+        .iter()
+        .chain(public_wires.outputs.iter())
+    {
+        write_u64(&mut writer, *wire)?;
+    }
+
+    let checksum = writer.checksum();
+    let mut inner = writer.into_inner();
+    inner.write_all(&checksum.to_le_bytes())?;
+    inner.flush()?;
+    Ok(())
+}
+
+// READING
+// ================================================================================================
+
+pub fn read_instance<B: StarkField>(path: &str) -> Result<R1csInstance<B>, BinaryFormatError> {
+    let mut reader = ChecksumReader::new(BufReader::new(File::open(path)?));
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(BinaryFormatError::BadMagic);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != VERSION {
+        return Err(BinaryFormatError::UnsupportedVersion(version));
+    }
+
+    let mut modulus = vec![0u8; read_u8(&mut reader)? as usize];
+    reader.read_exact(&mut modulus)?;
+    let expected = B::get_modulus_le_bytes();
+    if modulus != expected {
+        return Err(BinaryFormatError::FieldMismatch {
+            expected: to_hex(&expected),
+            found: to_hex(&modulus),
+        });
+    }
+    let element_bytes = read_u8(&mut reader)? as usize;
+    if element_bytes != B::ELEMENT_BYTES {
+        return Err(BinaryFormatError::Malformed(format!(
+            "elements are {} bytes, expected {}",
+            element_bytes,
+            B::ELEMENT_BYTES
+        )));
+    }
+
+    let num_cols = read_u64(&mut reader)?;
+    let num_rows = read_u64(&mut reader)?;
+    let mut element = vec![0u8; element_bytes];
+    let mut matrices = Vec::with_capacity(3);
+    for name in ["A", "B", "C"] {
+        let num_non_zero = read_u64(&mut reader)?;
+        let row_offsets = (0..=num_rows)
+            .map(|_| read_u64(&mut reader))
+            .collect::<Result<Vec<usize>, _>>()?;
+        let mut entries = Vec::with_capacity(num_non_zero.min(MAX_RESERVED_ENTRIES));
+        for _ in 0..num_non_zero {
+            let mut col = [0u8; 4];
+            reader.read_exact(&mut col)?;
+            let col = u32::from_le_bytes(col) as usize;
+            if col >= num_cols {
+                return Err(BinaryFormatError::Malformed(format!(
+                    "matrix {} has an entry in column {}, but there are only {} columns",
+                    name, col, num_cols
+                )));
+            }
+            let value = read_element::<B, _>(&mut reader, &mut element)?;
+            entries.push((col, value));
+        }
+        let matrix = SparseMatrix::from_parts(name, row_offsets, entries)
+            .map_err(BinaryFormatError::Malformed)?;
+        matrices.push(matrix);
+    }
+
+    let num_wires = read_u64(&mut reader)?;
+    if num_wires != num_cols {
+        return Err(BinaryFormatError::Malformed(format!(
+            "witness has {} wires, expected one per column ({})",
+            num_wires, num_cols
+        )));
+    }
+    let wires = (0..num_wires)
+        .map(|_| read_element::<B, _>(&mut reader, &mut element))
+        .collect::<Result<Vec<B>, _>>()?;
+
+    let num_inputs = read_u64(&mut reader)?;
+    let num_outputs = read_u64(&mut reader)?;
+    let inputs = (0..num_inputs)
+        .map(|_| read_u64(&mut reader))
+        .collect::<Result<Vec<usize>, _>>()?;
+    let outputs = (0..num_outputs)
+        .map(|_| read_u64(&mut reader))
+        .collect::<Result<Vec<usize>, _>>()?;
+    if let Some(wire) = inputs.iter().chain(outputs.iter()).find(|wire| **wire >= num_cols) {
+        return Err(BinaryFormatError::Malformed(format!(
+            "public wire {} is out of range for {} wires",
+            wire, num_cols
+        )));
+    }
+
+    let checksum = reader.checksum();
+    let mut stored = [0u8; 8];
+    reader.into_inner().read_exact(&mut stored)?;
+    if u64::from_le_bytes(stored) != checksum {
+        return Err(BinaryFormatError::ChecksumMismatch);
+    }
+
+    let c = matrices.pop().unwrap();
+    let b = matrices.pop().unwrap();
+    let a = matrices.pop().unwrap();
+    Ok(R1csInstance {
+        r1cs: SparseR1CS::from_matrices(a, b, c, num_cols),
+        wires,
+        public_wires: PublicWires { inputs, outputs },
+    })
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+fn read_element<B: StarkField, R: Read>(
+    reader: &mut R,
+    buffer: &mut [u8],
+) -> Result<B, BinaryFormatError> {
+    reader.read_exact(buffer)?;
+    <B as Deserializable>::read_from(&mut SliceReader::new(buffer))
+        .map_err(|err| BinaryFormatError::Malformed(format!("invalid field element: {}", err)))
+}
+
+fn to_hex(le_bytes: &[u8]) -> String {
+    le_bytes
+        .iter()
+        .rev()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// CHECKSUM
+// ================================================================================================
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+pub(crate) struct ChecksumWriter<W: Write> {
+    inner: W,
+    hash: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        ChecksumWriter {
+            inner,
+            hash: FNV_OFFSET_BASIS,
+        }
+    }
+
+    pub(crate) fn checksum(&self) -> u64 {
+        self.hash
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hash = fnv1a(self.hash, &buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(crate) struct ChecksumReader<R: Read> {
+    inner: R,
+    hash: u64,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        ChecksumReader {
+            inner,
+            hash: FNV_OFFSET_BASIS,
+        }
+    }
+
+    pub(crate) fn checksum(&self) -> u64 {
+        self.hash
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hash = fnv1a(self.hash, &buf[..read]);
+        Ok(read)
+    }
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use winter_math::fields::f64::BaseElement;
+    use winter_math::FieldElement;
+
+    use super::*;
+
+    /// `x * x = y` with `x` public input and `y` public output, assigned `x = 3`.
+    fn square_instance() -> R1csInstance<BaseElement> {
+        let one = BaseElement::ONE;
+        let mut r1cs = SparseR1CS::new(3);
+        r1cs.push_constraint(&[(1, one)], &[(1, one)], &[(2, one)]);
+        R1csInstance {
+            r1cs,
+            wires: vec![one, BaseElement::new(3), BaseElement::new(9)],
+            public_wires: PublicWires {
+                inputs: vec![1],
+                outputs: vec![2],
+            },
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("binary-{}-{}.{}", name, std::process::id(), EXTENSION))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("round-trip");
+        let instance = square_instance();
+        write_instance(&path, &instance).unwrap();
+        let read = read_instance::<BaseElement>(&path);
+        fs::remove_file(&path).unwrap();
+
+        let read = read.unwrap();
+        assert_eq!(read.r1cs.num_rows(), instance.r1cs.num_rows());
+        assert_eq!(read.r1cs.num_cols(), instance.r1cs.num_cols());
+        assert_eq!(read.r1cs.fingerprint(), instance.r1cs.fingerprint());
+        assert_eq!(read.wires, instance.wires);
+        assert_eq!(read.public_wires, instance.public_wires);
+    }
+
+    #[test]
+    fn rejects_corrupted_checksum() {
+        let path = temp_path("checksum");
+        write_instance(&path, &square_instance()).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        // the low byte of the last wire, before the public wires and the checksum, so that the
+        // element stays valid and only the checksum can catch it
+        let last_wire = bytes.len() - 8 - 4 * 8 - 8;
+        bytes[last_wire] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        let read = read_instance::<BaseElement>(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(read, Err(BinaryFormatError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn rejects_column_out_of_range() {
+        let path = temp_path("column");
+        let mut instance = square_instance();
+        let (a, b, c) = (instance.r1cs.a, instance.r1cs.b, instance.r1cs.c);
+        instance.r1cs = SparseR1CS::from_matrices(a, b, c, 2);
+        write_instance(&path, &instance).unwrap();
+        let read = read_instance::<BaseElement>(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(read, Err(BinaryFormatError::Malformed(_))));
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Satisfiability checking of an R1CS instance before it is handed to the prover.
+//!
+//! The Fractal prover assumes `A·z ∘ B·z = C·z` and fails deep inside proof generation when it
+//! does not hold. [check_instance] evaluates every constraint against the wire assignment `z`
+//! instead, and [locate_constraints] maps the failing rows back to the `.arith` gates they were
+//! generated from, so a bad fixture can be told apart from a prover bug.
+//!
+//! [check_fixture_field] combines both with the range checks of the loader to tell whether a
+//! fixture can be proven over a given base field.
+
+use std::fmt;
+
+use thiserror::Error;
+use winter_math::StarkField;
+
+use crate::binary::{load_instance, BinaryFormatError};
+use crate::jsnark::{ArithReader, Gate, JsnarkError};
+use crate::sparse::SparseR1CS;
+
+#[derive(Debug, Error)]
+pub enum CheckError {
+    #[error("The assignment has {found} wires but the instance has {expected} columns")]
+    WitnessLength { expected: usize, found: usize },
+    #[error("Wire 0 must be the constant 1, found {0}")]
+    ConstantWire(String),
+    #[error(transparent)]
+    Load(#[from] BinaryFormatError),
+}
+
+/// A constraint `<a, z> * <b, z> = <c, z>` which does not hold.
+#[derive(Debug, Clone)]
+pub struct UnsatisfiedConstraint<B: StarkField> {
+    /// Row of the constraint in the A, B and C matrices.
+    pub row: usize,
+    pub a: B,
+    pub b: B,
+    pub c: B,
+    /// The wires the constraint reads, with their values, in column order.
+    pub wires: Vec<(usize, B)>,
+    /// The `.arith` gate which produced the constraint, once located.
+    pub gate: Option<Gate<B>>,
+}
+
+/// The outcome of checking an instance. Only the first `max_failures` unsatisfied constraints
+/// are kept, but all of them are counted.
+#[derive(Debug, Clone)]
+pub struct CheckReport<B: StarkField> {
+    pub num_constraints: usize,
+    pub num_unsatisfied: usize,
+    pub failures: Vec<UnsatisfiedConstraint<B>>,
+}
+
+impl<B: StarkField> CheckReport<B> {
+    pub fn is_satisfied(&self) -> bool {
+        self.num_unsatisfied == 0
+    }
+}
+
+// CHECKING
+// ================================================================================================
+
+/// Evaluates every constraint of `r1cs` against `wires`, keeping the first `max_failures`
+/// unsatisfied ones.
+pub fn check_instance<B: StarkField>(
+    r1cs: &SparseR1CS<B>,
+    wires: &[B],
+    max_failures: usize,
+) -> Result<CheckReport<B>, CheckError> {
+    if wires.len() < r1cs.num_cols() {
+        return Err(CheckError::WitnessLength {
+            expected: r1cs.num_cols(),
+            found: wires.len(),
+        });
+    }
+    if wires.first() != Some(&B::ONE) {
+        return Err(CheckError::ConstantWire(
+            wires
+                .first()
+                .map_or("nothing".to_string(), |value| value.to_string()),
+        ));
+    }
+
+    let mut report = CheckReport {
+        num_constraints: r1cs.num_rows(),
+        num_unsatisfied: 0,
+        failures: vec![],
+    };
+    for row in 0..r1cs.num_rows() {
+        let a = r1cs.a.evaluate_row(row, wires);
+        let b = r1cs.b.evaluate_row(row, wires);
+        let c = r1cs.c.evaluate_row(row, wires);
+        if a * b == c {
+            continue;
+        }
+        report.num_unsatisfied += 1;
+        if report.failures.len() < max_failures {
+            let mut columns: Vec<usize> = [&r1cs.a, &r1cs.b, &r1cs.c]
+                .iter()
+                .flat_map(|matrix| matrix.row(row).iter().map(|(col, _)| *col))
+                .collect();
+            columns.sort_unstable();
+            columns.dedup();
+            report.failures.push(UnsatisfiedConstraint {
+                row,
+                a,
+                b,
+                c,
+                wires: columns.into_iter().map(|col| (col, wires[col])).collect(),
+                gate: None,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Re-reads `arith_file` to find the gate each failing constraint of `report` was generated
+/// from. The instance must have been parsed from this file.
+pub fn locate_constraints<B: StarkField>(
+    report: &mut CheckReport<B>,
+    arith_file: &str,
+) -> Result<(), JsnarkError> {
+    // failures are in row order, so a single pass over the gates locates all of them
+    let mut failures = report.failures.iter_mut().peekable();
+    let mut first_row = 0;
+    for gate in ArithReader::<B, _>::open(arith_file)? {
+        let gate = gate?;
+        let end_row = first_row + gate.constraints().len();
+        while let Some(failure) = failures.next_if(|failure| failure.row < end_row) {
+            failure.gate = Some(gate.clone());
+        }
+        if failures.peek().is_none() {
+            break;
+        }
+        first_row = end_row;
+    }
+    Ok(())
+}
+
+// FIELD VALIDITY
+// ================================================================================================
+
+/// Whether a fixture can be proven over a base field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValidity {
+    /// Every constant and wire value lies in the field and the constraints are satisfied.
+    Valid,
+    /// A constant or wire value is not below the modulus.
+    OutOfField(String),
+    /// The values lie in the field, but the assignment was computed modulo another prime and
+    /// does not satisfy the constraints in this one.
+    Unsatisfied {
+        num_unsatisfied: usize,
+        num_constraints: usize,
+    },
+}
+
+/// Loads the fixture at `fixture_path` (without extension) over the field `B`, computing the
+/// witness from `inputs_file` if one is given, and checks it.
+pub fn check_fixture_field<B: StarkField>(
+    fixture_path: &str,
+    inputs_file: Option<&str>,
+) -> Result<FieldValidity, CheckError> {
+    let instance = match load_instance::<B>(fixture_path, inputs_file) {
+        Ok(instance) => instance,
+        Err(BinaryFormatError::Jsnark(err @ JsnarkError::OutOfField { .. })) => {
+            return Ok(FieldValidity::OutOfField(err.to_string()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let report = check_instance(&instance.r1cs, &instance.wires, 0)?;
+    if report.is_satisfied() {
+        Ok(FieldValidity::Valid)
+    } else {
+        Ok(FieldValidity::Unsatisfied {
+            num_unsatisfied: report.num_unsatisfied,
+            num_constraints: report.num_constraints,
+        })
+    }
+}
+
+// DISPLAY
+// ================================================================================================
+
+impl fmt::Display for FieldValidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValidity::Valid => write!(f, "valid"),
+            FieldValidity::OutOfField(message) => write!(f, "out of field, {}", message),
+            FieldValidity::Unsatisfied {
+                num_unsatisfied,
+                num_constraints,
+            } => write!(
+                f,
+                "in field but unsatisfied, {} of {} constraints fail",
+                num_unsatisfied, num_constraints
+            ),
+        }
+    }
+}
+
+impl<B: StarkField> fmt::Display for UnsatisfiedConstraint<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {}: {} * {} != {}",
+            self.row, self.a, self.b, self.c
+        )?;
+        if let Some(gate) = &self.gate {
+            write!(
+                f,
+                "\n  from line {}: {:?} in {:?} out {:?}",
+                gate.line, gate.kind, gate.inputs, gate.outputs
+            )?;
+        }
+        for (wire, value) in self.wires.iter() {
+            write!(f, "\n  wire {} = {}", wire, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: StarkField> fmt::Display for CheckReport<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_satisfied() {
+            return write!(f, "All {} constraints are satisfied", self.num_constraints);
+        }
+        write!(
+            f,
+            "{} of {} constraints are unsatisfied",
+            self.num_unsatisfied, self.num_constraints
+        )?;
+        for failure in self.failures.iter() {
+            write!(f, "\n{}", failure)?;
+        }
+        if self.failures.len() < self.num_unsatisfied {
+            write!(
+                f,
+                "\n... and {} more",
+                self.num_unsatisfied - self.failures.len()
+            )?;
+        }
+        Ok(())
+    }
+}
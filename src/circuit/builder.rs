@@ -0,0 +1,242 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::ops::{Add, Neg, Sub};
+
+use winter_math::StarkField;
+
+use super::Circuit;
+
+// WIRES AND LINEAR COMBINATIONS
+// ================================================================================================
+
+/// A variable of the circuit, i.e. a column of the R1CS matrices. Wire 0 always carries the
+/// value 1, as in jsnark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wire(usize);
+
+impl Wire {
+    pub const ONE: Wire = Wire(0);
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A sum of wires scaled by constants. Additions and constant multiplications are free: they
+/// only grow the linear combination, and no constraint is emitted until the combination is
+/// multiplied by another one or materialized into a wire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearCombination<B: StarkField> {
+    terms: Vec<(Wire, B)>,
+}
+
+impl<B: StarkField> LinearCombination<B> {
+    pub fn zero() -> Self {
+        LinearCombination { terms: vec![] }
+    }
+
+    pub fn constant(value: B) -> Self {
+        LinearCombination {
+            terms: vec![(Wire::ONE, value)],
+        }
+    }
+
+    pub fn terms(&self) -> &[(Wire, B)] {
+        &self.terms
+    }
+
+    /// Returns the value of this combination if it only involves the constant wire.
+    pub fn as_constant(&self) -> Option<B> {
+        self.terms.iter().try_fold(B::ZERO, |sum, (wire, coeff)| {
+            (*wire == Wire::ONE).then(|| sum + *coeff)
+        })
+    }
+
+    /// Returns the wire this combination is equal to if it is a single wire with coefficient 1.
+    pub fn as_wire(&self) -> Option<Wire> {
+        match self.terms.as_slice() {
+            [(wire, coeff)] if *coeff == B::ONE => Some(*wire),
+            _ => None,
+        }
+    }
+
+    pub fn scale(mut self, factor: B) -> Self {
+        for (_, coeff) in self.terms.iter_mut() {
+            *coeff *= factor;
+        }
+        self
+    }
+
+    /// Evaluates this combination against an assignment of all wires.
+    pub fn evaluate(&self, values: &[B]) -> B {
+        self.terms
+            .iter()
+            .fold(B::ZERO, |sum, (wire, coeff)| sum + values[wire.0] * *coeff)
+    }
+}
+
+impl<B: StarkField> From<Wire> for LinearCombination<B> {
+    fn from(wire: Wire) -> Self {
+        LinearCombination {
+            terms: vec![(wire, B::ONE)],
+        }
+    }
+}
+
+impl<B: StarkField> Add for LinearCombination<B> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self.terms.extend(rhs.terms);
+        self
+    }
+}
+
+impl<B: StarkField> Neg for LinearCombination<B> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.scale(-B::ONE)
+    }
+}
+
+impl<B: StarkField> Sub for LinearCombination<B> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+/// A single R1CS constraint `<a, z> * <b, z> = <c, z>`.
+#[derive(Debug, Clone)]
+pub struct Constraint<B: StarkField> {
+    pub a: LinearCombination<B>,
+    pub b: LinearCombination<B>,
+    pub c: LinearCombination<B>,
+}
+
+impl<B: StarkField> Constraint<B> {
+    pub fn is_satisfied(&self, values: &[B]) -> bool {
+        self.a.evaluate(values) * self.b.evaluate(values) == self.c.evaluate(values)
+    }
+}
+
+// CIRCUIT BUILDER
+// ================================================================================================
+
+/// Builds the constraints of a circuit together with the values of all its wires, so the
+/// witness never has to be generated separately.
+pub struct CircuitBuilder<B: StarkField> {
+    values: Vec<B>,
+    constraints: Vec<Constraint<B>>,
+    inputs: Vec<Wire>,
+    outputs: Vec<Wire>,
+}
+
+impl<B: StarkField> CircuitBuilder<B> {
+    pub fn new() -> Self {
+        CircuitBuilder {
+            values: vec![B::ONE],
+            constraints: vec![],
+            inputs: vec![],
+            outputs: vec![],
+        }
+    }
+
+    pub fn num_wires(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Current value of a linear combination.
+    pub fn value(&self, lc: &LinearCombination<B>) -> B {
+        lc.evaluate(&self.values)
+    }
+
+    /// Allocates a wire holding a public input.
+    pub fn input(&mut self, value: B) -> Wire {
+        let wire = self.witness(value);
+        self.inputs.push(wire);
+        wire
+    }
+
+    /// Allocates an unconstrained wire; the caller is responsible for constraining it.
+    pub fn witness(&mut self, value: B) -> Wire {
+        self.values.push(value);
+        Wire(self.values.len() - 1)
+    }
+
+    /// Adds the constraint `a * b = c`.
+    pub fn enforce(
+        &mut self,
+        a: LinearCombination<B>,
+        b: LinearCombination<B>,
+        c: LinearCombination<B>,
+    ) {
+        let constraint = Constraint { a, b, c };
+        debug_assert!(
+            constraint.is_satisfied(&self.values),
+            "constraint {} is not satisfied by the witness",
+            self.constraints.len()
+        );
+        self.constraints.push(constraint);
+    }
+
+    /// Multiplies two linear combinations. This costs one constraint unless one side is a
+    /// constant.
+    pub fn mul(
+        &mut self,
+        a: LinearCombination<B>,
+        b: LinearCombination<B>,
+    ) -> LinearCombination<B> {
+        if let Some(constant) = a.as_constant() {
+            return b.scale(constant);
+        }
+        if let Some(constant) = b.as_constant() {
+            return a.scale(constant);
+        }
+        let product = self.witness(self.value(&a) * self.value(&b));
+        self.enforce(a, b, product.into());
+        product.into()
+    }
+
+    /// Returns a wire equal to `lc`, adding the constraint `lc * 1 = wire` unless `lc` already is
+    /// a single wire.
+    pub fn materialize(&mut self, lc: LinearCombination<B>) -> Wire {
+        if let Some(wire) = lc.as_wire() {
+            return wire;
+        }
+        let wire = self.witness(self.value(&lc));
+        self.enforce(lc, Wire::ONE.into(), wire.into());
+        wire
+    }
+
+    /// Exposes `lc` as an output of the circuit. Like jsnark, every output gets its own wire.
+    pub fn output(&mut self, lc: LinearCombination<B>) -> Wire {
+        let wire = self.witness(self.value(&lc));
+        self.enforce(lc, Wire::ONE.into(), wire.into());
+        self.outputs.push(wire);
+        wire
+    }
+
+    pub fn finish(self) -> Circuit<B> {
+        Circuit {
+            constraints: self.constraints,
+            wires: self.values,
+            inputs: self.inputs,
+            outputs: self.outputs,
+        }
+    }
+}
+
+impl<B: StarkField> Default for CircuitBuilder<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_math::StarkField;
+
+use super::builder::{CircuitBuilder, LinearCombination, Wire};
+
+/// Constrains `wire` to be 0 or 1.
+pub fn assert_boolean<B: StarkField>(builder: &mut CircuitBuilder<B>, wire: Wire) {
+    let lc = LinearCombination::from(wire);
+    builder.enforce(
+        lc.clone(),
+        lc - LinearCombination::constant(B::ONE),
+        LinearCombination::zero(),
+    );
+}
+
+/// Decomposes `lc` into `num_bits` little-endian bits, costing one constraint per bit plus one
+/// for the recomposition. The value of `lc` must fit in `num_bits` bits.
+pub fn split<B: StarkField>(
+    builder: &mut CircuitBuilder<B>,
+    lc: LinearCombination<B>,
+    num_bits: usize,
+) -> Vec<Wire> {
+    let value = builder.value(&lc).as_int();
+    let one = B::PositiveInteger::from(1u32);
+
+    let mut bits = Vec::with_capacity(num_bits);
+    let mut recomposed = LinearCombination::zero();
+    let mut power_of_two = B::ONE;
+    for i in 0..num_bits {
+        let bit_value = if (value >> i as u32) & one == one {
+            B::ONE
+        } else {
+            B::ZERO
+        };
+        let bit = builder.witness(bit_value);
+        assert_boolean(builder, bit);
+        recomposed = recomposed + LinearCombination::from(bit).scale(power_of_two);
+        power_of_two = power_of_two.double();
+        bits.push(bit);
+    }
+    builder.enforce(recomposed, Wire::ONE.into(), lc);
+    bits
+}
+
+/// Returns `if_one` when `bit` is 1 and `if_zero` when it is 0. Free if both branches are
+/// constants, one constraint otherwise.
+pub fn select<B: StarkField>(
+    builder: &mut CircuitBuilder<B>,
+    bit: Wire,
+    if_zero: LinearCombination<B>,
+    if_one: LinearCombination<B>,
+) -> LinearCombination<B> {
+    let delta = builder.mul(bit.into(), if_one - if_zero.clone());
+    if_zero + delta
+}
+
+/// Returns a new wire equal to `if_one` when `flag` is 1 and `if_zero` when it is 0, for a
+/// `flag` known to be 0 or 1. Unlike [select], the result does not grow with the branches, so it
+/// suits values which are selected again and again. Costs one constraint.
+pub fn select_into_wire<B: StarkField>(
+    builder: &mut CircuitBuilder<B>,
+    flag: LinearCombination<B>,
+    if_zero: LinearCombination<B>,
+    if_one: LinearCombination<B>,
+) -> Wire {
+    let value = if builder.value(&flag) == B::ONE {
+        builder.value(&if_one)
+    } else {
+        builder.value(&if_zero)
+    };
+    let wire = builder.witness(value);
+    builder.enforce(
+        flag,
+        if_one - if_zero.clone(),
+        LinearCombination::from(wire) - if_zero,
+    );
+    wire
+}
+
+/// Reads `table[index]`, where `index` is given by its little-endian bits, with a multiplexer
+/// tree. The table length must be `2^bits.len()`. Selecting between two constants is free, so a
+/// lookup costs at most `table.len() - 1` constraints, and `table.len() / 2 - 1` over a constant
+/// table.
+pub fn lookup<B: StarkField>(
+    builder: &mut CircuitBuilder<B>,
+    bits: &[Wire],
+    table: &[LinearCombination<B>],
+) -> LinearCombination<B> {
+    assert_eq!(
+        1 << bits.len(),
+        table.len(),
+        "lookup table length must be 2^(number of index bits)"
+    );
+    let mut layer = table.to_vec();
+    for bit in bits {
+        layer = layer
+            .chunks(2)
+            .map(|pair| select(builder, *bit, pair[0].clone(), pair[1].clone()))
+            .collect();
+    }
+    layer.pop().unwrap()
+}
+
+/// Returns the `2^bits.len()` flags which are 1 at the index given by the little-endian `bits`
+/// and 0 elsewhere. Costs `2^bits.len() - 2` constraints.
+pub fn decode<B: StarkField>(
+    builder: &mut CircuitBuilder<B>,
+    bits: &[Wire],
+) -> Vec<LinearCombination<B>> {
+    let mut flags = vec![LinearCombination::constant(B::ONE)];
+    for bit in bits {
+        let set: Vec<LinearCombination<B>> = flags
+            .iter()
+            .map(|flag| builder.mul(flag.clone(), (*bit).into()))
+            .collect();
+        let unset: Vec<LinearCombination<B>> = flags
+            .into_iter()
+            .zip(set.iter())
+            .map(|(flag, set)| flag - set.clone())
+            .collect();
+        flags = unset.into_iter().chain(set).collect();
+    }
+    flags
+}
@@ -0,0 +1,123 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Builds the R1CS instances of the benchmark programs in Rust, at any size, instead of reading
+//! jsnark `.arith` and `.wires` files.
+//!
+//! Circuits are written against [CircuitBuilder], which tracks the value of every wire while the
+//! constraints are emitted, so the witness comes out of the same pass. Wire 0 is the constant 1
+//! and the public inputs come next, following the jsnark wire layout.
+
+use winter_math::StarkField;
+use winter_models::r1cs::R1CS;
+
+use crate::registry::{ProgramSpec, RegistryError};
+use crate::sparse::SparseR1CS;
+
+pub mod builder;
+pub use builder::{CircuitBuilder, Constraint, LinearCombination, Wire};
+
+pub mod gadgets;
+
+pub mod programs;
+
+// CIRCUIT
+// ================================================================================================
+
+/// The constraints of a circuit together with a satisfying assignment of its wires.
+#[derive(Debug, Clone)]
+pub struct Circuit<B: StarkField> {
+    pub constraints: Vec<Constraint<B>>,
+    pub wires: Vec<B>,
+    pub inputs: Vec<Wire>,
+    pub outputs: Vec<Wire>,
+}
+
+impl<B: StarkField> Circuit<B> {
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    pub fn num_wires(&self) -> usize {
+        self.wires.len()
+    }
+
+    pub fn output_values(&self) -> Vec<B> {
+        self.outputs
+            .iter()
+            .map(|wire| self.wires[wire.index()])
+            .collect()
+    }
+
+    /// Returns the index of the first constraint not satisfied by the wire values, if any.
+    pub fn first_unsatisfied(&self) -> Option<usize> {
+        self.constraints
+            .iter()
+            .position(|constraint| !constraint.is_satisfied(&self.wires))
+    }
+
+    /// The constraints as sparse A, B and C matrices with one column per wire.
+    pub fn to_sparse(&self) -> SparseR1CS<B> {
+        let mut r1cs = SparseR1CS::new(self.num_wires());
+        for constraint in self.constraints.iter() {
+            r1cs.push_constraint(
+                &to_terms(&constraint.a),
+                &to_terms(&constraint.b),
+                &to_terms(&constraint.c),
+            );
+        }
+        r1cs
+    }
+
+    /// The constraints as the matrices expected by the Fractal indexer.
+    pub fn to_r1cs(&self) -> R1CS<B> {
+        self.to_sparse().to_r1cs()
+    }
+}
+
+fn to_terms<B: StarkField>(lc: &LinearCombination<B>) -> Vec<(usize, B)> {
+    lc.terms()
+        .iter()
+        .map(|(wire, coeff)| (wire.index(), *coeff))
+        .collect()
+}
+
+// PROGRAM CIRCUITS
+// ================================================================================================
+
+/// The circuits implemented in [programs], referenced from the program registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitKind {
+    Fft,
+    MulFib,
+    PointerChasing,
+}
+
+impl CircuitKind {
+    /// Builds the circuit for a program of log2 size `size`; `iterations` is the log2 number of
+    /// steps for programs which take one.
+    pub fn build<B: StarkField>(&self, size: u64, iterations: Option<u64>) -> Circuit<B> {
+        match self {
+            CircuitKind::Fft => programs::fft_circuit(size as u32),
+            CircuitKind::MulFib => programs::mulfib_circuit(size as u32),
+            CircuitKind::PointerChasing => {
+                programs::pointer_chasing_circuit(size as u32, iterations.unwrap() as u32)
+            }
+        }
+    }
+}
+
+/// Builds the R1CS circuit of `spec` natively. Unlike the jsnark fixtures, any size is accepted;
+/// the iteration count defaults to the smallest one the registry knows about.
+pub fn build_program_circuit<B: StarkField>(
+    spec: &ProgramSpec,
+    size: u64,
+    iterations: Option<u64>,
+) -> Result<Circuit<B>, RegistryError> {
+    let kind = spec
+        .r1cs_circuit
+        .ok_or(RegistryError::NoNativeCircuit(spec.name))?;
+    let iterations = iterations.or_else(|| spec.default_iterations());
+    Ok(kind.build(size, iterations))
+}
@@ -0,0 +1,132 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! The benchmark programs written against [CircuitBuilder]. Inputs are the same as in the jsnark
+//! generators, so the outputs match those of the `.wires` fixtures.
+
+use winter_math::StarkField;
+
+use super::builder::{CircuitBuilder, LinearCombination, Wire};
+use super::gadgets::{decode, lookup, select_into_wire, split};
+use super::Circuit;
+use crate::air::pointer_chasing::{
+    input_addresses, memory_cell, result_address, start_address, CHASE_INPUTS,
+};
+
+/// Constant factor of the jsnark multiplicative Fibonacci sequence, x_i = x_{i-1} * 20 * x_{i-2}.
+const FIB_MULTIPLIER: u64 = 20;
+
+/// Radix-2 FFT over `2^log_size` inputs `5, 10, 15, ...`. Twiddle factors are constants, so the
+/// butterflies are linear; each butterfly output is materialized into a wire so that every layer
+/// costs `2^log_size` constraints, as in the jsnark FFT.
+pub fn fft_circuit<B: StarkField>(log_size: u32) -> Circuit<B> {
+    let size = 1usize << log_size;
+    let mut builder = CircuitBuilder::<B>::new();
+
+    let inputs: Vec<Wire> = (0..size)
+        .map(|i| builder.input(B::from(5 * (i as u64 + 1))))
+        .collect();
+
+    // iterative Cooley-Tukey on the inputs in bit-reversed order
+    let mut values: Vec<LinearCombination<B>> = (0..size)
+        .map(|i| inputs[bit_reverse(i, log_size)].into())
+        .collect();
+    for stage in 1..=log_size {
+        let half = 1usize << (stage - 1);
+        let root = B::get_root_of_unity(stage);
+        for start in (0..size).step_by(2 * half) {
+            let mut twiddle = B::ONE;
+            for j in start..start + half {
+                let u = values[j].clone();
+                let t = values[j + half].clone().scale(twiddle);
+                values[j] = builder.materialize(u.clone() + t.clone()).into();
+                values[j + half] = builder.materialize(u - t).into();
+                twiddle *= root;
+            }
+        }
+    }
+
+    for value in values {
+        builder.output(value);
+    }
+    builder.finish()
+}
+
+/// The first `2^log_length` terms of the multiplicative Fibonacci sequence starting from 1, 1;
+/// the last term is the output.
+pub fn mulfib_circuit<B: StarkField>(log_length: u32) -> Circuit<B> {
+    let length = 1usize << log_length;
+    let mut builder = CircuitBuilder::<B>::new();
+
+    let mut previous: LinearCombination<B> = builder.input(B::ONE).into();
+    let mut current: LinearCombination<B> = builder.input(B::ONE).into();
+    for _ in 2..length {
+        let next = builder.mul(current.clone(), previous.scale(B::from(FIB_MULTIPLIER)));
+        previous = current;
+        current = next;
+    }
+
+    builder.output(current);
+    builder.finish()
+}
+
+/// `2^log_steps` steps of the jsnark pointer chasing program over a memory of
+/// `2^log_memory_size` cells, as in air::pointer_chasing. The two inputs are added to the initial
+/// memory, and every step reads the cell at the pointer with a multiplexer tree, splits the sum of
+/// the cell and the pointer into bits to reduce it, and rewrites every cell with a selection on
+/// the flags of the pointer's address, so a step costs about `3 * 2^log_memory_size` constraints.
+/// The final value of the output cell is the output.
+pub fn pointer_chasing_circuit<B: StarkField>(log_memory_size: u32, log_steps: u32) -> Circuit<B> {
+    assert!(log_memory_size > 0, "memory size must be greater than 1");
+    let num_bits = log_memory_size as usize;
+    let memory_size = 1u64 << log_memory_size;
+    let mut builder = CircuitBuilder::<B>::new();
+
+    let mut memory: Vec<LinearCombination<B>> = (0..memory_size)
+        .map(|address| LinearCombination::constant(B::from(memory_cell(address, memory_size))))
+        .collect();
+    for (address, input) in input_addresses(memory_size).iter().zip(CHASE_INPUTS) {
+        let input = builder.input(B::from(input));
+        memory[*address as usize] = memory[*address as usize].clone() + input.into();
+    }
+
+    let start = LinearCombination::constant(B::from(start_address(memory_size)));
+    let mut pointer_bits = split(&mut builder, start, num_bits);
+    for _ in 0..1u64 << log_steps {
+        let pointer = recompose(&pointer_bits);
+        let value = lookup(&mut builder, &pointer_bits, &memory);
+
+        // the cell and the pointer are both below the memory size, so the sum has one more bit
+        let sum_bits = split(&mut builder, value + pointer, num_bits + 1);
+        let next_bits = sum_bits[..num_bits].to_vec();
+        let next = recompose(&next_bits);
+
+        let flags = decode(&mut builder, &pointer_bits);
+        for (cell, flag) in memory.iter_mut().zip(flags) {
+            *cell = select_into_wire(&mut builder, flag, cell.clone(), next.clone()).into();
+        }
+        pointer_bits = next_bits;
+    }
+
+    builder.output(memory[result_address(memory_size) as usize].clone());
+    builder.finish()
+}
+
+/// The value of little-endian `bits`.
+fn recompose<B: StarkField>(bits: &[Wire]) -> LinearCombination<B> {
+    let mut lc = LinearCombination::zero();
+    let mut power_of_two = B::ONE;
+    for bit in bits {
+        lc = lc + LinearCombination::from(*bit).scale(power_of_two);
+        power_of_two = power_of_two.double();
+    }
+    lc
+}
+
+pub(crate) fn bit_reverse(index: usize, num_bits: u32) -> usize {
+    if num_bits == 0 {
+        return index;
+    }
+    index.reverse_bits() >> (usize::BITS - num_bits)
+}
@@ -0,0 +1,153 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Selection and validation of the coset shifts `eta` and `eta_k` used by the Fractal indexer.
+//!
+//! The H, K and L domains are multiplicative subgroups of power-of-two order, so they all lie in
+//! the 2-adic subgroup G of order `2^TWO_ADICITY`. A shift whose `2^TWO_ADICITY`-th power is not 1
+//! lies outside G, so its coset of G is disjoint from H, K and L, and two shifts whose quotient
+//! lies outside G give disjoint cosets of G. Checking these conditions on G rather than on the
+//! actual domains makes them independent of the instance size.
+
+use thiserror::Error;
+use winter_math::StarkField;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CosetError {
+    #[error("Coset shift {0} must be non-zero")]
+    Zero(&'static str),
+    #[error(
+        "Coset shift {0} lies in the 2-adic subgroup, so its coset overlaps the H, K and L domains"
+    )]
+    InSubgroup(&'static str),
+    #[error("Coset shifts eta and eta_k give overlapping cosets")]
+    Overlapping,
+    #[error("The field has no coset of its 2-adic subgroup to shift the domains into")]
+    NoCosets,
+}
+
+/// The shifts of the cosets used by the Fractal indexer, checked to be disjoint from the H, K and
+/// L domains and from each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CosetShifts<B: StarkField> {
+    eta: B,
+    eta_k: B,
+}
+
+impl<B: StarkField> CosetShifts<B> {
+    /// Validates user-supplied shifts.
+    pub fn new(eta: B, eta_k: B) -> Result<Self, CosetError> {
+        if eta == B::ZERO {
+            return Err(CosetError::Zero("eta"));
+        }
+        if eta_k == B::ZERO {
+            return Err(CosetError::Zero("eta_k"));
+        }
+        if in_two_adic_subgroup(eta) {
+            return Err(CosetError::InSubgroup("eta"));
+        }
+        if in_two_adic_subgroup(eta_k) {
+            return Err(CosetError::InSubgroup("eta_k"));
+        }
+        if in_two_adic_subgroup(eta * eta_k.inv()) {
+            return Err(CosetError::Overlapping);
+        }
+        Ok(CosetShifts { eta, eta_k })
+    }
+
+    /// Picks valid shifts for this field. These are `GENERATOR^(2 TWO_ADICITY)` and
+    /// `GENERATOR^(1337 TWO_ADICITY)`, which the benchmarks have always used, when they are valid.
+    pub fn select() -> Result<Self, CosetError> {
+        let power = |exponent: u32| B::GENERATOR.exp(B::PositiveInteger::from(exponent));
+        Self::new(power(2 * B::TWO_ADICITY), power(1337 * B::TWO_ADICITY))
+            // GENERATOR^i lies in G exactly when i is a multiple of the odd part of the group
+            // order, which is at least 3 whenever G has a coset
+            .or_else(|_| Self::new(power(1), power(2)))
+            .map_err(|_| CosetError::NoCosets)
+    }
+
+    pub fn eta(&self) -> B {
+        self.eta
+    }
+
+    pub fn eta_k(&self) -> B {
+        self.eta_k
+    }
+}
+
+/// True when `x^(2^TWO_ADICITY) = 1`, i.e. when x lies in the 2-adic subgroup of the field.
+fn in_two_adic_subgroup<B: StarkField>(x: B) -> bool {
+    let mut power = x;
+    for _ in 0..B::TWO_ADICITY {
+        power = power.square();
+    }
+    power == B::ONE
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use winter_math::fields::{f128, f62, f64};
+    use winter_math::FieldElement;
+
+    use super::*;
+
+    type BaseElement = f64::BaseElement;
+
+    fn valid_shifts() -> CosetShifts<BaseElement> {
+        CosetShifts::select().unwrap()
+    }
+
+    #[test]
+    fn select_in_every_field() {
+        assert!(CosetShifts::<f62::BaseElement>::select().is_ok());
+        assert!(CosetShifts::<f64::BaseElement>::select().is_ok());
+        assert!(CosetShifts::<f128::BaseElement>::select().is_ok());
+    }
+
+    #[test]
+    fn accepts_selected_shifts() {
+        let shifts = valid_shifts();
+        assert_eq!(CosetShifts::new(shifts.eta(), shifts.eta_k()), Ok(shifts));
+    }
+
+    #[test]
+    fn rejects_zero() {
+        let eta = valid_shifts().eta();
+        assert_eq!(
+            CosetShifts::new(BaseElement::ZERO, eta),
+            Err(CosetError::Zero("eta"))
+        );
+        assert_eq!(
+            CosetShifts::new(eta, BaseElement::ZERO),
+            Err(CosetError::Zero("eta_k"))
+        );
+    }
+
+    #[test]
+    fn rejects_shift_in_subgroup() {
+        let eta = valid_shifts().eta();
+        let root = BaseElement::get_root_of_unity(BaseElement::TWO_ADICITY);
+        assert_eq!(
+            CosetShifts::new(BaseElement::ONE, eta),
+            Err(CosetError::InSubgroup("eta"))
+        );
+        assert_eq!(
+            CosetShifts::new(eta, root),
+            Err(CosetError::InSubgroup("eta_k"))
+        );
+    }
+
+    #[test]
+    fn rejects_overlapping_cosets() {
+        let eta = valid_shifts().eta();
+        let root = BaseElement::get_root_of_unity(BaseElement::TWO_ADICITY);
+        assert_eq!(
+            CosetShifts::new(eta, eta * root),
+            Err(CosetError::Overlapping)
+        );
+    }
+}
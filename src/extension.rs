@@ -0,0 +1,72 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Runtime selection of the field extension the R1CS path is instantiated with.
+//!
+//! Fractal draws its random challenges and evaluates its polynomials over the extension field
+//! `E`, so with the 64-bit base field each query only gives 64-bit soundness unless `E` is an
+//! extension. Like [with_r1cs_hasher](crate::with_r1cs_hasher) for the hasher,
+//! [with_field_extension](crate::with_field_extension) turns an extension degree given on the
+//! command line into a type.
+
+use thiserror::Error;
+
+pub use winter_math::{fields, ExtensibleField};
+
+/// Extension degrees both proof systems support: none, quadratic and cubic.
+pub const FIELD_EXTENSIONS: [u32; 3] = [1, 2, 3];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExtensionError {
+    #[error("Unsupported field extension degree {0}, expected 1, 2 or 3")]
+    Unsupported(u32),
+    #[error("The base field has no extension of degree {0}")]
+    NotSupportedByField(u32),
+}
+
+/// Checks that both proof systems can be instantiated with an extension of `degree`.
+pub fn check_field_extension(degree: u32) -> Result<(), ExtensionError> {
+    if FIELD_EXTENSIONS.contains(&degree) {
+        Ok(())
+    } else {
+        Err(ExtensionError::Unsupported(degree))
+    }
+}
+
+/// Evaluates `$body` with the type alias `$E` bound to the extension of degree `$degree` of the
+/// base field `$B`, returning `Ok` with its value, or an
+/// [ExtensionError](crate::extension::ExtensionError) for any other degree or if `$B` has no
+/// such extension (`f128` has no cubic one).
+///
+/// ```ignore
+/// with_field_extension!(degree, BaseElement, E => {
+///     FractalPipeline::<BaseElement, E, Blake3_256<BaseElement>>::new(options)
+/// })
+/// ```
+#[macro_export]
+macro_rules! with_field_extension {
+    ($degree:expr, $B:ty, $E:ident => $body:expr) => {
+        match $degree {
+            1 => {
+                type $E = $B;
+                Ok($body)
+            }
+            2 if !<$B as $crate::extension::ExtensibleField<2>>::is_supported() => {
+                Err($crate::extension::ExtensionError::NotSupportedByField(2))
+            }
+            2 => {
+                type $E = $crate::extension::fields::QuadExtension<$B>;
+                Ok($body)
+            }
+            3 if !<$B as $crate::extension::ExtensibleField<3>>::is_supported() => {
+                Err($crate::extension::ExtensionError::NotSupportedByField(3))
+            }
+            3 => {
+                type $E = $crate::extension::fields::CubeExtension<$B>;
+                Ok($body)
+            }
+            other => Err($crate::extension::ExtensionError::Unsupported(other)),
+        }
+    };
+}
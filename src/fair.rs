@@ -0,0 +1,327 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Fair comparisons, where AIR and R1CS runs differ only in their arithmetization.
+//!
+//! By default each proof system keeps its own parameters: the AIR benches fold by 8, grind 16
+//! bits and keep FRI remainders of up to 256 values, while Fractal folds by 4, cannot grind and
+//! stops folding at 32. [fair_options] instead forces the same hash function, field, extension
+//! degree, blowup factor, folding factor, number of queries, grinding and FRI remainder size on
+//! both sides, and refuses with an explanation when one side cannot honour a setting. The field
+//! of an AIR is fixed, so [fair_field] picks the R1CS field to match it.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::air::{
+    check_air_hash_fn, min_blowup_factor, AirError, AirOptions, AIR_FRI_MAX_REMAINDER_SIZE,
+    AIR_MAX_BLOWUP_FACTOR,
+};
+use crate::field::DEFAULT_FIELD;
+use crate::hash::HashError;
+use crate::pipeline::FractalPipelineOptions;
+use crate::registry::{AirConstructor, AirLayout, ProgramSpec, SystemTag};
+use crate::security::MAX_NUM_QUERIES;
+
+/// Folding factors winterfell's FRI supports.
+const AIR_FOLDING_FACTORS: [usize; 3] = [4, 8, 16];
+
+#[derive(Debug, Error)]
+pub enum FairnessError {
+    #[error(transparent)]
+    Hash(#[from] HashError),
+    #[error(transparent)]
+    Air(#[from] AirError),
+    #[error("{system} cannot run in fair mode: {reason}")]
+    UnsupportedSystem {
+        system: SystemTag,
+        reason: &'static str,
+    },
+    #[error("{program} cannot be compared fairly: {setting} is {r1cs} for R1CS but {air} for AIR, {reason}")]
+    Mismatch {
+        program: &'static str,
+        setting: &'static str,
+        air: String,
+        r1cs: String,
+        reason: &'static str,
+    },
+    #[error(
+        "{program} cannot be compared fairly: AIR does not support {setting} {value}, {reason}"
+    )]
+    UnsupportedByAir {
+        program: &'static str,
+        setting: &'static str,
+        value: usize,
+        reason: &'static str,
+    },
+}
+
+/// The cryptographic parameters shared by both sides of a fair comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FairParams {
+    pub hash_fn: String,
+    pub field: String,
+    pub field_extension: u32,
+    pub blowup_factor: usize,
+    pub folding_factor: usize,
+    pub num_queries: usize,
+    pub grinding_factor: u32,
+    pub max_remainder_size: usize,
+}
+
+impl fmt::Display for FairParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hash {}, field {}, field extension {}, blowup {}, folding {}, {} queries, grinding {} bits, FRI remainder up to {}",
+            self.hash_fn,
+            self.field,
+            self.field_extension,
+            self.blowup_factor,
+            self.folding_factor,
+            self.num_queries,
+            self.grinding_factor,
+            self.max_remainder_size
+        )
+    }
+}
+
+/// Base field of an AIR layout. The winterfell examples are written over the 128-bit field, the
+/// AIRs in this crate over the 64-bit one.
+pub fn air_field(layout: &AirLayout) -> &'static str {
+    match layout.air {
+        AirConstructor::Example(_) => "f128",
+        AirConstructor::Native(_) => "f64",
+    }
+}
+
+/// The field R1CS runs over in a fair comparison with AIR under `layouts`: the field of the
+/// layouts, which is fixed, or `requested` if it is given and matches. With no AIR layouts, the
+/// requested field or the default one. Layouts over different fields cannot be compared fairly in
+/// one run.
+pub fn fair_field(
+    spec: &ProgramSpec,
+    layouts: &[&AirLayout],
+    requested: Option<&str>,
+) -> Result<String, FairnessError> {
+    let (first, rest) = match layouts.split_first() {
+        Some(split) => split,
+        None => return Ok(requested.unwrap_or(DEFAULT_FIELD).to_string()),
+    };
+    let field = air_field(first);
+    if let Some(other) = rest.iter().find(|layout| air_field(layout) != field) {
+        return Err(FairnessError::Mismatch {
+            program: spec.name,
+            setting: "the field",
+            air: format!("{} under layout {}", air_field(other), other.name),
+            r1cs: format!("{} to match layout {}", field, first.name),
+            reason: "compare layouts over different fields in separate runs",
+        });
+    }
+    match requested {
+        Some(requested) if requested != field => Err(FairnessError::Mismatch {
+            program: spec.name,
+            setting: "the field",
+            air: format!("{} under layout {}", field, first.name),
+            r1cs: requested.to_string(),
+            reason: "leave --field unset to run R1CS over the AIR field",
+        }),
+        _ => Ok(field.to_string()),
+    }
+}
+
+/// Derives matching AIR and R1CS options from `r1cs_options` for running `spec` at `size` under
+/// `systems`, with AIR under each of `layouts`.
+///
+/// The R1CS options win because Fractal is the less flexible side: AIR takes its hash, extension
+/// degree, folding factor and number of queries, and drops grinding, which Fractal lacks. The
+/// blowup factor is raised on both sides to what the AIR layouts need for their constraint
+/// degrees, and R1CS takes the FRI remainder size the winterfell examples are fixed to. The field
+/// of an AIR is fixed too, so the R1CS field has to be chosen to match it; see [fair_field].
+pub fn fair_options(
+    spec: &ProgramSpec,
+    layouts: &[&AirLayout],
+    size: u64,
+    systems: &[SystemTag],
+    r1cs_options: &FractalPipelineOptions,
+) -> Result<(AirOptions, FractalPipelineOptions, FairParams), FairnessError> {
+    if systems.contains(&SystemTag::R1CSPolyBatched) {
+        return Err(FairnessError::UnsupportedSystem {
+            system: SystemTag::R1CSPolyBatched,
+            reason: "its parameters are fixed by the fractal_examples orchestrator",
+        });
+    }
+
+    let mut blowup_factor = r1cs_options.lde_blowup;
+    if systems.contains(&SystemTag::AIR) {
+        check_air_hash_fn(&r1cs_options.hash_fn)?;
+        for layout in layouts {
+            let field = air_field(layout);
+            if field != r1cs_options.field {
+                return Err(FairnessError::Mismatch {
+                    program: spec.name,
+                    setting: "the field",
+                    air: format!("{} under layout {}", field, layout.name),
+                    r1cs: r1cs_options.field.clone(),
+                    reason: "leave --field unset to run R1CS over the AIR field",
+                });
+            }
+            blowup_factor = blowup_factor.max(min_blowup_factor(spec, layout, size, None)?);
+        }
+        if blowup_factor > AIR_MAX_BLOWUP_FACTOR {
+            return Err(FairnessError::UnsupportedByAir {
+                program: spec.name,
+                setting: "blowup factor",
+                value: blowup_factor,
+                reason: "the largest winterfell accepts is 128",
+            });
+        }
+        if !AIR_FOLDING_FACTORS.contains(&r1cs_options.folding_factor) {
+            return Err(FairnessError::UnsupportedByAir {
+                program: spec.name,
+                setting: "folding factor",
+                value: r1cs_options.folding_factor,
+                reason: "winterfell folds by 4, 8 or 16",
+            });
+        }
+        if r1cs_options.num_queries > MAX_NUM_QUERIES {
+            return Err(FairnessError::UnsupportedByAir {
+                program: spec.name,
+                setting: "number of queries",
+                value: r1cs_options.num_queries,
+                reason: "the largest winterfell accepts is 128",
+            });
+        }
+    }
+
+    let params = FairParams {
+        hash_fn: r1cs_options.hash_fn.clone(),
+        field: r1cs_options.field.clone(),
+        field_extension: r1cs_options.field_extension,
+        blowup_factor,
+        folding_factor: r1cs_options.folding_factor,
+        num_queries: r1cs_options.num_queries,
+        grinding_factor: 0,
+        max_remainder_size: AIR_FRI_MAX_REMAINDER_SIZE,
+    };
+    let air_options = AirOptions {
+        hash_fn: params.hash_fn.clone(),
+        num_queries: params.num_queries,
+        blowup_factor: params.blowup_factor,
+        grinding_factor: params.grinding_factor,
+        field_extension: params.field_extension,
+        folding_factor: params.folding_factor,
+    };
+    let r1cs_options = FractalPipelineOptions {
+        lde_blowup: params.blowup_factor,
+        max_remainder_size: params.max_remainder_size,
+        ..r1cs_options.clone()
+    };
+    Ok((air_options, r1cs_options, params))
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::PROGRAM_REGISTRY;
+
+    const SYSTEMS: [SystemTag; 2] = [SystemTag::AIR, SystemTag::R1CS];
+
+    /// The default R1CS options over the field `layout` needs.
+    fn r1cs_options_for(layout: &AirLayout) -> FractalPipelineOptions {
+        FractalPipelineOptions {
+            field: air_field(layout).to_string(),
+            index_cache_dir: None,
+            ..FractalPipelineOptions::default()
+        }
+    }
+
+    #[test]
+    fn fair_options_match_across_systems() {
+        for spec in PROGRAM_REGISTRY.programs() {
+            for layout in spec.air_layouts.iter() {
+                for size in [layout.sizes.start, layout.sizes.end - 1] {
+                    let requested = r1cs_options_for(layout);
+                    let (air, r1cs, params) =
+                        fair_options(spec, &[layout], size, &SYSTEMS, &requested).unwrap();
+                    let run = format!(
+                        "{} under layout {} at size {}",
+                        spec.name, layout.name, size
+                    );
+
+                    assert_eq!(air.blowup_factor, r1cs.lde_blowup, "{}", run);
+                    assert_eq!(air.num_queries, r1cs.num_queries, "{}", run);
+                    assert_eq!(air.folding_factor, r1cs.folding_factor, "{}", run);
+                    assert_eq!(air.field_extension, r1cs.field_extension, "{}", run);
+                    assert_eq!(air.hash_fn, r1cs.hash_fn, "{}", run);
+                    assert_eq!(air.grinding_factor, 0, "{}", run);
+                    assert_eq!(
+                        AIR_FRI_MAX_REMAINDER_SIZE, r1cs.max_remainder_size,
+                        "{}",
+                        run
+                    );
+                    assert_eq!(air_field(layout), r1cs.field, "{}", run);
+
+                    assert_eq!(params.blowup_factor, air.blowup_factor, "{}", run);
+                    let min_blowup = min_blowup_factor(spec, layout, size, None).unwrap();
+                    assert_eq!(
+                        air.blowup_factor,
+                        requested.lde_blowup.max(min_blowup),
+                        "{}",
+                        run
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fair_field_follows_the_air_layouts() {
+        let fib = PROGRAM_REGISTRY.lookup("fib").unwrap();
+        let mulfib2 = fib.air_layout(Some("mulfib2")).unwrap();
+        assert_eq!("f128", fair_field(fib, &[mulfib2], None).unwrap());
+        assert_eq!("f128", fair_field(fib, &[mulfib2], Some("f128")).unwrap());
+        assert!(fair_field(fib, &[mulfib2], Some("f64")).is_err());
+
+        let ptrchase = PROGRAM_REGISTRY.lookup("ptrchase").unwrap();
+        let sorted = ptrchase.air_layout(Some("sorted")).unwrap();
+        assert_eq!("f64", fair_field(ptrchase, &[sorted], None).unwrap());
+
+        let fft = PROGRAM_REGISTRY.lookup("fft").unwrap();
+        let layouts = fft.air_layouts_named(Some("all")).unwrap();
+        assert!(matches!(
+            fair_field(fft, &layouts, None),
+            Err(FairnessError::Mismatch { .. })
+        ));
+
+        assert_eq!(DEFAULT_FIELD, fair_field(fft, &[], None).unwrap());
+    }
+
+    #[test]
+    fn fair_options_refuse_settings_air_lacks() {
+        let ptrchase = PROGRAM_REGISTRY.lookup("ptrchase").unwrap();
+        let sorted = ptrchase.air_layout(None).unwrap();
+        let requested = FractalPipelineOptions {
+            folding_factor: 2,
+            ..r1cs_options_for(sorted)
+        };
+        assert!(matches!(
+            fair_options(ptrchase, &[sorted], 3, &SYSTEMS, &requested),
+            Err(FairnessError::UnsupportedByAir { .. })
+        ));
+
+        let requested = FractalPipelineOptions {
+            field: "f128".to_string(),
+            ..r1cs_options_for(sorted)
+        };
+        assert!(matches!(
+            fair_options(ptrchase, &[sorted], 3, &SYSTEMS, &requested),
+            Err(FairnessError::Mismatch { .. })
+        ));
+    }
+}
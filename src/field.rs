@@ -0,0 +1,128 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Runtime selection of the base field the R1CS path is instantiated with.
+//!
+//! R1CS instances can be proven over each winter-math base field: `f62`, `f64` and `f128`. The
+//! jsnark fixtures were generated for `f64`, so their constants and wire values have to be checked
+//! against the chosen modulus (see [parse_constant](crate::jsnark::parse_constant)), and a
+//! `.wires` file computed modulo one prime does not satisfy the constraints modulo another; the
+//! `check` subcommand reports which fields a fixture is valid for.
+//!
+//! The algebraic hash functions only work over their own field, so the base field and the hasher
+//! are chosen together by [with_r1cs_field](crate::with_r1cs_field).
+
+use thiserror::Error;
+use winter_math::StarkField;
+use winter_utils::Serializable;
+
+use crate::hash::HashError;
+
+pub use winter_math::fields::{f128, f62, f64};
+
+/// Base fields the R1CS path supports.
+pub const R1CS_FIELDS: [&str; 3] = ["f62", "f64", "f128"];
+
+/// Base field used when none is given, and the one the jsnark fixtures were generated for.
+pub const DEFAULT_FIELD: &str = "f64";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FieldError {
+    #[error("Unsupported field {0}, expected f62, f64 or f128")]
+    Unsupported(String),
+    #[error(transparent)]
+    Hash(#[from] HashError),
+}
+
+/// Checks that the R1CS path can be instantiated over `field`.
+pub fn check_r1cs_field(field: &str) -> Result<(), FieldError> {
+    if R1CS_FIELDS.contains(&field) {
+        Ok(())
+    } else {
+        Err(FieldError::Unsupported(field.to_string()))
+    }
+}
+
+/// The modulus of `B`. All winter-math base fields fit in 128 bits.
+pub fn modulus<B: StarkField>() -> u128 {
+    let bytes = B::get_modulus_le_bytes();
+    let mut buffer = [0u8; 16];
+    buffer[..bytes.len()].copy_from_slice(&bytes);
+    u128::from_le_bytes(buffer)
+}
+
+/// `values` as canonical integers, so that outputs of runs over different proof systems can be
+/// compared when they are in the same field.
+pub fn canonical_ints<B: StarkField>(values: &[B]) -> Vec<u128> {
+    values
+        .iter()
+        .map(|value| {
+            let bytes = value.to_bytes();
+            let mut buffer = [0u8; 16];
+            buffer[..bytes.len()].copy_from_slice(&bytes);
+            u128::from_le_bytes(buffer)
+        })
+        .collect()
+}
+
+/// Evaluates `$body` with the type alias `$B` bound to the base field named by `$field`,
+/// returning `Ok` with its value, or a [FieldError](crate::field::FieldError) if the name is not
+/// one of [R1CS_FIELDS](crate::field::R1CS_FIELDS).
+#[macro_export]
+macro_rules! with_base_field {
+    ($field:expr, $B:ident => $body:expr) => {
+        match $field {
+            "f62" => {
+                type $B = $crate::field::f62::BaseElement;
+                Ok($body)
+            }
+            "f64" => {
+                type $B = $crate::field::f64::BaseElement;
+                Ok($body)
+            }
+            "f128" => {
+                type $B = $crate::field::f128::BaseElement;
+                Ok($body)
+            }
+            other => Err($crate::field::FieldError::Unsupported(other.to_string())),
+        }
+    };
+}
+
+/// Like [with_base_field](crate::with_base_field), and also binds `$H` to the hasher named by
+/// `$hash_fn` over that field, as [with_r1cs_hasher](crate::with_r1cs_hasher) does. Each field
+/// only offers its own algebraic hash.
+///
+/// ```ignore
+/// with_r1cs_field!(field, hash_fn, B, H => {
+///     FractalPipeline::<B, B, H>::new(options)
+/// })
+/// ```
+#[macro_export]
+macro_rules! with_r1cs_field {
+    ($field:expr, $hash_fn:expr, $B:ident, $H:ident => $body:expr) => {
+        match $field {
+            "f62" => {
+                type $B = $crate::field::f62::BaseElement;
+                $crate::with_r1cs_hasher!(
+                    $hash_fn, $B, ["rp62_248" => $crate::hash::hashers::Rp62_248], $H => $body
+                )
+                .map_err($crate::field::FieldError::from)
+            }
+            "f64" => {
+                type $B = $crate::field::f64::BaseElement;
+                $crate::with_r1cs_hasher!(
+                    $hash_fn, $B, ["rp64_256" => $crate::hash::hashers::Rp64_256], $H => $body
+                )
+                .map_err($crate::field::FieldError::from)
+            }
+            "f128" => {
+                type $B = $crate::field::f128::BaseElement;
+                $crate::with_r1cs_hasher!($hash_fn, $B, [], $H => $body)
+                    .map_err($crate::field::FieldError::from)
+            }
+            other => Err($crate::field::FieldError::Unsupported(other.to_string())),
+        }
+    };
+}
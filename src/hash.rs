@@ -0,0 +1,119 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Runtime selection of the hash function the R1CS path is instantiated with.
+//!
+//! The Fractal prover and verifier are generic over the hasher, so a hash function named on the
+//! command line has to be turned into a type. [with_r1cs_hasher](crate::with_r1cs_hasher) does
+//! this by expanding its body once per supported hasher. Names are the ones the AIR side accepts
+//! for `hash_fn`, so both arithmetizations can be measured with the same hash.
+
+use thiserror::Error;
+
+pub use winter_crypto::hashers;
+
+/// Hash functions the R1CS path supports. The algebraic Rescue Prime hashes `rp62_248` and
+/// `rp64_256` only work over their own base field, see [algebraic_hash_field].
+pub const R1CS_HASH_FNS: [&str; 5] = [
+    "blake3_256",
+    "blake3_192",
+    "sha3_256",
+    "rp62_248",
+    "rp64_256",
+];
+
+/// Hash function used when none is given.
+pub const DEFAULT_HASH_FN: &str = "blake3_256";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HashError {
+    #[error("Unsupported hash function {hash_fn} for {system}, expected one of {supported}")]
+    Unsupported {
+        hash_fn: String,
+        system: &'static str,
+        supported: String,
+    },
+    #[error("Hash function {hash_fn} only works over the {required} field, not {field}")]
+    WrongField {
+        hash_fn: String,
+        field: String,
+        required: &'static str,
+    },
+}
+
+impl HashError {
+    pub fn unsupported(hash_fn: &str, system: &'static str, supported: &[&str]) -> Self {
+        HashError::Unsupported {
+            hash_fn: hash_fn.to_string(),
+            system,
+            supported: supported.join(", "),
+        }
+    }
+}
+
+/// The base field an algebraic hash function is defined over, or `None` for the others.
+pub fn algebraic_hash_field(hash_fn: &str) -> Option<&'static str> {
+    match hash_fn {
+        "rp62_248" => Some("f62"),
+        "rp64_256" => Some("f64"),
+        _ => None,
+    }
+}
+
+/// Checks that the R1CS path can be instantiated with `hash_fn` over `field`.
+pub fn check_r1cs_hash_fn(hash_fn: &str, field: &str) -> Result<(), HashError> {
+    if !R1CS_HASH_FNS.contains(&hash_fn) {
+        return Err(HashError::unsupported(hash_fn, "r1cs", &R1CS_HASH_FNS));
+    }
+    match algebraic_hash_field(hash_fn) {
+        Some(required) if required != field => Err(HashError::WrongField {
+            hash_fn: hash_fn.to_string(),
+            field: field.to_string(),
+            required,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Evaluates `$body` with the type alias `$H` bound to the hasher named by `$hash_fn` over the
+/// base field `$B`, returning `Ok` with its value, or a [HashError](crate::hash::HashError) if
+/// there is no such hasher. The generic hashers work over any field; the algebraic ones which
+/// work over `$B` are listed in brackets, which is what
+/// [with_r1cs_field](crate::with_r1cs_field) does.
+///
+/// ```ignore
+/// with_r1cs_hasher!(hash_fn, BaseElement, ["rp64_256" => Rp64_256], H => {
+///     FractalPipeline::<BaseElement, BaseElement, H>::new(options)
+/// })
+/// ```
+#[macro_export]
+macro_rules! with_r1cs_hasher {
+    ($hash_fn:expr, $B:ty, [$($name:literal => $algebraic:ty),*], $H:ident => $body:expr) => {
+        match $hash_fn {
+            "blake3_256" => {
+                type $H = $crate::hash::hashers::Blake3_256<$B>;
+                Ok($body)
+            }
+            "blake3_192" => {
+                type $H = $crate::hash::hashers::Blake3_192<$B>;
+                Ok($body)
+            }
+            "sha3_256" => {
+                type $H = $crate::hash::hashers::Sha3_256<$B>;
+                Ok($body)
+            }
+            $(
+                $name => {
+                    type $H = $algebraic;
+                    Ok($body)
+                }
+            )*
+            other => Err($crate::hash::HashError::unsupported(
+                other,
+                "r1cs",
+                &["blake3_256", "blake3_192", "sha3_256", $($name),*],
+            )),
+        }
+    };
+}
@@ -0,0 +1,356 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! A content-addressed cache of R1CS indexes, shared by every run and bench on the machine.
+//!
+//! Indexing A, B and C is the most expensive part of preprocessing, yet the index only depends
+//! on the matrices and the [IndexParams]: not on the hash function, the field extension or the
+//! FRI parameters. [FractalPipeline::index](crate::pipeline::FractalPipeline::index) therefore
+//! looks the index up under a key hashed from the circuit's
+//! [fingerprint](crate::sparse::SparseR1CS::fingerprint), the index parameters and the field
+//! modulus, so a sweep re-run with a different prover setting skips indexing. The
+//! `IndexDomains` are a function of the index parameters alone and are rebuilt on a hit rather
+//! than stored.
+//!
+//! Each entry is a file named after its key, laid out as
+//!
+//! | field              | encoding                                                     |
+//! |--------------------|--------------------------------------------------------------|
+//! | magic              | `b"FRIX"`                                                    |
+//! | version            | u16, currently 1                                             |
+//! | key                | u64, the cache key                                           |
+//! | index              | u64 length, then the serialized `Index`                      |
+//! | checksum           | u64 FNV-1a of every preceding byte                           |
+//!
+//! The cache is kept under a size limit by evicting the least recently used entries after each
+//! store, and can be pruned explicitly with `arith-bench cache prune`.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use fractal_indexer::index::{Index, IndexParams};
+use winter_math::StarkField;
+use winter_utils::{Deserializable, Serializable};
+
+use crate::binary::{fnv1a, ChecksumReader, ChecksumWriter, FNV_OFFSET_BASIS};
+use crate::proof_file::{
+    check_checksum, finish_checksum, read_bytes, read_preamble, write_bytes, ProofFileError,
+};
+
+pub const INDEX_MAGIC: [u8; 4] = *b"FRIX";
+pub const INDEX_VERSION: u16 = 1;
+
+/// Where indexes are cached unless the options say otherwise.
+pub const DEFAULT_INDEX_CACHE_DIR: &str = "target/index-cache";
+/// The size the cache is kept under unless the options say otherwise: 4 GiB.
+pub const DEFAULT_INDEX_CACHE_LIMIT: u64 = 4 << 30;
+
+const ENTRY_EXTENSION: &str = "index";
+
+/// The cache key of the index of a circuit with `fingerprint`, indexed with `params`.
+pub fn index_cache_key<B: StarkField>(fingerprint: u64, params: &IndexParams<B>) -> u64 {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, &B::get_modulus_le_bytes());
+    hash = fnv1a(hash, &fingerprint.to_le_bytes());
+    for value in [
+        params.num_input_variables,
+        params.num_constraints,
+        params.num_non_zero,
+        params.max_degree,
+    ] {
+        hash = fnv1a(hash, &(value as u64).to_le_bytes());
+    }
+    hash = fnv1a(hash, &Serializable::to_bytes(&params.eta));
+    fnv1a(hash, &Serializable::to_bytes(&params.eta_k))
+}
+
+// INDEX CACHE
+// ================================================================================================
+
+/// A directory of cached indexes, kept under `limit` bytes.
+#[derive(Debug, Clone)]
+pub struct IndexCache {
+    dir: PathBuf,
+    limit: u64,
+}
+
+/// What [IndexCache::prune] removed and what is left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub removed: usize,
+    pub removed_bytes: u64,
+    pub kept: usize,
+    pub kept_bytes: u64,
+}
+
+impl IndexCache {
+    pub fn new(dir: &str, limit: u64) -> Self {
+        IndexCache {
+            dir: PathBuf::from(dir),
+            limit,
+        }
+    }
+
+    /// The file the index with `key` is cached in.
+    pub fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.{}", key, ENTRY_EXTENSION))
+    }
+
+    /// The cached index with `key`, or `None` if there is none. A hit marks the entry as
+    /// recently used.
+    pub fn load<B: StarkField>(&self, key: u64) -> Result<Option<Index<B>>, ProofFileError> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(&path)?;
+        let mut reader = ChecksumReader::new(BufReader::new(file.try_clone()?));
+        read_preamble(&mut reader, INDEX_MAGIC, INDEX_VERSION, "cached index")?;
+        let mut found = [0u8; 8];
+        reader.read_exact(&mut found)?;
+        if u64::from_le_bytes(found) != key {
+            return Err(ProofFileError::Malformed(format!(
+                "{} is not the cached index {:016x}",
+                path.display(),
+                key
+            )));
+        }
+        let index_bytes = read_bytes(&mut reader)?;
+        check_checksum(reader)?;
+        let index = Index::<B>::read_from_bytes(&index_bytes)
+            .map_err(|err| ProofFileError::Deserialization(format!("cached index: {}", err)))?;
+
+        // recency is tracked by modification time; failing to update it only affects eviction
+        let _ = file.set_modified(SystemTime::now());
+        Ok(Some(index))
+    }
+
+    /// Caches `index` under `key`, then evicts the least recently used entries until the cache
+    /// fits in its limit. The entry is written to a temporary file first, so concurrent runs never
+    /// read a partial entry.
+    pub fn store<B: StarkField>(
+        &self,
+        key: u64,
+        index: &Index<B>,
+    ) -> Result<PruneSummary, ProofFileError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        let mut writer = ChecksumWriter::new(BufWriter::new(File::create(&tmp_path)?));
+        writer.write_all(&INDEX_MAGIC)?;
+        writer.write_all(&INDEX_VERSION.to_le_bytes())?;
+        writer.write_all(&key.to_le_bytes())?;
+        write_bytes(&mut writer, &index.to_bytes())?;
+        finish_checksum(writer)?;
+        fs::rename(&tmp_path, &path)?;
+        self.prune(self.limit)
+    }
+
+    /// Removes the least recently used entries until the cache holds at most `limit` bytes.
+    pub fn prune(&self, limit: u64) -> Result<PruneSummary, ProofFileError> {
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|entry| entry.modified);
+        let mut summary = PruneSummary {
+            kept: entries.len(),
+            kept_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+            ..PruneSummary::default()
+        };
+        for entry in entries {
+            if summary.kept_bytes <= limit {
+                break;
+            }
+            fs::remove_file(&entry.path)?;
+            summary.removed += 1;
+            summary.removed_bytes += entry.bytes;
+            summary.kept -= 1;
+            summary.kept_bytes -= entry.bytes;
+        }
+        Ok(summary)
+    }
+
+    fn entries(&self) -> Result<Vec<CacheEntry>, ProofFileError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if !is_entry(&path) {
+                continue;
+            }
+            let metadata = fs::metadata(&path)?;
+            entries.push(CacheEntry {
+                modified: metadata.modified()?,
+                bytes: metadata.len(),
+                path,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+struct CacheEntry {
+    path: PathBuf,
+    modified: SystemTime,
+    bytes: u64,
+}
+
+fn is_entry(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some(ENTRY_EXTENSION)
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use winter_math::fields::f64::BaseElement;
+
+    use super::*;
+    use crate::binary::R1csInstance;
+    use crate::hash::hashers::Blake3_256;
+    use crate::pipeline::{FractalPipeline, FractalPipelineOptions};
+
+    type Pipeline = FractalPipeline<BaseElement, BaseElement, Blake3_256<BaseElement>>;
+
+    fn temp_dir(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("index-cache-{}-{}", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn cached_pipeline(cache_dir: &str, num_queries: usize) -> Pipeline {
+        Pipeline::new(FractalPipelineOptions {
+            index_cache_dir: Some(cache_dir.to_string()),
+            num_queries,
+            ..FractalPipelineOptions::default()
+        })
+        .unwrap()
+    }
+
+    fn fixture(pipeline: &Pipeline, name: &str) -> R1csInstance<BaseElement> {
+        let path = format!("{}/src/jsnark_outputs/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let mut instance = pipeline.parse(&path, None).unwrap();
+        instance.expose_public_io();
+        instance
+    }
+
+    #[test]
+    fn index_is_reused_across_prover_settings() {
+        let dir = temp_dir("hit");
+        let pipeline = cached_pipeline(&dir, 16);
+        let fib = fixture(&pipeline, "fibonacciexample_5");
+        let fft = fixture(&pipeline, "fftexample_5");
+
+        let first = pipeline.index(&fib.r1cs).unwrap();
+        // the index does not depend on the FRI parameters
+        let other_settings = cached_pipeline(&dir, 32);
+        let second = other_settings.index(&fib.r1cs).unwrap();
+        let other_circuit = pipeline.index(&fft.r1cs).unwrap();
+
+        assert!(!first.from_cache);
+        assert!(second.from_cache);
+        assert!(!other_circuit.from_cache);
+        assert_eq!(
+            first.index.to_bytes(),
+            second.index.to_bytes(),
+            "the cached index differs from the one built"
+        );
+
+        // keys made from the cached index prove and verify
+        let (prover_key, verifier_key) = other_settings.keygen(second).unwrap();
+        let public = fib.public_io();
+        let proof = other_settings
+            .prove(&prover_key, fib.wires, &public)
+            .unwrap();
+        let verified = other_settings.verify(&verifier_key, &proof, &public);
+        fs::remove_dir_all(&dir).unwrap();
+        verified.unwrap();
+    }
+
+    #[test]
+    fn corrupted_entry_is_rebuilt() {
+        let dir = temp_dir("corrupted");
+        let pipeline = cached_pipeline(&dir, 16);
+        let fib = fixture(&pipeline, "fibonacciexample_5");
+        let indexed = pipeline.index(&fib.r1cs).unwrap();
+        let cache = IndexCache::new(&dir, DEFAULT_INDEX_CACHE_LIMIT);
+        let key = index_cache_key(indexed.fingerprint, &indexed.params);
+
+        let path = cache.path(key);
+        let mut bytes = fs::read(&path).unwrap();
+        // the last byte of the index, so that only the checksum catches it
+        let last_index_byte = bytes.len() - 8 - 1;
+        bytes[last_index_byte] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        let corrupted = cache.load::<BaseElement>(key).map(|index| index.is_some());
+        let rebuilt = pipeline.index(&fib.r1cs).unwrap();
+        let reloaded = cache.load::<BaseElement>(key).map(|index| index.is_some());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(corrupted, Err(ProofFileError::ChecksumMismatch)));
+        assert!(!rebuilt.from_cache);
+        assert!(reloaded.unwrap());
+    }
+
+    #[test]
+    fn prune_evicts_the_least_recently_used() {
+        let dir = temp_dir("prune");
+        let pipeline = cached_pipeline(&dir, 16);
+        let fib = fixture(&pipeline, "fibonacciexample_5");
+        let index = pipeline.index(&fib.r1cs).unwrap().index;
+        fs::remove_dir_all(&dir).unwrap();
+
+        // three entries of the same size, stored a minute apart, then the oldest is used again
+        let cache = IndexCache::new(&dir, DEFAULT_INDEX_CACHE_LIMIT);
+        let start = SystemTime::now() - Duration::from_secs(600);
+        for key in 1..=3 {
+            cache.store(key, &index).unwrap();
+            File::options()
+                .write(true)
+                .open(cache.path(key))
+                .unwrap()
+                .set_modified(start + Duration::from_secs(60 * key))
+                .unwrap();
+        }
+        assert!(cache.load::<BaseElement>(1).unwrap().is_some());
+        // files which are not entries are left alone
+        fs::write(Path::new(&dir).join("notes.txt"), b"kept").unwrap();
+
+        let entry_bytes = fs::metadata(cache.path(1)).unwrap().len();
+        let pruned = cache.prune(2 * entry_bytes);
+        let remaining = [1, 2, 3].map(|key| cache.path(key).exists());
+        let emptied = cache.prune(0);
+        let notes_kept = Path::new(&dir).join("notes.txt").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            pruned.unwrap(),
+            PruneSummary {
+                removed: 1,
+                removed_bytes: entry_bytes,
+                kept: 2,
+                kept_bytes: 2 * entry_bytes,
+            }
+        );
+        assert_eq!(remaining, [true, false, true]);
+        assert_eq!(
+            emptied.unwrap(),
+            PruneSummary {
+                removed: 2,
+                removed_bytes: 2 * entry_bytes,
+                kept: 0,
+                kept_bytes: 0,
+            }
+        );
+        assert!(notes_kept);
+    }
+}
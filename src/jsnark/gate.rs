@@ -0,0 +1,268 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_math::StarkField;
+
+use super::JsnarkError;
+use crate::field::modulus;
+
+/// The operations which can appear on a line of a jsnark `.arith` file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GateKind<B: StarkField> {
+    Input,
+    NizkInput,
+    Output,
+    Add,
+    Mul,
+    ConstMul(B),
+    ConstMulNeg(B),
+    Split,
+    Pack,
+    ZeroP,
+    Assert,
+    Xor,
+    Or,
+}
+
+/// One line of a jsnark `.arith` file. For `input` and `nizkinput` the declared wire is the
+/// single output, for `output` it is the single input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gate<B: StarkField> {
+    pub kind: GateKind<B>,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+    /// 1-based line number in the `.arith` file.
+    pub line: usize,
+}
+
+impl<B: StarkField> Gate<B> {
+    /// Parses a gate line, without its trailing comment. Returns `None` for blank lines.
+    pub fn parse(text: &str, line: usize) -> Result<Option<Self>, JsnarkError> {
+        let text = text.split('#').next().unwrap_or_default().trim();
+        let op = match text.split_whitespace().next() {
+            Some(op) => op,
+            None => return Ok(None),
+        };
+        let malformed = |message: String| JsnarkError::Malformed { line, message };
+
+        let kind = match op {
+            "input" | "nizkinput" | "output" => {
+                let wire = text[op.len()..]
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| malformed(format!("expected a wire id after {}", op)))?;
+                let gate = match op {
+                    "output" => Gate {
+                        kind: GateKind::Output,
+                        inputs: vec![wire],
+                        outputs: vec![],
+                        line,
+                    },
+                    "input" => Gate {
+                        kind: GateKind::Input,
+                        inputs: vec![],
+                        outputs: vec![wire],
+                        line,
+                    },
+                    _ => Gate {
+                        kind: GateKind::NizkInput,
+                        inputs: vec![],
+                        outputs: vec![wire],
+                        line,
+                    },
+                };
+                return Ok(Some(gate));
+            }
+            "add" => GateKind::Add,
+            "mul" => GateKind::Mul,
+            "split" => GateKind::Split,
+            "pack" => GateKind::Pack,
+            "zerop" => GateKind::ZeroP,
+            "assert" => GateKind::Assert,
+            "xor" => GateKind::Xor,
+            "or" => GateKind::Or,
+            _ => {
+                if let Some(constant) = op.strip_prefix("const-mul-neg-") {
+                    GateKind::ConstMulNeg(parse_constant(constant, line)?)
+                } else if let Some(constant) = op.strip_prefix("const-mul-") {
+                    GateKind::ConstMul(parse_constant(constant, line)?)
+                } else {
+                    return Err(malformed(format!("unknown gate {}", op)));
+                }
+            }
+        };
+
+        // the wire lists are the two <...> groups following `in` and `out`
+        let lists = text
+            .split('<')
+            .skip(1)
+            .map(|group| {
+                let group = group
+                    .split('>')
+                    .next()
+                    .ok_or_else(|| malformed("unterminated wire list".to_string()))?;
+                group
+                    .split_whitespace()
+                    .map(|wire| {
+                        wire.parse::<usize>()
+                            .map_err(|_| malformed(format!("invalid wire id {}", wire)))
+                    })
+                    .collect::<Result<Vec<usize>, JsnarkError>>()
+            })
+            .collect::<Result<Vec<Vec<usize>>, JsnarkError>>()?;
+        let (inputs, outputs) = match <[Vec<usize>; 2]>::try_from(lists) {
+            Ok([inputs, outputs]) => (inputs, outputs),
+            Err(_) => {
+                return Err(malformed(
+                    "expected an input and an output wire list".into(),
+                ))
+            }
+        };
+
+        let gate = Gate {
+            kind,
+            inputs,
+            outputs,
+            line,
+        };
+        gate.check_arity().map_err(malformed)?;
+        Ok(Some(gate))
+    }
+
+    fn check_arity(&self) -> Result<(), String> {
+        let (num_inputs, num_outputs) = (self.inputs.len(), self.outputs.len());
+        let valid = match self.kind {
+            GateKind::Input | GateKind::NizkInput | GateKind::Output => true,
+            GateKind::Add => num_inputs >= 1 && num_outputs == 1,
+            GateKind::Mul | GateKind::Assert | GateKind::Xor | GateKind::Or => {
+                num_inputs == 2 && num_outputs == 1
+            }
+            GateKind::ConstMul(_) | GateKind::ConstMulNeg(_) => num_inputs == 1 && num_outputs == 1,
+            GateKind::Split => num_inputs == 1 && num_outputs >= 1,
+            GateKind::Pack => num_inputs >= 1 && num_outputs == 1,
+            GateKind::ZeroP => num_inputs == 1 && num_outputs == 2,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "gate has {} inputs and {} outputs",
+                num_inputs, num_outputs
+            ))
+        }
+    }
+
+    /// The R1CS constraints enforcing this gate, as (A, B, C) rows over wire ids. Wire 0 is the
+    /// constant 1.
+    pub fn constraints(&self) -> Vec<[Vec<(usize, B)>; 3]> {
+        let one = vec![(0, B::ONE)];
+        match self.kind {
+            GateKind::Input | GateKind::NizkInput | GateKind::Output => vec![],
+            GateKind::Add => vec![[
+                self.inputs.iter().map(|wire| (*wire, B::ONE)).collect(),
+                one,
+                vec![(self.outputs[0], B::ONE)],
+            ]],
+            GateKind::Mul | GateKind::Assert => vec![[
+                vec![(self.inputs[0], B::ONE)],
+                vec![(self.inputs[1], B::ONE)],
+                vec![(self.outputs[0], B::ONE)],
+            ]],
+            GateKind::ConstMul(constant) => vec![[
+                vec![(self.inputs[0], constant)],
+                one,
+                vec![(self.outputs[0], B::ONE)],
+            ]],
+            GateKind::ConstMulNeg(constant) => vec![[
+                vec![(self.inputs[0], -constant)],
+                one,
+                vec![(self.outputs[0], B::ONE)],
+            ]],
+            GateKind::Split => {
+                // every output is a bit, and the bits recompose the input
+                let mut constraints: Vec<[Vec<(usize, B)>; 3]> = self
+                    .outputs
+                    .iter()
+                    .map(|bit| {
+                        [
+                            vec![(*bit, B::ONE)],
+                            vec![(*bit, B::ONE)],
+                            vec![(*bit, B::ONE)],
+                        ]
+                    })
+                    .collect();
+                constraints.push([
+                    weighted_bits(&self.outputs),
+                    one,
+                    vec![(self.inputs[0], B::ONE)],
+                ]);
+                constraints
+            }
+            GateKind::Pack => vec![[
+                weighted_bits(&self.inputs),
+                one,
+                vec![(self.outputs[0], B::ONE)],
+            ]],
+            GateKind::ZeroP => {
+                // outputs are (inverse, is_non_zero): x * inverse = is_non_zero and
+                // x * (1 - is_non_zero) = 0
+                let (x, inverse, is_non_zero) = (self.inputs[0], self.outputs[0], self.outputs[1]);
+                vec![
+                    [
+                        vec![(x, B::ONE)],
+                        vec![(inverse, B::ONE)],
+                        vec![(is_non_zero, B::ONE)],
+                    ],
+                    [
+                        vec![(x, B::ONE)],
+                        vec![(0, B::ONE), (is_non_zero, -B::ONE)],
+                        vec![],
+                    ],
+                ]
+            }
+            GateKind::Xor | GateKind::Or => {
+                // xor: 2x * y = x + y - z, or: x * y = x + y - z
+                let factor = match self.kind {
+                    GateKind::Xor => B::ONE.double(),
+                    _ => B::ONE,
+                };
+                let (x, y, z) = (self.inputs[0], self.inputs[1], self.outputs[0]);
+                vec![[
+                    vec![(x, factor)],
+                    vec![(y, B::ONE)],
+                    vec![(x, B::ONE), (y, B::ONE), (z, -B::ONE)],
+                ]]
+            }
+        }
+    }
+}
+
+/// The linear combination sum(2^i * bits[i]).
+fn weighted_bits<B: StarkField>(bits: &[usize]) -> Vec<(usize, B)> {
+    let mut power_of_two = B::ONE;
+    bits.iter()
+        .map(|bit| {
+            let term = (*bit, power_of_two);
+            power_of_two = power_of_two.double();
+            term
+        })
+        .collect()
+}
+
+/// Parses a hexadecimal jsnark constant, read from `line`, into a field element. Constants must be
+/// below the modulus: a fixture generated for another field is rejected rather than reduced.
+pub fn parse_constant<B: StarkField>(hex: &str, line: usize) -> Result<B, JsnarkError> {
+    let value = u128::from_str_radix(hex, 16).map_err(|_| JsnarkError::Malformed {
+        line,
+        message: format!("invalid constant {}", hex),
+    })?;
+    if value >= modulus::<B>() {
+        return Err(JsnarkError::OutOfField {
+            line,
+            value: hex.to_string(),
+            modulus: format!("{:x}", modulus::<B>()),
+        });
+    }
+    Ok(B::from(value))
+}
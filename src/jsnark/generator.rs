@@ -0,0 +1,148 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Writes the `.arith` files of jsnark generators, for fixtures which ship with their `.in` files
+//! only. The files are written gate for gate as jsnark writes them, so the shipped `.in` and
+//! `.wires` files apply to them.
+
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+
+use super::JsnarkError;
+use crate::circuit::CircuitKind;
+use crate::registry::ProgramSpec;
+
+/// Constant factor of the jsnark multiplicative Fibonacci sequence, as the hex constant of its
+/// `const-mul` gates.
+const FIB_MULTIPLIER_HEX: &str = "14";
+
+/// Writes the `.arith` file of `spec` at log2 size `size` to `path`. Only the Fibonacci generator
+/// is implemented, since its fixtures are the only ones shipped without `.arith` files. An
+/// existing file is never overwritten.
+pub fn generate_arith_file(spec: &ProgramSpec, size: u64, path: &str) -> Result<(), JsnarkError> {
+    if spec.r1cs_circuit != Some(CircuitKind::MulFib) {
+        return Err(JsnarkError::NoGenerator(spec.name));
+    }
+    let io_error = |source| JsnarkError::Io {
+        path: path.to_string(),
+        source,
+    };
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+    write_fibonacci_arith(size as u32, &mut writer).map_err(io_error)?;
+    writer.flush().map_err(io_error)
+}
+
+/// The jsnark Fibonacci circuit over `2^log_length` terms, x_i = x_(i-1) * 20 * x_(i-2) from
+/// the inputs on wires 2 and 3. Wire 1 holds the zero jsnark derives from the one-input wire,
+/// and term `i` ends up on wire `2i + 1`.
+pub fn write_fibonacci_arith<W: Write>(log_length: u32, out: &mut W) -> io::Result<()> {
+    let length = 1usize << log_length;
+    writeln!(out, "total {}", 2 * length)?;
+    writeln!(out, "input 0\t\t\t # The one-input wire.")?;
+    writeln!(out, "const-mul-0 in 1 <0> out 1 <1>")?;
+    writeln!(out, "input 2\t\t\t # init1")?;
+    writeln!(out, "input 3\t\t\t # init2")?;
+    let (mut previous, mut current) = (2, 3);
+    for i in 2..length {
+        writeln!(
+            out,
+            "const-mul-{} in 1 <{}> out 1 <{}>",
+            FIB_MULTIPLIER_HEX,
+            previous,
+            2 * i
+        )?;
+        writeln!(
+            out,
+            "mul in 2 <{} {}> out 1 <{}>",
+            current,
+            2 * i,
+            2 * i + 1
+        )?;
+        previous = current;
+        current = 2 * i + 1;
+    }
+    writeln!(out, "output {}\t\t\t # output of fibonacci[0]", current)
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::binary::load_instance;
+    use crate::circuit::programs::mulfib_circuit;
+    use crate::field::f64::BaseElement;
+    use crate::registry::{ProgramTag, JSNARK_OUTPUTS_DIR, PROGRAM_REGISTRY};
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("generator-{}-{}", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn fibonacci_arith_matches_the_shipped_fixtures() {
+        // fibonacciexample_8 and _11 ship the circuits of sizes 7 and 10
+        for size in (5..16).filter(|size| ![8, 11].contains(size)) {
+            let mut generated = vec![];
+            write_fibonacci_arith(size, &mut generated).unwrap();
+            let path = format!("{JSNARK_OUTPUTS_DIR}/fibonacciexample_{size}.arith");
+            let shipped = fs::read(&path).unwrap();
+            assert!(generated == shipped, "{} differs from the generator", path);
+        }
+    }
+
+    #[test]
+    fn generated_fixture_loads_with_its_shipped_inputs() {
+        let fib = PROGRAM_REGISTRY.get(ProgramTag::Fibonacci);
+        let size = 19;
+        let path = temp_path("fibonacciexample_19");
+        let arith_file = format!("{path}.arith");
+        generate_arith_file(fib, size, &arith_file).unwrap();
+        assert!(matches!(
+            generate_arith_file(fib, size, &arith_file),
+            Err(JsnarkError::Io { .. })
+        ));
+
+        let inputs = format!("{JSNARK_OUTPUTS_DIR}/fibonacciexample_{size}.in");
+        let instance = load_instance::<BaseElement>(&path, Some(&inputs));
+        fs::remove_file(&arith_file).unwrap();
+
+        let instance = instance.unwrap();
+        let circuit = mulfib_circuit::<BaseElement>(size as u32);
+        assert_eq!(instance.public_io().outputs, circuit.output_values());
+
+        // the matrices grow with the number of constraints and nothing else
+        let small = load_instance::<BaseElement>(&fib.r1cs_path(15, None).unwrap(), None).unwrap();
+        let (large, small) = (
+            instance.r1cs.bytes_per_constraint(),
+            small.r1cs.bytes_per_constraint(),
+        );
+        assert!(
+            (0.9..1.1).contains(&(large / small)),
+            "{:.1} bytes per constraint at size 2^{}, {:.1} at 2^15",
+            large,
+            size,
+            small
+        );
+    }
+
+    #[test]
+    fn other_programs_have_no_generator() {
+        let fft = PROGRAM_REGISTRY.get(ProgramTag::FFT);
+        assert!(matches!(
+            generate_arith_file(fft, 5, &temp_path("fftexample_5.arith")),
+            Err(JsnarkError::NoGenerator("fft"))
+        ));
+    }
+}
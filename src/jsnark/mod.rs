@@ -0,0 +1,225 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Streaming readers for the jsnark `.arith`, `.wires` and `.in` files, a gate evaluator which
+//! computes the wire assignment from an `.in` file, and a writer for `.arith` files which are not
+//! shipped.
+//!
+//! Files are read one line at a time into a reused buffer, and gates are turned into sparse
+//! constraints as they are read, so peak memory is proportional to the number of non-zero
+//! matrix entries rather than to the size of the file.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::marker::PhantomData;
+
+use thiserror::Error;
+use winter_math::StarkField;
+
+use crate::public::PublicWires;
+use crate::sparse::SparseR1CS;
+
+mod gate;
+pub use gate::{parse_constant, Gate, GateKind};
+
+mod witness;
+pub use witness::{generate_witness, parse_arith_file_with_inputs, WitnessGenerator};
+
+mod generator;
+pub use generator::{generate_arith_file, write_fibonacci_arith};
+
+#[derive(Debug, Error)]
+pub enum JsnarkError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Malformed line {line}: {message}")]
+    Malformed { line: usize, message: String },
+    #[error("Line {line} reads wire {wire}, which has no value")]
+    Unassigned { line: usize, wire: usize },
+    #[error("Line {line}: {message}")]
+    Evaluation { line: usize, message: String },
+    #[error("Line {line}: value 0x{value} is not below the field modulus 0x{modulus}")]
+    OutOfField {
+        line: usize,
+        value: String,
+        modulus: String,
+    },
+    #[error("No .arith generator for {0}; generate its fixtures with jsnark")]
+    NoGenerator(&'static str),
+}
+
+// ARITH READER
+// ================================================================================================
+
+/// Iterates over the gates of a `.arith` file. The `total <num_wires>` header, if present, is
+/// available from [ArithReader::num_wires] once it has been read, and the `input` and `output`
+/// wires read so far from [ArithReader::public_wires].
+pub struct ArithReader<B: StarkField, R: BufRead> {
+    path: String,
+    reader: R,
+    buffer: String,
+    line: usize,
+    num_wires: Option<usize>,
+    public_wires: PublicWires,
+    _field: PhantomData<B>,
+}
+
+impl<B: StarkField> ArithReader<B, BufReader<File>> {
+    pub fn open(path: &str) -> Result<Self, JsnarkError> {
+        let file = File::open(path).map_err(|source| JsnarkError::Io {
+            path: path.to_string(),
+            source,
+        })?;
+        Ok(Self::new(path, BufReader::new(file)))
+    }
+}
+
+impl<B: StarkField, R: BufRead> ArithReader<B, R> {
+    pub fn new(path: &str, reader: R) -> Self {
+        ArithReader {
+            path: path.to_string(),
+            reader,
+            buffer: String::new(),
+            line: 0,
+            num_wires: None,
+            public_wires: PublicWires::default(),
+            _field: PhantomData,
+        }
+    }
+
+    pub fn num_wires(&self) -> Option<usize> {
+        self.num_wires
+    }
+
+    pub fn public_wires(&self) -> &PublicWires {
+        &self.public_wires
+    }
+
+    fn next_gate(&mut self) -> Result<Option<Gate<B>>, JsnarkError> {
+        loop {
+            self.buffer.clear();
+            let read =
+                self.reader
+                    .read_line(&mut self.buffer)
+                    .map_err(|source| JsnarkError::Io {
+                        path: self.path.clone(),
+                        source,
+                    })?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            if let Some(total) = self.buffer.trim().strip_prefix("total") {
+                let num_wires =
+                    total
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| JsnarkError::Malformed {
+                            line: self.line,
+                            message: "expected a wire count after total".to_string(),
+                        })?;
+                self.num_wires = Some(num_wires);
+                continue;
+            }
+            if let Some(gate) = Gate::parse(&self.buffer, self.line)? {
+                match gate.kind {
+                    GateKind::Input => self.public_wires.inputs.push(gate.outputs[0]),
+                    GateKind::Output => self.public_wires.outputs.push(gate.inputs[0]),
+                    _ => {}
+                }
+                return Ok(Some(gate));
+            }
+        }
+    }
+}
+
+impl<B: StarkField, R: BufRead> Iterator for ArithReader<B, R> {
+    type Item = Result<Gate<B>, JsnarkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_gate().transpose()
+    }
+}
+
+/// Reads a `.arith` file into sparse R1CS matrices, one gate at a time, together with its public
+/// wires.
+pub fn parse_arith_file<B: StarkField>(
+    path: &str,
+) -> Result<(SparseR1CS<B>, PublicWires), JsnarkError> {
+    let mut reader = ArithReader::<B, _>::open(path)?;
+    let mut r1cs = SparseR1CS::new(0);
+    for gate in reader.by_ref() {
+        for [a, b, c] in gate?.constraints() {
+            r1cs.push_constraint(&a, &b, &c);
+        }
+    }
+    if let Some(num_wires) = reader.num_wires() {
+        r1cs.set_num_cols(num_wires);
+    }
+    Ok((r1cs, reader.public_wires().clone()))
+}
+
+/// Reads the public wires of a `.arith` file without building its constraints.
+pub fn read_public_wires<B: StarkField>(path: &str) -> Result<PublicWires, JsnarkError> {
+    let mut reader = ArithReader::<B, _>::open(path)?;
+    for gate in reader.by_ref() {
+        gate?;
+    }
+    Ok(reader.public_wires().clone())
+}
+
+// WIRES READER
+// ================================================================================================
+
+/// Reads a `.wires` file of `<wire id> <hex value>` lines. Wires which are not listed are 0; the
+/// result has at least `num_wires` entries.
+pub fn read_wires_file<B: StarkField>(path: &str, num_wires: usize) -> Result<Vec<B>, JsnarkError> {
+    let mut wires = vec![B::ZERO; num_wires];
+    for_each_assignment(path, |wire, value| {
+        if wire >= wires.len() {
+            wires.resize(wire + 1, B::ZERO);
+        }
+        wires[wire] = value;
+    })?;
+    Ok(wires)
+}
+
+/// Reads the (wire id, value) pairs of an `.in` file.
+pub fn read_inputs_file<B: StarkField>(path: &str) -> Result<Vec<(usize, B)>, JsnarkError> {
+    let mut inputs = vec![];
+    for_each_assignment(path, |wire, value| inputs.push((wire, value)))?;
+    Ok(inputs)
+}
+
+/// Calls `assign` on every `<wire id> <hex value>` line of a `.wires` or `.in` file.
+fn for_each_assignment<B: StarkField, F: FnMut(usize, B)>(
+    path: &str,
+    mut assign: F,
+) -> Result<(), JsnarkError> {
+    let io_error = |source| JsnarkError::Io {
+        path: path.to_string(),
+        source,
+    };
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+    let mut buffer = String::new();
+    let mut line = 0;
+    while reader.read_line(&mut buffer).map_err(io_error)? != 0 {
+        line += 1;
+        let mut tokens = buffer.split_whitespace();
+        if let (Some(wire), Some(value)) = (tokens.next(), tokens.next()) {
+            let malformed = |message: String| JsnarkError::Malformed { line, message };
+            let wire = wire
+                .parse::<usize>()
+                .map_err(|_| malformed(format!("invalid wire id {}", wire)))?;
+            assign(wire, parse_constant(value, line)?);
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_math::StarkField;
+
+use super::{read_inputs_file, ArithReader, Gate, GateKind, JsnarkError};
+use crate::public::PublicWires;
+use crate::sparse::SparseR1CS;
+
+/// Computes the full wire assignment of a jsnark circuit from its input wires by evaluating the
+/// gates in file order, which jsnark emits topologically sorted.
+pub struct WitnessGenerator<B: StarkField> {
+    values: Vec<Option<B>>,
+}
+
+impl<B: StarkField> WitnessGenerator<B> {
+    /// Starts from the assignment of an `.in` file; wires missing from it are unassigned.
+    pub fn new(inputs: &[(usize, B)]) -> Self {
+        let mut generator = WitnessGenerator { values: vec![] };
+        for (wire, value) in inputs {
+            generator.set(*wire, *value);
+        }
+        generator
+    }
+
+    fn set(&mut self, wire: usize, value: B) {
+        if wire >= self.values.len() {
+            self.values.resize(wire + 1, None);
+        }
+        self.values[wire] = Some(value);
+    }
+
+    fn get(&self, wire: usize, gate: &Gate<B>) -> Result<B, JsnarkError> {
+        self.values
+            .get(wire)
+            .copied()
+            .flatten()
+            .ok_or(JsnarkError::Unassigned {
+                line: gate.line,
+                wire,
+            })
+    }
+
+    /// Assigns the outputs of `gate` from its inputs.
+    pub fn evaluate(&mut self, gate: &Gate<B>) -> Result<(), JsnarkError> {
+        let inputs = gate
+            .inputs
+            .iter()
+            .map(|wire| self.get(*wire, gate))
+            .collect::<Result<Vec<B>, JsnarkError>>()?;
+
+        match gate.kind {
+            // inputs must come from the .in file
+            GateKind::Input | GateKind::NizkInput => {
+                self.get(gate.outputs[0], gate)?;
+            }
+            GateKind::Output => {}
+            GateKind::Add => {
+                let sum = inputs.iter().fold(B::ZERO, |sum, value| sum + *value);
+                self.set(gate.outputs[0], sum);
+            }
+            GateKind::Mul => self.set(gate.outputs[0], inputs[0] * inputs[1]),
+            GateKind::ConstMul(constant) => self.set(gate.outputs[0], inputs[0] * constant),
+            GateKind::ConstMulNeg(constant) => self.set(gate.outputs[0], -(inputs[0] * constant)),
+            GateKind::Split => {
+                let value = inputs[0].as_int();
+                let one = B::PositiveInteger::from(1u32);
+                for (i, bit) in gate.outputs.iter().enumerate() {
+                    let bit_value = if (value >> i as u32) & one == one {
+                        B::ONE
+                    } else {
+                        B::ZERO
+                    };
+                    self.set(*bit, bit_value);
+                }
+                // the bits must recompose the input, i.e. it has to fit in the output width
+                let recomposed = gate.outputs.iter().rev().fold(B::ZERO, |sum, bit| {
+                    sum.double() + self.values[*bit].unwrap()
+                });
+                if recomposed != inputs[0] {
+                    return Err(JsnarkError::Evaluation {
+                        line: gate.line,
+                        message: format!(
+                            "value {} does not fit in {} bits",
+                            inputs[0],
+                            gate.outputs.len()
+                        ),
+                    });
+                }
+            }
+            GateKind::Pack => {
+                let packed = inputs
+                    .iter()
+                    .rev()
+                    .fold(B::ZERO, |sum, bit| sum.double() + *bit);
+                self.set(gate.outputs[0], packed);
+            }
+            GateKind::ZeroP => {
+                let (inverse, is_non_zero) = if inputs[0] == B::ZERO {
+                    (B::ZERO, B::ZERO)
+                } else {
+                    (inputs[0].inv(), B::ONE)
+                };
+                self.set(gate.outputs[0], inverse);
+                self.set(gate.outputs[1], is_non_zero);
+            }
+            GateKind::Assert => {
+                let product = inputs[0] * inputs[1];
+                match self.values.get(gate.outputs[0]).copied().flatten() {
+                    Some(expected) if expected != product => {
+                        return Err(JsnarkError::Evaluation {
+                            line: gate.line,
+                            message: format!("assertion failed: {} != {}", product, expected),
+                        });
+                    }
+                    Some(_) => {}
+                    None => self.set(gate.outputs[0], product),
+                }
+            }
+            GateKind::Xor => {
+                let (x, y) = (inputs[0], inputs[1]);
+                self.set(gate.outputs[0], x + y - (x * y).double());
+            }
+            GateKind::Or => {
+                let (x, y) = (inputs[0], inputs[1]);
+                self.set(gate.outputs[0], x + y - x * y);
+            }
+        }
+        Ok(())
+    }
+
+    /// The wire assignment, with at least `num_wires` entries. Wires no gate assigned are 0.
+    pub fn finish(self, num_wires: usize) -> Vec<B> {
+        let mut wires: Vec<B> = self
+            .values
+            .into_iter()
+            .map(|value| value.unwrap_or(B::ZERO))
+            .collect();
+        if wires.len() < num_wires {
+            wires.resize(num_wires, B::ZERO);
+        }
+        wires
+    }
+}
+
+/// Reads a `.arith` file and computes its wire assignment from the `.in` file in the same pass,
+/// returning the sparse R1CS matrices, the full assignment and the public wires.
+pub fn parse_arith_file_with_inputs<B: StarkField>(
+    arith_file: &str,
+    inputs_file: &str,
+) -> Result<(SparseR1CS<B>, Vec<B>, PublicWires), JsnarkError> {
+    let mut generator = WitnessGenerator::new(&read_inputs_file::<B>(inputs_file)?);
+    let mut reader = ArithReader::<B, _>::open(arith_file)?;
+    let mut r1cs = SparseR1CS::new(0);
+    for gate in reader.by_ref() {
+        let gate = gate?;
+        generator.evaluate(&gate)?;
+        for [a, b, c] in gate.constraints() {
+            r1cs.push_constraint(&a, &b, &c);
+        }
+    }
+    if let Some(num_wires) = reader.num_wires() {
+        r1cs.set_num_cols(num_wires);
+    }
+    let wires = generator.finish(r1cs.num_cols());
+    Ok((r1cs, wires, reader.public_wires().clone()))
+}
+
+/// Computes the wire assignment of the circuit in `arith_file` from the inputs in `inputs_file`.
+pub fn generate_witness<B: StarkField>(
+    arith_file: &str,
+    inputs_file: &str,
+) -> Result<Vec<B>, JsnarkError> {
+    let mut generator = WitnessGenerator::new(&read_inputs_file::<B>(inputs_file)?);
+    let mut reader = ArithReader::<B, _>::open(arith_file)?;
+    for gate in reader.by_ref() {
+        generator.evaluate(&gate?)?;
+    }
+    Ok(generator.finish(reader.num_wires().unwrap_or(0)))
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use winter_math::fields::f64::BaseElement;
+    use winter_math::FieldElement;
+
+    use super::*;
+    use crate::jsnark::read_wires_file;
+
+    fn gate(text: &str) -> Gate<BaseElement> {
+        Gate::parse(text, 1).unwrap().unwrap()
+    }
+
+    /// Evaluates `text` with wire `i` of `inputs` assigned to `inputs[i]`.
+    fn evaluate(text: &str, inputs: &[u64]) -> Result<Vec<BaseElement>, JsnarkError> {
+        let inputs = inputs
+            .iter()
+            .enumerate()
+            .map(|(wire, value)| (wire, BaseElement::new(*value)))
+            .collect::<Vec<_>>();
+        let mut generator = WitnessGenerator::new(&inputs);
+        generator.evaluate(&gate(text))?;
+        Ok(generator.finish(0))
+    }
+
+    fn fixture(name: &str) -> String {
+        format!("{}/src/jsnark_outputs/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn add() {
+        let wires = evaluate("add in 3 <0 1 2> out 1 <3>", &[1, 2, 3]).unwrap();
+        assert_eq!(wires[3], BaseElement::new(6));
+    }
+
+    #[test]
+    fn mul() {
+        let wires = evaluate("mul in 2 <0 1> out 1 <2>", &[6, 7]).unwrap();
+        assert_eq!(wires[2], BaseElement::new(42));
+    }
+
+    #[test]
+    fn const_mul() {
+        let wires = evaluate("const-mul-a in 1 <0> out 1 <1>", &[3]).unwrap();
+        assert_eq!(wires[1], BaseElement::new(30));
+    }
+
+    #[test]
+    fn const_mul_neg() {
+        let wires = evaluate("const-mul-neg-a in 1 <0> out 1 <1>", &[3]).unwrap();
+        assert_eq!(wires[1], -BaseElement::new(30));
+    }
+
+    #[test]
+    fn split() {
+        let wires = evaluate("split in 1 <0> out 4 <1 2 3 4>", &[0b1011]).unwrap();
+        let bits = [1, 1, 0, 1].map(BaseElement::new);
+        assert_eq!(wires[1..], bits);
+    }
+
+    #[test]
+    fn split_rejects_value_wider_than_outputs() {
+        let result = evaluate("split in 1 <0> out 2 <1 2>", &[0b100]);
+        assert!(matches!(result, Err(JsnarkError::Evaluation { .. })));
+    }
+
+    #[test]
+    fn unassigned_input() {
+        let result = evaluate("mul in 2 <0 1> out 1 <2>", &[6]);
+        assert!(matches!(result, Err(JsnarkError::Unassigned { wire: 1, .. })));
+    }
+
+    /// The FFT fixture uses add, mul, const-mul and const-mul-neg gates, the pointer chasing one
+    /// split gates as well; both must reproduce the assignment jsnark shipped with them.
+    #[test]
+    fn fixtures_match_shipped_wires() {
+        for name in ["fftexample_5", "pointer_chasing_10_iters/ptrchasingexample_3"] {
+            let path = fixture(name);
+            let wires = generate_witness::<BaseElement>(
+                &format!("{path}.arith"),
+                &format!("{path}.in"),
+            )
+            .unwrap();
+            let expected =
+                read_wires_file::<BaseElement>(&format!("{path}.wires"), wires.len()).unwrap();
+            assert_eq!(wires, expected, "assignment of {}", name);
+        }
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Fractal prover and verifier keys written to disk, so proof files can be verified without the
+//! circuit and the preprocessing of a circuit can be reused across runs.
+//!
+//! A key file is laid out like a [proof file](crate::proof_file), with the same header so the
+//! verifier can check that key and proof match:
+//!
+//! | field              | encoding                                                     |
+//! |--------------------|--------------------------------------------------------------|
+//! | magic              | `b"FRVK"` for verifier keys, `b"FRPK"` for prover keys       |
+//! | version            | u16, currently 6                                             |
+//! | header             | as in a proof file                                           |
+//! | fingerprint        | u64, see [SparseR1CS::fingerprint]                           |
+//! | index parameters   | variables, constraints, non-zeros, max degree: u64;          |
+//! |                    | eta, eta_k: field elements                                   |
+//! | variables          | u64, the number of R1CS variables                            |
+//! | key                | u64 length, then the serialized `VerifierKey` or `ProverKey` |
+//! | checksum           | u64 FNV-1a of every preceding byte                           |
+//!
+//! Neither the `FractalOptions` nor the `FractalProverOptions` are stored: they are rebuilt from
+//! the index parameters and the header, exactly as when the circuit is indexed.
+//!
+//! [SparseR1CS::fingerprint]: crate::sparse::SparseR1CS::fingerprint
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use fractal_indexer::{
+    index::IndexParams,
+    snark_keys::{ProverKey, VerifierKey},
+};
+use winter_crypto::ElementHasher;
+use winter_math::{FieldElement, StarkField};
+use winter_utils::{Deserializable, Serializable, SliceReader};
+
+use crate::binary::{read_u64, write_u64, ChecksumReader, ChecksumWriter};
+use crate::pipeline::{FractalPipeline, FractalProverKey, FractalVerifierKey};
+use crate::proof_file::{
+    check_checksum, finish_checksum, read_bytes, read_preamble, write_bytes, ProofFileError,
+    ProofHeader,
+};
+
+pub const VERIFIER_KEY_MAGIC: [u8; 4] = *b"FRVK";
+pub const PROVER_KEY_MAGIC: [u8; 4] = *b"FRPK";
+pub const KEY_VERSION: u16 = 6;
+
+// VERIFIER KEYS
+// ================================================================================================
+
+/// Writes `key` to `path`, recording `header` for the proofs it verifies.
+pub fn write_verifier_key<B: StarkField, H: ElementHasher<BaseField = B>>(
+    path: &str,
+    header: &ProofHeader,
+    key: &FractalVerifierKey<B, H>,
+) -> Result<(), ProofFileError> {
+    write_key_file(
+        path,
+        VERIFIER_KEY_MAGIC,
+        header,
+        key.fingerprint,
+        &key.params,
+        key.options.degree_fs,
+        &key.key.to_bytes(),
+    )
+}
+
+/// Reads the verifier key at `path`, rebuilding its options with those of `pipeline`, which must
+/// be the options recorded in the returned header.
+pub fn read_verifier_key<B, E, H>(
+    path: &str,
+    pipeline: &FractalPipeline<B, E, H>,
+) -> Result<(ProofHeader, FractalVerifierKey<B, H>), ProofFileError>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    let file = read_key_file::<B>(path, VERIFIER_KEY_MAGIC, "verifier key")?;
+    let key = VerifierKey::<B, H>::read_from_bytes(&file.key_bytes)
+        .map_err(|err| ProofFileError::Deserialization(format!("verifier key: {}", err)))?;
+    let (options, _) = pipeline.options_from_params(&file.params, file.degree_fs);
+    Ok((
+        file.header,
+        FractalVerifierKey {
+            key,
+            params: file.params,
+            fingerprint: file.fingerprint,
+            options,
+        },
+    ))
+}
+
+// PROVER KEYS
+// ================================================================================================
+
+/// Writes `key` to `path`, recording `header` for the proofs it generates.
+pub fn write_prover_key<B, E, H>(
+    path: &str,
+    header: &ProofHeader,
+    key: &FractalProverKey<B, E, H>,
+) -> Result<(), ProofFileError>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    write_key_file(
+        path,
+        PROVER_KEY_MAGIC,
+        header,
+        key.fingerprint,
+        &key.params,
+        key.options.degree_fs,
+        &key.key.to_bytes(),
+    )
+}
+
+/// Reads the prover key at `path`, rebuilding its options like [read_verifier_key].
+pub fn read_prover_key<B, E, H>(
+    path: &str,
+    pipeline: &FractalPipeline<B, E, H>,
+) -> Result<(ProofHeader, FractalProverKey<B, E, H>), ProofFileError>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    let file = read_key_file::<B>(path, PROVER_KEY_MAGIC, "prover key")?;
+    let key = ProverKey::<B, E, H>::read_from_bytes(&file.key_bytes)
+        .map_err(|err| ProofFileError::Deserialization(format!("prover key: {}", err)))?;
+    let (_, options) = pipeline.options_from_params(&file.params, file.degree_fs);
+    Ok((
+        file.header,
+        FractalProverKey {
+            key: key.into(),
+            params: file.params,
+            fingerprint: file.fingerprint,
+            options,
+        },
+    ))
+}
+
+// KEY CACHE
+// ================================================================================================
+
+/// A directory of prover and verifier keys, named after the program, size and options they were
+/// generated for. Keys are only reused for the circuit they were generated from: a key whose
+/// fingerprint differs from the circuit's is rejected as stale.
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn new(dir: &str) -> Self {
+        KeyCache {
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    /// The prover and verifier key paths for proofs described by `header`.
+    pub fn paths(&self, header: &ProofHeader) -> (PathBuf, PathBuf) {
+        let iterations = header
+            .iterations
+            .map(|iterations| format!("_i{}", iterations))
+            .unwrap_or_default();
+        let stem = format!(
+            "{}_{}{}_{}_{}_x{}",
+            header.program,
+            header.size,
+            iterations,
+            header.field,
+            header.hash_fn,
+            header.field_extension
+        );
+        (
+            self.dir.join(format!("{}.pk", stem)),
+            self.dir.join(format!("{}.vk", stem)),
+        )
+    }
+
+    /// The saved keys for proofs described by `header` of the circuit with `fingerprint`, or
+    /// `None` if there are none. Keys generated for another circuit or other options are an
+    /// error.
+    #[allow(clippy::type_complexity)]
+    pub fn load<B, E, H>(
+        &self,
+        header: &ProofHeader,
+        fingerprint: u64,
+        pipeline: &FractalPipeline<B, E, H>,
+    ) -> Result<Option<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>)>, ProofFileError>
+    where
+        B: StarkField,
+        E: FieldElement<BaseField = B>,
+        H: ElementHasher<BaseField = B>,
+    {
+        let (pk_path, vk_path) = self.paths(header);
+        if !pk_path.exists() || !vk_path.exists() {
+            return Ok(None);
+        }
+        let (pk_header, prover_key) = read_prover_key(&path_str(&pk_path), pipeline)?;
+        let (vk_header, verifier_key) = read_verifier_key(&path_str(&vk_path), pipeline)?;
+        for (found, key_fingerprint) in [
+            (&pk_header, prover_key.fingerprint),
+            (&vk_header, verifier_key.fingerprint),
+        ] {
+            if found != header {
+                return Err(ProofFileError::HeaderMismatch {
+                    key: found.describe(),
+                    proof: header.describe(),
+                });
+            }
+            if key_fingerprint != fingerprint {
+                return Err(ProofFileError::StaleKey {
+                    expected: fingerprint,
+                    found: key_fingerprint,
+                });
+            }
+        }
+        Ok(Some((prover_key, verifier_key)))
+    }
+
+    /// Saves `prover_key` and `verifier_key` for proofs described by `header`, creating the
+    /// directory if needed.
+    pub fn save<B, E, H>(
+        &self,
+        header: &ProofHeader,
+        prover_key: &FractalProverKey<B, E, H>,
+        verifier_key: &FractalVerifierKey<B, H>,
+    ) -> Result<(), ProofFileError>
+    where
+        B: StarkField,
+        E: FieldElement<BaseField = B>,
+        H: ElementHasher<BaseField = B>,
+    {
+        fs::create_dir_all(&self.dir)?;
+        let (pk_path, vk_path) = self.paths(header);
+        write_prover_key(&path_str(&pk_path), header, prover_key)?;
+        write_verifier_key(&path_str(&vk_path), header, verifier_key)
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// The contents of a key file, with the key still serialized.
+struct KeyFile<B: StarkField> {
+    header: ProofHeader,
+    fingerprint: u64,
+    params: IndexParams<B>,
+    degree_fs: usize,
+    key_bytes: Vec<u8>,
+}
+
+fn write_key_file<B: StarkField>(
+    path: &str,
+    magic: [u8; 4],
+    header: &ProofHeader,
+    fingerprint: u64,
+    params: &IndexParams<B>,
+    degree_fs: usize,
+    key_bytes: &[u8],
+) -> Result<(), ProofFileError> {
+    let mut writer = ChecksumWriter::new(BufWriter::new(File::create(path)?));
+    writer.write_all(&magic)?;
+    writer.write_all(&KEY_VERSION.to_le_bytes())?;
+    header.write_into(&mut writer)?;
+    writer.write_all(&fingerprint.to_le_bytes())?;
+
+    write_u64(&mut writer, params.num_input_variables)?;
+    write_u64(&mut writer, params.num_constraints)?;
+    write_u64(&mut writer, params.num_non_zero)?;
+    write_u64(&mut writer, params.max_degree)?;
+    writer.write_all(&Serializable::to_bytes(&params.eta))?;
+    writer.write_all(&Serializable::to_bytes(&params.eta_k))?;
+    write_u64(&mut writer, degree_fs)?;
+    write_bytes(&mut writer, key_bytes)?;
+    finish_checksum(writer)
+}
+
+fn read_key_file<B: StarkField>(
+    path: &str,
+    magic: [u8; 4],
+    kind: &'static str,
+) -> Result<KeyFile<B>, ProofFileError> {
+    let mut reader = ChecksumReader::new(BufReader::new(File::open(path)?));
+    read_preamble(&mut reader, magic, KEY_VERSION, kind)?;
+    let header = ProofHeader::read_from(&mut reader)?;
+    let mut fingerprint = [0u8; 8];
+    reader.read_exact(&mut fingerprint)?;
+
+    let num_input_variables = read_u64(&mut reader)?;
+    let num_constraints = read_u64(&mut reader)?;
+    let num_non_zero = read_u64(&mut reader)?;
+    let max_degree = read_u64(&mut reader)?;
+    let eta = read_element::<B, _>(&mut reader)?;
+    let eta_k = read_element::<B, _>(&mut reader)?;
+    let degree_fs = read_u64(&mut reader)?;
+    let key_bytes = read_bytes(&mut reader)?;
+    check_checksum(reader)?;
+
+    Ok(KeyFile {
+        header,
+        fingerprint: u64::from_le_bytes(fingerprint),
+        params: IndexParams::<B> {
+            num_input_variables,
+            num_constraints,
+            num_non_zero,
+            max_degree,
+            eta,
+            eta_k,
+        },
+        degree_fs,
+        key_bytes,
+    })
+}
+
+fn read_element<B: StarkField, R: Read>(reader: &mut R) -> Result<B, ProofFileError> {
+    let mut bytes = vec![0u8; B::ELEMENT_BYTES];
+    reader.read_exact(&mut bytes)?;
+    B::read_from(&mut SliceReader::new(&bytes))
+        .map_err(|err| ProofFileError::Deserialization(format!("field element: {}", err)))
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use winter_math::fields::f64::BaseElement;
+
+    use super::*;
+    use crate::binary::R1csInstance;
+    use crate::hash::hashers::Blake3_256;
+    use crate::pipeline::FractalPipelineOptions;
+
+    type Hasher = Blake3_256<BaseElement>;
+    type Pipeline = FractalPipeline<BaseElement, BaseElement, Hasher>;
+
+    fn pipeline() -> Pipeline {
+        Pipeline::new(FractalPipelineOptions {
+            index_cache_dir: None,
+            ..FractalPipelineOptions::default()
+        })
+        .unwrap()
+    }
+
+    fn temp_dir(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("keys-{}-{}", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The fib fixture of size 2^5 with its public wires exposed, the header of its proofs and
+    /// its keys.
+    #[allow(clippy::type_complexity)]
+    fn fib_keys(
+        pipeline: &Pipeline,
+    ) -> (
+        R1csInstance<BaseElement>,
+        ProofHeader,
+        FractalProverKey<BaseElement, BaseElement, Hasher>,
+        FractalVerifierKey<BaseElement, Hasher>,
+    ) {
+        let path = format!(
+            "{}/src/jsnark_outputs/fibonacciexample_5",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut instance = pipeline.parse(&path, None).unwrap();
+        instance.expose_public_io();
+        let indexed = pipeline.index(&instance.r1cs).unwrap();
+        let (prover_key, verifier_key) = pipeline.keygen(indexed).unwrap();
+        let header = ProofHeader::for_r1cs("fib", 5, None, pipeline.options());
+        (instance, header, prover_key, verifier_key)
+    }
+
+    #[test]
+    fn saved_keys_prove_and_verify() {
+        let pipeline = pipeline();
+        let (instance, header, prover_key, verifier_key) = fib_keys(&pipeline);
+        let cache = KeyCache::new(&temp_dir("round-trip"));
+        let fingerprint = instance.r1cs.fingerprint();
+        assert!(cache
+            .load(&header, fingerprint, &pipeline)
+            .unwrap()
+            .is_none());
+        cache.save(&header, &prover_key, &verifier_key).unwrap();
+        let loaded = cache.load(&header, fingerprint, &pipeline);
+        fs::remove_dir_all(&cache.dir).unwrap();
+
+        let (prover_key, verifier_key) = loaded.unwrap().unwrap();
+        assert_eq!(prover_key.fingerprint, fingerprint);
+        assert_eq!(verifier_key.fingerprint, fingerprint);
+        let public = instance.public_io();
+        let proof = pipeline
+            .prove(&prover_key, instance.wires, &public)
+            .unwrap();
+        pipeline.verify(&verifier_key, &proof, &public).unwrap();
+    }
+
+    #[test]
+    fn rejects_stale_and_mismatched_keys() {
+        let pipeline = pipeline();
+        let (instance, header, prover_key, verifier_key) = fib_keys(&pipeline);
+        let cache = KeyCache::new(&temp_dir("stale"));
+        cache.save(&header, &prover_key, &verifier_key).unwrap();
+        let fingerprint = instance.r1cs.fingerprint();
+        let stale = cache.load(&header, fingerprint ^ 1, &pipeline);
+        // the key paths do not depend on the proof parameters, so these keys are found
+        let other_header = ProofHeader {
+            num_queries: header.num_queries + 1,
+            ..header.clone()
+        };
+        let mismatched = cache.load(&other_header, fingerprint, &pipeline);
+        fs::remove_dir_all(&cache.dir).unwrap();
+
+        assert!(matches!(
+            stale,
+            Err(ProofFileError::StaleKey { expected, found })
+                if expected == fingerprint ^ 1 && found == fingerprint
+        ));
+        assert!(matches!(
+            mismatched,
+            Err(ProofFileError::HeaderMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupted_and_swapped_key_files() {
+        let pipeline = pipeline();
+        let (_, header, prover_key, verifier_key) = fib_keys(&pipeline);
+        let cache = KeyCache::new(&temp_dir("corrupted"));
+        cache.save(&header, &prover_key, &verifier_key).unwrap();
+        let (pk_path, vk_path) = cache.paths(&header);
+        let (pk_path, vk_path) = (path_str(&pk_path), path_str(&vk_path));
+
+        let swapped = read_prover_key(&vk_path, &pipeline).map(|(header, _)| header);
+        let mut bytes = fs::read(&vk_path).unwrap();
+        // the last byte of the key, so that the file still parses and only the checksum
+        // catches it
+        let last_key_byte = bytes.len() - 8 - 1;
+        bytes[last_key_byte] ^= 1;
+        fs::write(&vk_path, &bytes).unwrap();
+        let corrupted = read_verifier_key(&vk_path, &pipeline).map(|(header, _)| header);
+        let intact = read_prover_key(&pk_path, &pipeline).map(|(header, _)| header);
+        fs::remove_dir_all(&cache.dir).unwrap();
+
+        assert!(matches!(
+            swapped,
+            Err(ProofFileError::BadMagic("prover key"))
+        ));
+        assert!(matches!(corrupted, Err(ProofFileError::ChecksumMismatch)));
+        assert_eq!(intact.unwrap(), header);
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Shared code for the orchestrator binaries and the criterion benches.
+
+#[cfg(feature = "flame_it")]
+extern crate flame;
+#[cfg(feature = "flame_it")]
+#[macro_use]
+extern crate flamer;
+
+pub mod air;
+pub mod binary;
+pub mod check;
+pub mod circuit;
+pub mod coset;
+pub mod extension;
+pub mod fair;
+pub mod field;
+pub mod hash;
+pub mod index_cache;
+pub mod jsnark;
+pub mod keys;
+pub mod pipeline;
+pub mod proof_file;
+pub mod proof_system;
+pub mod public;
+pub mod registry;
+pub mod report;
+pub mod security;
+pub mod sparse;
@@ -1,3 +1,989 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Single entry point for running the benchmark programs under every proof system.
+//
+// All sizes are given as log2 of the problem size: `-s 7` is a 2^7-point FFT for both R1CS
+// (`fftexample_7`) and AIR (`fft -n=128`).
+
+use std::time::Instant;
+
+use structopt::StructOpt;
+
+use arithmetization_benchmarks::air::{check_air_hash_fn, verify_air_proof_file, AirOptions};
+use arithmetization_benchmarks::binary::{
+    binary_path, convert_jsnark_files, load_instance, write_instance,
+};
+use arithmetization_benchmarks::check::{
+    check_fixture_field, check_instance, locate_constraints, FieldValidity,
+};
+use arithmetization_benchmarks::circuit::build_program_circuit;
+use arithmetization_benchmarks::extension::check_field_extension;
+use arithmetization_benchmarks::fair::{air_field, fair_field, fair_options};
+use arithmetization_benchmarks::field::{check_r1cs_field, DEFAULT_FIELD, R1CS_FIELDS};
+use arithmetization_benchmarks::hash::check_r1cs_hash_fn;
+use arithmetization_benchmarks::index_cache::IndexCache;
+use arithmetization_benchmarks::jsnark::generate_arith_file;
+use arithmetization_benchmarks::pipeline::{verify_r1cs_proof_file, FractalPipelineOptions};
+use arithmetization_benchmarks::proof_file::{default_vk_path, ProofFile};
+use arithmetization_benchmarks::proof_system::{build_proof_system, run_proof_system, R1csSource};
+use arithmetization_benchmarks::registry::{AirLayout, ProgramTag, SystemTag, PROGRAM_REGISTRY};
+use arithmetization_benchmarks::report::{check_same_output, write_reports, RunReport};
+use arithmetization_benchmarks::security::{check_target_reached, SecurityCeiling};
+use arithmetization_benchmarks::{with_base_field, with_field_extension, with_r1cs_field};
+use winter_math::StarkField;
+
+#[cfg(feature = "flame_it")]
+extern crate flame;
+#[cfg(feature = "flame_it")]
+#[macro_use]
+extern crate flamer;
+
+// COMMAND LINE INTERFACE
+// ================================================================================================
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "arith-bench",
+    about = "Prove and verify benchmark programs under AIR and R1CS proof systems"
+)]
+enum Command {
+    /// Generate a proof for a program.
+    Prove(RunOptions),
+    /// Generate a proof for a program and verify it, or verify a proof file.
+    Verify(RunOptions),
+    /// Prove and verify a program under several proof systems.
+    Compare(CompareOptions),
+    /// List the supported programs, proof systems and sizes.
+    List,
+    /// Convert jsnark .arith and .wires (or .in) files into the binary R1CS format.
+    Convert(ConvertOptions),
+    /// Check that the wire assignment satisfies every R1CS constraint, without proving.
+    Check(CheckOptions),
+    /// Write the jsnark .arith file of a fixture which ships without one (fib from size 16).
+    Generate(GenerateOptions),
+    /// Manage the cache of R1CS indexes.
+    Cache {
+        #[structopt(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum CacheCommand {
+    /// Evict the least recently used indexes until the cache fits in the size limit; pass
+    /// --index-cache-limit=0 to empty it.
+    Prune(IndexCacheFlags),
+}
+
+#[derive(StructOpt, Debug)]
+struct RunOptions {
+    /// Which program to run: fft, fib, ptrchase or sample.
+    #[structopt(short = "p", long = "program", default_value = "fft")]
+    program: ProgramTag,
+
+    /// Which proof system to use: air, r1cs or r1cs-batched.
+    #[structopt(short = "y", long = "system", default_value = "r1cs")]
+    system: SystemTag,
+
+    /// Log2 of the program size.
+    #[structopt(short = "s", long = "size", default_value = "5")]
+    size: u64,
+
+    /// Log2 of the number of iterations, for programs which take one (ptrchase).
+    #[structopt(short = "i", long = "iterations")]
+    iterations: Option<u64>,
+
+    /// AIR trace layout to prove the program under; defaults to the program's first layout.
+    /// `list` shows the layouts of each program.
+    #[structopt(long = "layout")]
+    layout: Option<String>,
+
+    /// Build the R1CS instance in Rust instead of reading the jsnark fixtures. Any size is
+    /// accepted.
+    #[structopt(long = "native")]
+    native: bool,
+
+    /// Jsnark .in file to compute the R1CS witness from, instead of the fixture's .wires file.
+    #[structopt(long = "inputs")]
+    inputs_file: Option<String>,
+
+    #[structopt(flatten)]
+    proof: ProofFlags,
+
+    #[structopt(flatten)]
+    files: FileFlags,
+
+    /// Verbose logging and reporting.
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Write a machine-readable run report to this .json or .csv file.
+    #[structopt(long = "report")]
+    report: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct CompareOptions {
+    /// Which program to run: fft, fib, ptrchase or sample.
+    #[structopt(short = "p", long = "program", default_value = "fft")]
+    program: ProgramTag,
+
+    /// Which proof systems to compare.  Comma-separated list.
+    #[structopt(short = "y", long = "systems", default_value = "air,r1cs")]
+    systems: String,
+
+    /// Log2 of the program size.
+    #[structopt(short = "s", long = "size", default_value = "5")]
+    size: u64,
+
+    /// Log2 of the number of iterations, for programs which take one (ptrchase).
+    #[structopt(short = "i", long = "iterations")]
+    iterations: Option<u64>,
+
+    /// AIR trace layouts to run, one AIR run each: a comma-separated list, or `all`. Defaults to
+    /// the program's first layout.
+    #[structopt(long = "layouts")]
+    layouts: Option<String>,
+
+    /// Build the R1CS instance in Rust instead of reading the jsnark fixtures. Any size is
+    /// accepted.
+    #[structopt(long = "native")]
+    native: bool,
+
+    /// Jsnark .in file to compute the R1CS witness from, instead of the fixture's .wires file.
+    #[structopt(long = "inputs")]
+    inputs_file: Option<String>,
+
+    #[structopt(flatten)]
+    proof: ProofFlags,
+
+    /// Verbose logging and reporting.
+    #[structopt(short = "v", long = "verbose")]
+    verbose: bool,
+
+    /// Write a machine-readable run report to this .json or .csv file.
+    #[structopt(long = "report")]
+    report: Option<String>,
+}
+
+/// Proof parameters shared by `prove`, `verify` and `compare`.
+#[derive(StructOpt, Debug, Clone)]
+struct ProofFlags {
+    /// Target security level in bits. Blowup, queries, grinding and folding are derived from it
+    /// for every proof system, instead of using each system's defaults.
+    #[structopt(long = "security")]
+    security: Option<u32>,
+
+    /// Force the same hash, field, extension degree, blowup, folding, queries and grinding on
+    /// every proof system, or refuse to run if one of them cannot honour a setting.
+    #[structopt(long = "fair")]
+    fair: bool,
+
+    /// Base field of the R1CS instance: f62, f64 or f128; f64 by default, and the field of the
+    /// AIR layouts in fair mode. The field of each AIR is fixed.
+    #[structopt(long = "field")]
+    field: Option<String>,
+
+    /// Hash function for every proof system: blake3_256, blake3_192, sha3_256, or the R1CS-only
+    /// rp62_248 (over f62) and rp64_256 (over f64).
+    #[structopt(long = "hash", default_value = "blake3_256")]
+    hash_fn: String,
+
+    /// Degree of the field extension for every proof system: 1 (none), 2 or 3.
+    #[structopt(long = "field-extension", default_value = "1")]
+    field_extension: u32,
+
+    /// Save the R1CS prover and verifier keys in this directory, and reuse them on later runs of
+    /// the same circuit with the same options instead of indexing it again.
+    #[structopt(long = "keys")]
+    key_dir: Option<String>,
+
+    /// Always index the R1CS instance instead of using the index cache.
+    #[structopt(long = "no-index-cache")]
+    no_index_cache: bool,
+
+    #[structopt(flatten)]
+    index_cache: IndexCacheFlags,
+}
+
+/// Location and size limit of the R1CS index cache.
+#[derive(StructOpt, Debug, Clone)]
+struct IndexCacheFlags {
+    /// Directory R1CS indexes are cached in across runs.
+    #[structopt(long = "index-cache", default_value = "target/index-cache")]
+    dir: String,
+
+    /// Size limit of the index cache in MiB, beyond which the least recently used indexes are
+    /// evicted.
+    #[structopt(long = "index-cache-limit", default_value = "4096")]
+    limit_mib: u64,
+}
+
+impl IndexCacheFlags {
+    fn limit_bytes(&self) -> u64 {
+        self.limit_mib << 20
+    }
+}
+
+/// Proof and verifier key files for `prove` and `verify`.
+#[derive(StructOpt, Debug, Clone, Default)]
+struct FileFlags {
+    /// Write the proof to this file, with a header recording the program and proof parameters.
+    #[structopt(long = "out")]
+    out: Option<String>,
+
+    /// Write the R1CS verifier key to this file; defaults to the --out file with a .vk extension.
+    #[structopt(long = "vk-out")]
+    vk_out: Option<String>,
+
+    /// Verify this proof file instead of proving (verify only). The program and proof parameters
+    /// are read from its header.
+    #[structopt(long = "proof")]
+    proof_file: Option<String>,
+
+    /// Verifier key to check an R1CS proof file against (verify only).
+    #[structopt(long = "vk")]
+    vk_file: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct ConvertOptions {
+    /// Program whose fixture to convert, at the given size and iterations.
+    #[structopt(short = "p", long = "program")]
+    program: Option<ProgramTag>,
+
+    /// Log2 of the program size.
+    #[structopt(short = "s", long = "size", default_value = "5")]
+    size: u64,
+
+    /// Log2 of the number of iterations, for programs which take one (ptrchase).
+    #[structopt(short = "i", long = "iterations")]
+    iterations: Option<u64>,
+
+    /// Jsnark .arith file to convert instead of a registered fixture.
+    #[structopt(short = "a", long = "arith")]
+    arith_file: Option<String>,
+
+    /// Jsnark .wires file, or an .in file to compute the wires from; defaults to the .arith file
+    /// with a .wires extension.
+    #[structopt(short = "w", long = "wires")]
+    wires_file: Option<String>,
+
+    /// Output file; defaults to the .arith file with a .r1bin extension.
+    #[structopt(short = "o", long = "out")]
+    out: Option<String>,
+
+    /// Base field to convert the instance for: f62, f64 or f128.
+    #[structopt(long = "field", default_value = "f64")]
+    field: String,
+}
+
+#[derive(StructOpt, Debug)]
+struct GenerateOptions {
+    /// Program whose fixture to generate; only fib has a generator.
+    #[structopt(short = "p", long = "program", default_value = "fib")]
+    program: ProgramTag,
+
+    /// Log2 of the program size.
+    #[structopt(short = "s", long = "size")]
+    size: u64,
+
+    /// Output file; defaults to the .arith file of the fixture in the registry. An existing file
+    /// is not overwritten.
+    #[structopt(short = "o", long = "out")]
+    out: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct CheckOptions {
+    /// Which program to check: fft, fib, ptrchase or sample.
+    #[structopt(short = "p", long = "program", default_value = "fft")]
+    program: ProgramTag,
+
+    /// Log2 of the program size.
+    #[structopt(short = "s", long = "size", default_value = "5")]
+    size: u64,
+
+    /// Log2 of the number of iterations, for programs which take one (ptrchase).
+    #[structopt(short = "i", long = "iterations")]
+    iterations: Option<u64>,
+
+    /// Check the natively built R1CS instance instead of the jsnark fixtures.
+    #[structopt(long = "native")]
+    native: bool,
+
+    /// Jsnark .in file to compute the witness from, instead of the fixture's .wires file.
+    #[structopt(long = "inputs")]
+    inputs_file: Option<String>,
+
+    /// Number of unsatisfied constraints to print.
+    #[structopt(long = "max-failures", default_value = "10")]
+    max_failures: usize,
+
+    /// Base field to check the instance in: f62, f64 or f128.
+    #[structopt(long = "field", default_value = "f64")]
+    field: String,
+
+    /// Report which base fields the fixture is valid for instead of checking it in one.
+    #[structopt(long = "all-fields")]
+    all_fields: bool,
+}
+
+// RUNNERS
+// ================================================================================================
+
 fn main() {
-    println!("Hello, world!");
+    match Command::from_args() {
+        Command::Prove(options) => {
+            if options.files.proof_file.is_some() || options.files.vk_file.is_some() {
+                panic!("--proof and --vk are only accepted by verify");
+            }
+            let report = run(&options, false);
+            let reports = [report];
+            save_reports(&reports, &options.report);
+            if !check_security(&reports, options.proof.security) {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify(options) => {
+            let report = match &options.files.proof_file {
+                Some(proof_file) => verify_proof_file(
+                    proof_file,
+                    options.files.vk_file.as_deref(),
+                    options.verbose,
+                ),
+                None => run(&options, true),
+            };
+            let reports = [report];
+            save_reports(&reports, &options.report);
+            if !check_security(&reports, options.proof.security) {
+                std::process::exit(1);
+            }
+        }
+        Command::Compare(options) => {
+            let (reports, outputs_match) = compare(&options);
+            // the reports are kept even if the outputs disagree, to help find out why
+            save_reports(&reports, &options.report);
+            let security_reached = check_security(&reports, options.proof.security);
+            if !outputs_match || !security_reached {
+                std::process::exit(1);
+            }
+        }
+        Command::List => list(),
+        Command::Convert(options) => convert(&options),
+        Command::Check(options) => check(&options),
+        Command::Generate(options) => generate(&options),
+        Command::Cache {
+            command: CacheCommand::Prune(flags),
+        } => prune_index_cache(&flags),
+    }
+}
+
+fn save_reports(reports: &[RunReport], report_path: &Option<String>) {
+    if let Some(path) = report_path {
+        write_reports(reports, path).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+        println!("Report written to {}", path);
+    }
+}
+
+fn run(options: &RunOptions, verify: bool) -> RunReport {
+    let layouts = air_layouts(
+        options.program,
+        &[options.system],
+        options.layout.as_deref(),
+    );
+    let (air_options, r1cs_options) = proof_options(
+        options.program,
+        &layouts,
+        options.size,
+        &[options.system],
+        &options.proof,
+        options.verbose,
+    );
+    run_with_options(options, &air_options, &r1cs_options, verify)
+}
+
+fn run_with_options(
+    options: &RunOptions,
+    air_options: &AirOptions,
+    r1cs_options: &FractalPipelineOptions,
+    verify: bool,
+) -> RunReport {
+    let spec = PROGRAM_REGISTRY.get(options.program);
+    let native = options.native && options.system == SystemTag::R1CS;
+    if options.native && options.system == SystemTag::R1CSPolyBatched {
+        panic!("--native is not supported by {}", options.system);
+    }
+    if options.layout.is_some() && options.system != SystemTag::AIR {
+        panic!("--layout only applies to {}", SystemTag::AIR);
+    }
+    if options.files.out.is_some() && options.system == SystemTag::R1CSPolyBatched {
+        panic!(
+            "--out is not supported by {}, whose keys are held by the fractal_examples orchestrator",
+            options.system
+        );
+    }
+    let iterations = if native {
+        options.iterations.or_else(|| spec.default_iterations())
+    } else {
+        spec.check_size(options.system, options.size)
+            .unwrap_or_else(|err| panic!("{}", err));
+        spec.resolve_iterations(options.iterations)
+            .unwrap_or_else(|err| panic!("{}", err))
+    };
+
+    println!("============================================================");
+    println!(
+        "Program {}, system {}, size 2^{}",
+        spec.name, options.system, options.size
+    );
+
+    let mut report = RunReport::new(spec.name, options.system.name(), options.size);
+    report.iterations = iterations;
+    if options.system == SystemTag::AIR {
+        let layout = spec
+            .air_layout(options.layout.as_deref())
+            .unwrap_or_else(|err| panic!("{}", err));
+        layout
+            .check_size(spec.name, options.size)
+            .unwrap_or_else(|err| panic!("{}", err));
+        println!("AIR layout {}", layout.name);
+        report.field = Some(air_field(layout).to_string());
+        report.hash_fn = Some(air_options.hash_fn.clone());
+        report.field_extension = Some(air_options.field_extension);
+    } else {
+        report.field = Some(r1cs_options.field.clone());
+        report.hash_fn = Some(r1cs_options.hash_fn.clone());
+        report.field_extension = Some(r1cs_options.field_extension);
+    }
+    run_system(
+        options,
+        iterations,
+        air_options,
+        r1cs_options,
+        verify,
+        &mut report,
+    );
+    println!("============================================================");
+    report
+}
+
+/// The AIR layouts of `program` named in `names`, see [ProgramSpec::air_layouts_named], if AIR is
+/// one of `systems`.
+///
+/// [ProgramSpec::air_layouts_named]: arithmetization_benchmarks::registry::ProgramSpec::air_layouts_named
+fn air_layouts(
+    program: ProgramTag,
+    systems: &[SystemTag],
+    names: Option<&str>,
+) -> Vec<&'static AirLayout> {
+    if !systems.contains(&SystemTag::AIR) {
+        return vec![];
+    }
+    PROGRAM_REGISTRY
+        .get(program)
+        .air_layouts_named(names)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Proof options for AIR and R1CS runs of `program` under `systems`, with AIR under each of
+/// `layouts`, with the field, hash function and field extension of `flags`, derived from the
+/// target security level if one is given and matched across the systems and layouts in fair mode.
+/// In fair mode R1CS runs over the field of the AIR layouts unless `--field` says otherwise.
+fn proof_options(
+    program: ProgramTag,
+    layouts: &[&AirLayout],
+    size: u64,
+    systems: &[SystemTag],
+    flags: &ProofFlags,
+    verbose: bool,
+) -> (AirOptions, FractalPipelineOptions) {
+    let hash_fn = flags.hash_fn.as_str();
+    let spec = PROGRAM_REGISTRY.get(program);
+    let field = if flags.fair {
+        fair_field(spec, layouts, flags.field.as_deref()).unwrap_or_else(|err| panic!("{}", err))
+    } else {
+        flags
+            .field
+            .clone()
+            .unwrap_or_else(|| DEFAULT_FIELD.to_string())
+    };
+    if systems.contains(&SystemTag::AIR) {
+        check_air_hash_fn(hash_fn).unwrap_or_else(|err| panic!("{}", err));
+    }
+    if systems.iter().any(|system| *system != SystemTag::AIR) {
+        check_r1cs_field(&field).unwrap_or_else(|err| panic!("{}", err));
+        check_r1cs_hash_fn(hash_fn, &field).unwrap_or_else(|err| panic!("{}", err));
+    }
+    check_field_extension(flags.field_extension).unwrap_or_else(|err| panic!("{}", err));
+    if flags.security.is_some() && systems.contains(&SystemTag::R1CSPolyBatched) {
+        panic!(
+            "--security is not supported by {}, whose parameters are fixed by the fractal_examples orchestrator",
+            SystemTag::R1CSPolyBatched
+        );
+    }
+    let (air_options, r1cs_options) = match flags.security {
+        Some(bits) => {
+            // each side's target is checked against its own field, and only if it runs
+            let air_options = if systems.contains(&SystemTag::AIR) {
+                let ceiling = layouts
+                    .iter()
+                    .map(|layout| {
+                        SecurityCeiling::of(air_field(layout), flags.field_extension, hash_fn)
+                            .unwrap_or_else(|err| panic!("{}", err))
+                    })
+                    .min_by_key(|ceiling| ceiling.field_bits)
+                    .expect("AIR runs under at least one layout");
+                AirOptions::for_security_level(bits, &ceiling)
+                    .unwrap_or_else(|err| panic!("{}", err))
+            } else {
+                AirOptions::default()
+            };
+            let r1cs_options = if systems.contains(&SystemTag::R1CS) {
+                let ceiling = SecurityCeiling::of(&field, flags.field_extension, hash_fn)
+                    .unwrap_or_else(|err| panic!("{}", err));
+                FractalPipelineOptions::for_security_level(bits, &ceiling)
+                    .unwrap_or_else(|err| panic!("{}", err))
+            } else {
+                FractalPipelineOptions::default()
+            };
+            (air_options, r1cs_options)
+        }
+        None => (AirOptions::default(), FractalPipelineOptions::default()),
+    };
+    let air_options = AirOptions {
+        hash_fn: hash_fn.to_string(),
+        field_extension: flags.field_extension,
+        ..air_options
+    };
+    let r1cs_options = FractalPipelineOptions {
+        field,
+        hash_fn: hash_fn.to_string(),
+        field_extension: flags.field_extension,
+        key_dir: flags.key_dir.clone(),
+        index_cache_dir: (!flags.no_index_cache).then(|| flags.index_cache.dir.clone()),
+        index_cache_limit: flags.index_cache.limit_bytes(),
+        verbose,
+        ..r1cs_options
+    };
+    if !flags.fair {
+        return (air_options, r1cs_options);
+    }
+    let (air_options, r1cs_options, params) =
+        fair_options(spec, layouts, size, systems, &r1cs_options)
+            .unwrap_or_else(|err| panic!("{}", err));
+    println!("Fair mode: {}", params);
+    (air_options, r1cs_options)
+}
+
+/// Runs every requested system and layout, and returns their reports and whether their outputs
+/// agree.
+fn compare(options: &CompareOptions) -> (Vec<RunReport>, bool) {
+    let systems = options
+        .systems
+        .split(',')
+        .map(|x| x.parse::<SystemTag>())
+        .collect::<Result<Vec<SystemTag>, _>>()
+        .unwrap_or_else(|err| panic!("{}", err));
+    let layouts = air_layouts(options.program, &systems, options.layouts.as_deref());
+    // options are derived once so that fair mode matches them across all systems and layouts
+    let (air_options, r1cs_options) = proof_options(
+        options.program,
+        &layouts,
+        options.size,
+        &systems,
+        &options.proof,
+        options.verbose,
+    );
+
+    let instance = PROGRAM_REGISTRY
+        .get(options.program)
+        .instance(options.size, options.iterations)
+        .unwrap_or_else(|err| panic!("{}", err));
+    println!("Comparing {} at {}", options.program, instance);
+
+    // AIR runs once per layout, the other systems once
+    let runs = systems.into_iter().flat_map(|system| {
+        if system == SystemTag::AIR {
+            layouts
+                .iter()
+                .map(|layout| (system, Some(layout.name.to_string())))
+                .collect::<Vec<_>>()
+        } else {
+            vec![(system, None)]
+        }
+    });
+    let reports = runs
+        .map(|(system, layout)| {
+            let run_options = RunOptions {
+                program: options.program,
+                system,
+                size: options.size,
+                iterations: options.iterations,
+                layout,
+                native: options.native,
+                inputs_file: options.inputs_file.clone(),
+                proof: options.proof.clone(),
+                files: FileFlags::default(),
+                verbose: options.verbose,
+                report: None,
+            };
+            run_with_options(&run_options, &air_options, &r1cs_options, true)
+        })
+        .collect::<Vec<RunReport>>();
+    let outputs_match = check_outputs(&reports, &layouts);
+    (reports, outputs_match)
+}
+
+/// Checks that every run of a comparison computed the same program output, against an R1CS run
+/// if there is one, since the R1CS fixtures always expose their output wires. Returns false if
+/// a run disagrees, or if its output cannot be checked; a failing AIR run is followed by the
+/// note of its layout, e.g. that it proves a different computation than the R1CS circuit.
+fn check_outputs(reports: &[RunReport], layouts: &[&AirLayout]) -> bool {
+    let reference = reports.iter().min_by_key(|report| {
+        (
+            report.outputs.is_none(),
+            report.system == SystemTag::AIR.name(),
+        )
+    });
+    let reference = match reference {
+        Some(reference) => reference,
+        None => return true,
+    };
+    let note = |report: &RunReport| {
+        let name = report.air_layout.as_deref()?;
+        let layout = layouts.iter().find(|layout| layout.name == name)?;
+        layout.output.note()
+    };
+    let mut outputs_match = true;
+    for report in reports
+        .iter()
+        .filter(|report| !std::ptr::eq(*report, reference))
+    {
+        match check_same_output(reference, report) {
+            Ok(()) => println!("Output of {} matches {}", report.label(), reference.label()),
+            Err(err) => {
+                println!("{}", err);
+                for run in [reference, report] {
+                    if let Some(note) = note(run) {
+                        println!("  {}: {}", run.label(), note);
+                    }
+                }
+                outputs_match = false;
+            }
+        }
+    }
+    outputs_match
+}
+
+/// Checks that every run reached the target security level, if there is one. The target is
+/// checked against the field before the runs, but the LDE domain of an instance can still take
+/// it below. Returns false if a run falls short.
+fn check_security(reports: &[RunReport], target: Option<u32>) -> bool {
+    let target = match target {
+        Some(target) => target,
+        None => return true,
+    };
+    let mut reached = true;
+    for report in reports {
+        if let Some(bits) = report.conjectured_security_bits {
+            if let Err(err) = check_target_reached(&report.label(), bits, target) {
+                println!("{}", err);
+                reached = false;
+            }
+        }
+    }
+    reached
+}
+
+fn prune_index_cache(flags: &IndexCacheFlags) {
+    let summary = IndexCache::new(&flags.dir, flags.limit_bytes())
+        .prune(flags.limit_bytes())
+        .unwrap_or_else(|err| panic!("{}", err));
+    println!(
+        "Removed {} indexes ({} MiB) from {}; {} indexes ({} MiB) remain",
+        summary.removed,
+        summary.removed_bytes >> 20,
+        flags.dir,
+        summary.kept,
+        summary.kept_bytes >> 20
+    );
+}
+
+fn list() {
+    println!("Sizes are log2 of the problem size and are shared by all proof systems.");
+    println!("AIR runs under the first layout listed unless --layout names another.");
+    for spec in PROGRAM_REGISTRY.programs() {
+        for system in SystemTag::ALL.iter() {
+            // one line per AIR layout
+            let lines: Vec<(String, Vec<u64>)> = if *system == SystemTag::AIR {
+                spec.air_layouts
+                    .iter()
+                    .map(|layout| {
+                        let sizes = layout.sizes.clone().collect();
+                        (format!("{}:{}", system, layout.name), sizes)
+                    })
+                    .collect()
+            } else {
+                // only the sizes whose fixtures are on disk
+                vec![(system.to_string(), spec.r1cs_fixture_sizes())]
+            };
+            for (name, sizes) in lines {
+                if sizes.is_empty() {
+                    continue;
+                }
+                print!(
+                    "{:<8} {:<14} sizes {}",
+                    spec.name,
+                    name,
+                    format_sizes(&sizes)
+                );
+                match &spec.iterations {
+                    Some(iterations) => {
+                        println!(", iterations {}..={}", iterations.start, iterations.end - 1)
+                    }
+                    None => println!(),
+                }
+            }
+        }
+    }
+}
+
+/// Increasing `sizes` as comma-separated runs, e.g. `5, 7..=12`.
+fn format_sizes(sizes: &[u64]) -> String {
+    let mut runs: Vec<(u64, u64)> = vec![];
+    for size in sizes {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == *size => *end = *size,
+            _ => runs.push((*size, *size)),
+        }
+    }
+    runs.iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}..={}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn convert(options: &ConvertOptions) {
+    let fixture_path = match (&options.arith_file, options.program) {
+        (Some(arith_file), _) => arith_file
+            .strip_suffix(".arith")
+            .unwrap_or(arith_file)
+            .to_string(),
+        (None, Some(program)) => PROGRAM_REGISTRY
+            .get(program)
+            .r1cs_path(options.size, options.iterations)
+            .unwrap_or_else(|err| panic!("{}", err)),
+        (None, None) => panic!("Either a program or an .arith file is required"),
+    };
+    let arith_file = format!("{fixture_path}.arith");
+    let wires_file = options
+        .wires_file
+        .clone()
+        .unwrap_or_else(|| format!("{fixture_path}.wires"));
+    let out = options
+        .out
+        .clone()
+        .unwrap_or_else(|| binary_path(&fixture_path));
+
+    with_base_field!(options.field.as_str(), B => {
+        convert_in_field::<B>(&arith_file, &wires_file, &out)
+    })
+    .unwrap_or_else(|err| panic!("{}", err));
+}
+
+fn convert_in_field<B: StarkField>(arith_file: &str, wires_file: &str, out: &str) {
+    let now = Instant::now();
+    let instance =
+        convert_jsnark_files::<B>(arith_file, wires_file).unwrap_or_else(|err| panic!("{}", err));
+    println!(
+        "Parsed {} constraints over {} wires in {} ms",
+        instance.r1cs.num_rows(),
+        instance.r1cs.num_cols(),
+        now.elapsed().as_millis()
+    );
+
+    let now = Instant::now();
+    write_instance(out, &instance).unwrap_or_else(|err| panic!("{}", err));
+    println!("Wrote {} in {} ms", out, now.elapsed().as_millis());
+}
+
+fn generate(options: &GenerateOptions) {
+    let spec = PROGRAM_REGISTRY.get(options.program);
+    let out = options.out.clone().unwrap_or_else(|| {
+        let path = spec
+            .expected_r1cs_path(options.size, None)
+            .unwrap_or_else(|err| panic!("{}", err));
+        format!("{path}.arith")
+    });
+    let now = Instant::now();
+    generate_arith_file(spec, options.size, &out).unwrap_or_else(|err| panic!("{}", err));
+    println!("Wrote {} in {} ms", out, now.elapsed().as_millis());
+}
+
+fn check(options: &CheckOptions) {
+    if options.all_fields {
+        check_all_fields(options);
+        return;
+    }
+    with_base_field!(options.field.as_str(), B => check_in_field::<B>(options))
+        .unwrap_or_else(|err| panic!("{}", err));
+}
+
+/// Prints, for every base field, whether the fixture is valid for it.
+fn check_all_fields(options: &CheckOptions) {
+    if options.native {
+        panic!("--all-fields checks jsnark fixtures and cannot be combined with --native");
+    }
+    let fixture_path = PROGRAM_REGISTRY
+        .get(options.program)
+        .r1cs_path(options.size, options.iterations)
+        .unwrap_or_else(|err| panic!("{}", err));
+    let inputs_file = options.inputs_file.as_deref();
+    let mut num_valid = 0;
+    for field in R1CS_FIELDS {
+        let validity = with_base_field!(field, B => {
+            check_fixture_field::<B>(&fixture_path, inputs_file)
+        })
+        .unwrap_or_else(|err| panic!("{}", err))
+        .unwrap_or_else(|err| panic!("{}", err));
+        if validity == FieldValidity::Valid {
+            num_valid += 1;
+        }
+        println!("{}: {}", field, validity);
+    }
+    if num_valid == 0 {
+        std::process::exit(1);
+    }
+}
+
+fn check_in_field<B: StarkField>(options: &CheckOptions) {
+    let spec = PROGRAM_REGISTRY.get(options.program);
+    let now = Instant::now();
+    let (r1cs, wires, arith_file) = if options.native {
+        let iterations = options.iterations.or_else(|| spec.default_iterations());
+        let circuit = build_program_circuit::<B>(spec, options.size, iterations)
+            .unwrap_or_else(|err| panic!("{}", err));
+        (circuit.to_sparse(), circuit.wires, None)
+    } else {
+        let fixture_path = spec
+            .r1cs_path(options.size, options.iterations)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let instance = load_instance::<B>(&fixture_path, options.inputs_file.as_deref())
+            .unwrap_or_else(|err| panic!("{}", err));
+        let arith_file = format!("{fixture_path}.arith");
+        (instance.r1cs, instance.wires, Some(arith_file))
+    };
+    println!("Loaded instance in {} ms", now.elapsed().as_millis());
+
+    let now = Instant::now();
+    let mut report =
+        check_instance(&r1cs, &wires, options.max_failures).unwrap_or_else(|err| panic!("{}", err));
+    println!("Checked instance in {} ms", now.elapsed().as_millis());
+    if let Some(arith_file) = arith_file.filter(|_| !report.is_satisfied()) {
+        locate_constraints(&mut report, &arith_file).unwrap_or_else(|err| panic!("{}", err));
+    }
+    println!("{}", report);
+    if !report.is_satisfied() {
+        std::process::exit(1);
+    }
+}
+
+/// Runs `options.system` through its `ProofSystem` implementation, and writes the proof (and
+/// for R1CS the verifier key) if `--out` is given.
+fn run_system(
+    options: &RunOptions,
+    iterations: Option<u64>,
+    air_options: &AirOptions,
+    r1cs_options: &FractalPipelineOptions,
+    verify: bool,
+    report: &mut RunReport,
+) {
+    let r1cs_source = match (&options.inputs_file, options.native) {
+        (Some(_), true) => panic!("--inputs and --native cannot be combined"),
+        (Some(inputs_file), false) => R1csSource::Inputs(inputs_file.clone()),
+        (None, true) => R1csSource::Native,
+        // the .wires files of the fixtures hold values modulo the default field, so fair mode,
+        // which runs R1CS over the field of the AIR, computes the witness from the .in file
+        (None, false)
+            if options.proof.fair
+                && options.system == SystemTag::R1CS
+                && r1cs_options.field != DEFAULT_FIELD =>
+        {
+            let fixture_path = PROGRAM_REGISTRY
+                .get(options.program)
+                .r1cs_path(options.size, iterations)
+                .unwrap_or_else(|err| panic!("{}", err));
+            R1csSource::Inputs(format!("{fixture_path}.in"))
+        }
+        (None, false) => R1csSource::Fixture,
+    };
+    let mut proof_system = build_proof_system(
+        PROGRAM_REGISTRY.get(options.program),
+        options.system,
+        options.size,
+        iterations,
+        options.layout.as_deref(),
+        air_options,
+        &r1cs_source,
+        r1cs_options,
+    )
+    .unwrap_or_else(|err| panic!("{}", err));
+    run_proof_system(proof_system.as_mut(), verify, report).unwrap_or_else(|err| panic!("{}", err));
+
+    if let Some(out) = &options.files.out {
+        let vk_out = options
+            .files
+            .vk_out
+            .clone()
+            .unwrap_or_else(|| default_vk_path(out));
+        proof_system
+            .write_proof(out, &vk_out)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+}
+
+/// Verifies a proof written by `prove --out`, with the program and proof parameters recorded in
+/// its header. R1CS proofs also need the verifier key written next to them.
+fn verify_proof_file(proof_path: &str, vk_path: Option<&str>, verbose: bool) -> RunReport {
+    let file = ProofFile::read(proof_path).unwrap_or_else(|err| panic!("{}", err));
+    let header = &file.header;
+    println!("============================================================");
+    println!("Verifying {}", header.describe());
+
+    let system = header
+        .system
+        .parse::<SystemTag>()
+        .unwrap_or_else(|err| panic!("{}", err));
+    let mut report = RunReport::new(&header.program, system.name(), header.size);
+    report.iterations = header.iterations;
+    report.air_layout = header.layout.clone();
+    report.field = Some(header.field.clone());
+    report.hash_fn = Some(header.hash_fn.clone());
+    report.field_extension = Some(header.field_extension);
+    if system == SystemTag::AIR {
+        verify_air_proof_file(&file, &mut report).unwrap_or_else(|err| panic!("{}", err));
+    } else {
+        let vk_path =
+            vk_path.unwrap_or_else(|| panic!("R1CS proofs are verified against a key: pass --vk"));
+        let (field, hash_fn) = (header.field.as_str(), header.hash_fn.as_str());
+        with_r1cs_field!(field, hash_fn, B, H => {
+            with_field_extension!(header.field_extension, B, E => {
+                verify_r1cs_proof_file::<B, E, H>(&file, vk_path, verbose, &mut report)
+            })
+        })
+        .unwrap_or_else(|err| panic!("{}", err))
+        .unwrap_or_else(|err| panic!("{}", err))
+        .unwrap_or_else(|err| panic!("{}", err));
+    }
+    println!("============================================================");
+    report
 }
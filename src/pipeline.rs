@@ -0,0 +1,668 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! The Fractal pipeline for an R1CS instance: parse → index → keygen → prove → verify.
+//!
+//! Each stage is a method of [FractalPipeline] which returns owned artifacts, so callers can time
+//! the stages separately, keep the keys around to prove several times, or stop after any stage.
+//! The R1CS [proof system](crate::proof_system::PipelineProofSystem) and `r1cs_benchmark.rs` both
+//! run their instances through it.
+
+use std::cmp::max;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Instant;
+
+use fractal_indexer::{
+    index::{build_index_domains, Index, IndexDomains, IndexParams},
+    indexed_matrix::index_matrix,
+    snark_keys::{generate_prover_and_verifier_keys, ProverKey, VerifierKey},
+};
+use fractal_proofs::{fft, FractalProverOptions, LayeredFractalProof};
+use fractal_prover::prover::FractalProver;
+use fractal_prover::{LayeredProver, LayeredSubProver};
+use fractal_utils::FractalOptions;
+use fractal_verifier::verifier::verify_layered_fractal_proof_from_top;
+use thiserror::Error;
+use winter_crypto::ElementHasher;
+use winter_fri::FriOptions;
+use winter_math::{get_power_series, FieldElement, StarkField};
+use winter_utils::Deserializable;
+
+use crate::binary::{load_instance, BinaryFormatError, R1csInstance};
+use crate::circuit::build_program_circuit;
+use crate::coset::{CosetError, CosetShifts};
+use crate::field::DEFAULT_FIELD;
+use crate::hash::DEFAULT_HASH_FN;
+use crate::index_cache::{
+    index_cache_key, IndexCache, DEFAULT_INDEX_CACHE_DIR, DEFAULT_INDEX_CACHE_LIMIT,
+};
+use crate::keys::read_verifier_key;
+use crate::proof_file::{ProofFile, ProofFileError};
+use crate::public::{PublicIo, PublicWires};
+use crate::registry::{ProgramSpec, RegistryError};
+use crate::report::RunReport;
+use crate::security::{
+    estimate_security, SecurityCeiling, SecurityError, SecurityParams, SecurityReport,
+};
+use crate::sparse::SparseR1CS;
+
+macro_rules! println_if {
+    ($verbose:expr, $($x:tt)*) => { if $verbose { println!($($x)*) } }
+}
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error(transparent)]
+    Load(#[from] BinaryFormatError),
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error(transparent)]
+    Coset(#[from] CosetError),
+    #[error("Key generation failed: {0}")]
+    Keygen(String),
+    #[error("Proof generation failed: {0}")]
+    Prove(String),
+    #[error("Failed to verify proof: {0}")]
+    Verify(String),
+    #[error("The witness does not carry the claimed public inputs and outputs")]
+    WitnessMismatch,
+}
+
+/// Proof parameters for a Fractal run, independent of the instance being proven.
+#[derive(Debug, Clone)]
+pub struct FractalPipelineOptions {
+    pub lde_blowup: usize,
+    pub num_queries: usize,
+    pub folding_factor: usize,
+    pub max_remainder_size: usize,
+    /// Directory to save prover and verifier keys in and reuse them from, see
+    /// [KeyCache](crate::keys::KeyCache).
+    pub key_dir: Option<String>,
+    /// Directory indexes are cached in across runs, or `None` to always index; see
+    /// [index_cache](crate::index_cache).
+    pub index_cache_dir: Option<String>,
+    /// Size in bytes the index cache is kept under.
+    pub index_cache_limit: u64,
+    /// Base field, hash function and extension degree the callers dispatch on with
+    /// [with_r1cs_field](crate::with_r1cs_field) and
+    /// [with_field_extension](crate::with_field_extension). The pipeline itself takes them from
+    /// its `B`, `H` and `E` type parameters.
+    pub field: String,
+    pub hash_fn: String,
+    pub field_extension: u32,
+    pub verbose: bool,
+}
+
+impl Default for FractalPipelineOptions {
+    /// The options used by the orchestrators and benches.
+    fn default() -> Self {
+        FractalPipelineOptions {
+            lde_blowup: 4,
+            num_queries: 16,
+            folding_factor: 4,
+            max_remainder_size: 32,
+            key_dir: None,
+            index_cache_dir: Some(DEFAULT_INDEX_CACHE_DIR.to_string()),
+            index_cache_limit: DEFAULT_INDEX_CACHE_LIMIT,
+            field: DEFAULT_FIELD.to_string(),
+            hash_fn: DEFAULT_HASH_FN.to_string(),
+            field_extension: 1,
+            verbose: false,
+        }
+    }
+}
+
+impl FractalPipelineOptions {
+    /// The default options with blowup, queries and folding derived from a target security level
+    /// in bits, which the field and hash function of the run must reach up to `ceiling`. Fractal
+    /// has no grinding, so it needs more queries than AIR for the same level.
+    pub fn for_security_level(bits: u32, ceiling: &SecurityCeiling) -> Result<Self, SecurityError> {
+        let params = SecurityParams::for_target(bits, false, ceiling)?;
+        Ok(FractalPipelineOptions {
+            lde_blowup: params.blowup_factor,
+            num_queries: params.num_queries,
+            folding_factor: params.folding_factor,
+            ..FractalPipelineOptions::default()
+        })
+    }
+
+    pub fn security_params(&self) -> SecurityParams {
+        SecurityParams {
+            blowup_factor: self.lde_blowup,
+            num_queries: self.num_queries,
+            grinding_factor: 0,
+            folding_factor: self.folding_factor,
+        }
+    }
+}
+
+// ARTIFACTS
+// ================================================================================================
+
+/// The index of an R1CS instance together with its parameters, the fingerprint of the circuit
+/// and the options derived from its domains.
+pub struct IndexedR1cs<B: StarkField> {
+    pub index: Index<B>,
+    pub params: IndexParams<B>,
+    pub fingerprint: u64,
+    /// Whether the index was read from the index cache instead of being built.
+    pub from_cache: bool,
+    pub options: FractalOptions<B>,
+    pub prover_options: FractalProverOptions<B>,
+}
+
+/// Everything the prover needs besides the witness and the public inputs and outputs. The index
+/// parameters are kept so the options can be rebuilt when the key is read back from disk, and the
+/// fingerprint of the circuit so a stale key can be told apart.
+pub struct FractalProverKey<B: StarkField, E: FieldElement<BaseField = B>, H: ElementHasher> {
+    pub key: Arc<ProverKey<B, E, H>>,
+    pub params: IndexParams<B>,
+    pub fingerprint: u64,
+    pub options: FractalProverOptions<B>,
+}
+
+/// Everything the verifier needs besides the proof and the claimed public inputs and outputs,
+/// kept like [FractalProverKey].
+pub struct FractalVerifierKey<B: StarkField, H: ElementHasher> {
+    pub key: VerifierKey<B, H>,
+    pub params: IndexParams<B>,
+    pub fingerprint: u64,
+    pub options: FractalOptions<B>,
+}
+
+// PIPELINE
+// ================================================================================================
+
+pub struct FractalPipeline<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    options: FractalPipelineOptions,
+    coset_shifts: CosetShifts<B>,
+    _marker: PhantomData<(E, H)>,
+}
+
+impl<B, E, H> FractalPipeline<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    /// A pipeline with coset shifts selected for the field.
+    pub fn new(options: FractalPipelineOptions) -> Result<Self, PipelineError> {
+        Ok(Self::with_coset_shifts(options, CosetShifts::select()?))
+    }
+
+    pub fn with_coset_shifts(
+        options: FractalPipelineOptions,
+        coset_shifts: CosetShifts<B>,
+    ) -> Self {
+        FractalPipeline {
+            options,
+            coset_shifts,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn options(&self) -> &FractalPipelineOptions {
+        &self.options
+    }
+
+    /// Loads the instance at `fixture_path` (without extension); see [load_instance].
+    pub fn parse(
+        &self,
+        fixture_path: &str,
+        inputs_file: Option<&str>,
+    ) -> Result<R1csInstance<B>, PipelineError> {
+        Ok(load_instance::<B>(fixture_path, inputs_file)?)
+    }
+
+    /// Builds the instance of `spec` with the native circuit builder instead of parsing it.
+    pub fn build(
+        &self,
+        spec: &ProgramSpec,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<R1csInstance<B>, PipelineError> {
+        let circuit = build_program_circuit::<B>(spec, size, iterations)?;
+        let public_wires = PublicWires {
+            inputs: circuit.inputs.iter().map(|wire| wire.index()).collect(),
+            outputs: circuit.outputs.iter().map(|wire| wire.index()).collect(),
+        };
+        Ok(R1csInstance {
+            r1cs: circuit.to_sparse(),
+            wires: circuit.wires,
+            public_wires,
+        })
+    }
+
+    /// Indexes the A, B and C matrices, or reads their index from the index cache, and derives
+    /// the verifier and prover options from the index domains.
+    pub fn index(&self, r1cs: &SparseR1CS<B>) -> Result<IndexedR1cs<B>, PipelineError> {
+        let verbose = self.options.verbose;
+        let fingerprint = r1cs.fingerprint();
+        let index_params = self.index_params(r1cs);
+        let degree_fs = r1cs.num_cols();
+        let index_domains = build_index_domains::<B>(index_params.clone());
+        println_if!(verbose, "built index domains");
+
+        let cache = self
+            .options
+            .index_cache_dir
+            .as_ref()
+            .map(|dir| IndexCache::new(dir, self.options.index_cache_limit));
+        let key = index_cache_key(fingerprint, &index_params);
+        let cached = cache.as_ref().and_then(|cache| match cache.load::<B>(key) {
+            Ok(index) => index,
+            Err(err) => {
+                println!("Not using the cached index: {}", err);
+                None
+            }
+        });
+        let from_cache = cached.is_some();
+        let index = match cached {
+            Some(index) => {
+                println_if!(
+                    verbose,
+                    "Read the index from the index cache ({:016x})",
+                    key
+                );
+                index
+            }
+            None => {
+                let index = self.index_matrices(r1cs, &index_params, &index_domains);
+                if let Some(cache) = &cache {
+                    match cache.store(key, &index) {
+                        Ok(summary) if summary.removed > 0 => println_if!(
+                            verbose,
+                            "Evicted {} indexes ({} bytes) from the index cache",
+                            summary.removed,
+                            summary.removed_bytes
+                        ),
+                        Ok(_) => {}
+                        Err(err) => println!("Failed to cache the index: {}", err),
+                    }
+                }
+                index
+            }
+        };
+
+        let (eta, eta_k) = (index_params.eta, index_params.eta_k);
+        let (options, prover_options) = self.derive_options(&index_domains, degree_fs, eta, eta_k);
+        Ok(IndexedR1cs {
+            index,
+            params: index_params,
+            fingerprint,
+            from_cache,
+            options,
+            prover_options,
+        })
+    }
+
+    /// The index parameters of `r1cs`, padded to powers of two.
+    fn index_params(&self, r1cs: &SparseR1CS<B>) -> IndexParams<B> {
+        let num_input_variables = r1cs.num_cols().next_power_of_two();
+        let num_non_zero = r1cs.max_num_nonzero().next_power_of_two();
+        let num_constraints =
+            max(max(r1cs.a.num_rows(), r1cs.b.num_rows()), r1cs.c.num_rows()).next_power_of_two();
+        let max_degree = FractalProver::<B, E, H>::get_max_degree_constraint(
+            num_input_variables,
+            num_non_zero,
+            num_constraints,
+        );
+        let (eta, eta_k) = (self.coset_shifts.eta(), self.coset_shifts.eta_k());
+        IndexParams::<B> {
+            num_input_variables,
+            num_constraints,
+            num_non_zero,
+            max_degree,
+            eta,
+            eta_k,
+        }
+    }
+
+    /// Indexes the A, B and C matrices of `r1cs` over `index_domains`.
+    fn index_matrices(
+        &self,
+        r1cs: &SparseR1CS<B>,
+        index_params: &IndexParams<B>,
+        index_domains: &IndexDomains<B>,
+    ) -> Index<B> {
+        let verbose = self.options.verbose;
+        let mut r1cs = r1cs.to_r1cs();
+
+        let now = Instant::now();
+        let indexed_a = index_matrix::<B>(&mut r1cs.A, index_domains);
+        println_if!(verbose, "Indexed A in {} ms", now.elapsed().as_millis());
+
+        let now = Instant::now();
+        let indexed_b = index_matrix::<B>(&mut r1cs.B, index_domains);
+        println_if!(verbose, "Indexed B in {} ms", now.elapsed().as_millis());
+
+        let now = Instant::now();
+        let indexed_c = index_matrix::<B>(&mut r1cs.C, index_domains);
+        println_if!(verbose, "Indexed C in {} ms", now.elapsed().as_millis());
+
+        // This is the index i.e. the pre-processed data for this r1cs
+        Index::new(index_params.clone(), indexed_a, indexed_b, indexed_c)
+    }
+
+    /// The verifier and prover options for an instance indexed with `params` over `degree_fs`
+    /// variables, without indexing it again. Used for keys read back from disk.
+    pub fn options_from_params(
+        &self,
+        params: &IndexParams<B>,
+        degree_fs: usize,
+    ) -> (FractalOptions<B>, FractalProverOptions<B>) {
+        let index_domains = build_index_domains::<B>(params.clone());
+        self.derive_options(&index_domains, degree_fs, params.eta, params.eta_k)
+    }
+
+    /// Derives the verifier and prover options from the index domains.
+    fn derive_options(
+        &self,
+        index_domains: &IndexDomains<B>,
+        degree_fs: usize,
+        eta: B,
+        eta_k: B,
+    ) -> (FractalOptions<B>, FractalProverOptions<B>) {
+        // TODO: the IndexDomains should already guarantee powers of two, so why add extraneous bit or use next_power_of_two?
+        let size_subgroup_h = index_domains.h_field.len().next_power_of_two();
+        let size_subgroup_k = index_domains.k_field.len().next_power_of_two();
+        let evaluation_domain =
+            get_power_series(index_domains.l_field_base, index_domains.l_field_len);
+        let summing_domain = index_domains.k_field.clone();
+        let h_domain = index_domains.h_field.clone();
+        let fri_options = FriOptions::new(
+            self.options.lde_blowup,
+            self.options.folding_factor,
+            self.options.max_remainder_size,
+        );
+        let num_queries = self.options.num_queries;
+        println_if!(
+            self.options.verbose,
+            "Size of subgroup h = {}, size of subgroup k = {}",
+            size_subgroup_h,
+            size_subgroup_k
+        );
+
+        let options = FractalOptions::<B> {
+            degree_fs,
+            size_subgroup_h,
+            size_subgroup_k,
+            summing_domain: summing_domain.clone(),
+            evaluation_domain: evaluation_domain.clone(),
+            h_domain: h_domain.clone(),
+            eta,
+            eta_k,
+            fri_options: fri_options.clone(),
+            num_queries,
+        };
+        let prover_options = FractalProverOptions::<B> {
+            degree_fs,
+            size_subgroup_h,
+            size_subgroup_k,
+            h_domain_twiddles: fft::get_twiddles(size_subgroup_h),
+            h_domain_inv_twiddles: fft::get_inv_twiddles(size_subgroup_h),
+            k_domain_twiddles: fft::get_twiddles(size_subgroup_k),
+            k_domain_inv_twiddles: fft::get_inv_twiddles(size_subgroup_k),
+            l_domain_twiddles: fft::get_twiddles(evaluation_domain.len()),
+            l_domain_inv_twiddles: fft::get_inv_twiddles(evaluation_domain.len()),
+            summing_domain,
+            evaluation_domain,
+            h_domain,
+            eta,
+            eta_k,
+            fri_options,
+            num_queries,
+        };
+        (options, prover_options)
+    }
+
+    /// Commits to the index, producing the prover and verifier keys. They do not depend on the
+    /// public inputs and outputs, which are only given when proving and verifying.
+    pub fn keygen(
+        &self,
+        indexed: IndexedR1cs<B>,
+    ) -> Result<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>), PipelineError> {
+        let (prover_key, verifier_key) =
+            generate_prover_and_verifier_keys::<B, E, H>(indexed.index, &indexed.options)
+                .map_err(|err| PipelineError::Keygen(format!("{:?}", err)))?;
+        Ok((
+            FractalProverKey {
+                key: prover_key.into(),
+                params: indexed.params.clone(),
+                fingerprint: indexed.fingerprint,
+                options: indexed.prover_options,
+            },
+            FractalVerifierKey {
+                key: verifier_key,
+                params: indexed.params,
+                fingerprint: indexed.fingerprint,
+                options: indexed.options,
+            },
+        ))
+    }
+
+    /// Proves that `wires` satisfies the instance `prover_key` was generated for, with `public`
+    /// as Fractal's public input. The public wires of the instance must have been exposed, see
+    /// [R1csInstance::expose_public_io](crate::binary::R1csInstance::expose_public_io), and must
+    /// carry the values of `public`.
+    pub fn prove(
+        &self,
+        prover_key: &FractalProverKey<B, E, H>,
+        wires: Vec<B>,
+        public: &PublicIo<B>,
+    ) -> Result<LayeredFractalProof<B, E>, PipelineError> {
+        let public_inputs = public.to_elements();
+        if wires.get(..public_inputs.len()) != Some(&public_inputs[..]) {
+            return Err(PipelineError::WitnessMismatch);
+        }
+        let pub_inputs_bytes = public.to_bytes();
+        let mut prover = FractalProver::<B, E, H>::new(
+            prover_key.key.clone(),
+            public_inputs,
+            wires,
+            pub_inputs_bytes.clone(),
+        );
+        prover
+            .generate_proof(&None, pub_inputs_bytes, &prover_key.options)
+            .map_err(|err| PipelineError::Prove(format!("{:?}", err)))
+    }
+
+    /// The security of proofs for the instance `verifier_key` was generated for.
+    pub fn security(&self, verifier_key: &FractalVerifierKey<B, H>) -> SecurityReport {
+        estimate_security(
+            &self.options.security_params(),
+            B::MODULUS_BITS * E::EXTENSION_DEGREE as u32,
+            verifier_key.options.evaluation_domain.len(),
+            H::COLLISION_RESISTANCE,
+        )
+    }
+
+    /// Verifies `proof` against the claimed public inputs and outputs `public`, the public input
+    /// it must have been generated with.
+    pub fn verify(
+        &self,
+        verifier_key: &FractalVerifierKey<B, H>,
+        proof: &LayeredFractalProof<B, E>,
+        public: &PublicIo<B>,
+    ) -> Result<(), PipelineError> {
+        verify_layered_fractal_proof_from_top(
+            &verifier_key.key,
+            proof,
+            &public.to_bytes(),
+            &verifier_key.options,
+        )
+        .map_err(|err| PipelineError::Verify(format!("{:?}", err)))
+    }
+}
+
+// PROOF FILES
+// ================================================================================================
+
+/// Verifies the proof in `file` against the verifier key at `vk_path`, with the options recorded
+/// in the header of both. Loading the key is timed as `parse_ms` and verification as `verify_ms`,
+/// so cold verification can be measured apart from proving.
+pub fn verify_r1cs_proof_file<B, E, H>(
+    file: &ProofFile,
+    vk_path: &str,
+    verbose: bool,
+    report: &mut RunReport,
+) -> Result<(), ProofFileError>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    let pipeline = FractalPipeline::<B, E, H>::new(file.header.r1cs_options(verbose))
+        .map_err(|err| ProofFileError::Verification(err.to_string()))?;
+
+    let now = Instant::now();
+    let (header, verifier_key) = read_verifier_key(vk_path, &pipeline)?;
+    if header != file.header {
+        return Err(ProofFileError::HeaderMismatch {
+            key: header.describe(),
+            proof: file.header.describe(),
+        });
+    }
+    let proof = LayeredFractalProof::<B, E>::read_from_bytes(&file.proof)
+        .map_err(|err| ProofFileError::Deserialization(format!("proof: {}", err)))?;
+    let public = PublicIo::<B>::from_bytes(&file.public_inputs)
+        .map_err(|err| ProofFileError::Deserialization(format!("public inputs: {}", err)))?;
+    report.parse_ms = Some(now.elapsed().as_millis());
+    report.proof_size_bytes = Some(file.proof.len());
+    println_if!(
+        verbose,
+        "Loaded verifier key {} in {} ms",
+        vk_path,
+        now.elapsed().as_millis()
+    );
+
+    let now = Instant::now();
+    pipeline
+        .verify(&verifier_key, &proof, &public)
+        .map_err(|err| ProofFileError::Verification(err.to_string()))?;
+    report.verify_ms = Some(now.elapsed().as_millis());
+    println!("Proof verified in {} ms", now.elapsed().as_millis());
+    Ok(())
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use winter_math::fields::f64::BaseElement;
+
+    use super::*;
+    use crate::hash::hashers::Blake3_256;
+    use crate::registry::{ProgramTag, PROGRAM_REGISTRY};
+
+    type Pipeline = FractalPipeline<BaseElement, BaseElement, Blake3_256<BaseElement>>;
+
+    fn pipeline() -> Pipeline {
+        Pipeline::new(FractalPipelineOptions {
+            index_cache_dir: None,
+            ..FractalPipelineOptions::default()
+        })
+        .unwrap()
+    }
+
+    fn fixture_path(name: &str) -> String {
+        format!("{}/src/jsnark_outputs/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    /// Runs the fixture through parse, index and keygen, returning the keys, the witness with its
+    /// public wires exposed and the claim it carries.
+    #[allow(clippy::type_complexity)]
+    fn prepare(
+        pipeline: &Pipeline,
+        name: &str,
+    ) -> (
+        FractalProverKey<BaseElement, BaseElement, Blake3_256<BaseElement>>,
+        FractalVerifierKey<BaseElement, Blake3_256<BaseElement>>,
+        Vec<BaseElement>,
+        PublicIo<BaseElement>,
+    ) {
+        let mut instance = pipeline.parse(&fixture_path(name), None).unwrap();
+        let public = instance.expose_public_io();
+        let indexed = pipeline.index(&instance.r1cs).unwrap();
+        let (prover_key, verifier_key) = pipeline.keygen(indexed).unwrap();
+        (prover_key, verifier_key, instance.wires, public)
+    }
+
+    #[test]
+    fn fixture_round_trip() {
+        let pipeline = pipeline();
+        for name in ["sample", "fftexample_5", "fibonacciexample_5"] {
+            let (prover_key, verifier_key, wires, public) = prepare(&pipeline, name);
+            let proof = pipeline.prove(&prover_key, wires, &public).unwrap();
+            pipeline
+                .verify(&verifier_key, &proof, &public)
+                .unwrap_or_else(|err| panic!("{}: {}", name, err));
+        }
+    }
+
+    #[test]
+    fn native_round_trip() {
+        let pipeline = pipeline();
+        let spec = PROGRAM_REGISTRY.get(ProgramTag::Fibonacci);
+        let mut instance = pipeline.build(spec, 5, None).unwrap();
+        let public = instance.expose_public_io();
+        let indexed = pipeline.index(&instance.r1cs).unwrap();
+        let (prover_key, verifier_key) = pipeline.keygen(indexed).unwrap();
+        let proof = pipeline
+            .prove(&prover_key, instance.wires, &public)
+            .unwrap();
+        pipeline.verify(&verifier_key, &proof, &public).unwrap();
+    }
+
+    #[test]
+    fn options_rebuilt_from_params_match_the_index() {
+        let pipeline = pipeline();
+        let mut instance = pipeline.parse(&fixture_path("fftexample_5"), None).unwrap();
+        instance.expose_public_io();
+        let indexed = pipeline.index(&instance.r1cs).unwrap();
+        let (options, prover_options) =
+            pipeline.options_from_params(&indexed.params, instance.r1cs.num_cols());
+        assert_eq!(options.degree_fs, indexed.options.degree_fs);
+        assert_eq!(options.size_subgroup_h, indexed.options.size_subgroup_h);
+        assert_eq!(options.size_subgroup_k, indexed.options.size_subgroup_k);
+        assert_eq!(options.evaluation_domain, indexed.options.evaluation_domain);
+        assert_eq!(options.num_queries, indexed.options.num_queries);
+        assert_eq!(prover_options.h_domain, indexed.prover_options.h_domain);
+        assert_eq!(
+            prover_options.summing_domain,
+            indexed.prover_options.summing_domain
+        );
+    }
+
+    #[test]
+    fn proof_does_not_verify_against_another_output() {
+        let pipeline = pipeline();
+        let (prover_key, verifier_key, wires, public) = prepare(&pipeline, "fibonacciexample_5");
+        let proof = pipeline.prove(&prover_key, wires, &public).unwrap();
+        pipeline.verify(&verifier_key, &proof, &public).unwrap();
+
+        let wrong_claim = public.with_wrong_output().unwrap();
+        assert!(pipeline
+            .verify(&verifier_key, &proof, &wrong_claim)
+            .is_err());
+    }
+
+    #[test]
+    fn prove_rejects_a_claim_the_witness_does_not_carry() {
+        let pipeline = pipeline();
+        let (prover_key, _, wires, public) = prepare(&pipeline, "fibonacciexample_5");
+        let wrong_claim = public.with_wrong_output().unwrap();
+        assert!(matches!(
+            pipeline.prove(&prover_key, wires, &wrong_claim),
+            Err(PipelineError::WitnessMismatch)
+        ));
+    }
+}
@@ -0,0 +1,489 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Proofs written to disk, so they can be verified in a separate invocation or on another machine.
+//!
+//! A proof file starts with a [ProofHeader] recording what was proven and with which parameters,
+//! from which the verifier rebuilds its options. All integers are little-endian and strings are a
+//! u8 length followed by UTF-8 bytes. A file is laid out as
+//!
+//! | field              | encoding                                                     |
+//! |--------------------|--------------------------------------------------------------|
+//! | magic              | `b"APRF"`                                                    |
+//! | version            | u16, currently 3                                             |
+//! | header             | system, program: strings; size: u64; iterations: u8 flag and |
+//! |                    | u64; AIR layout: string, empty for R1CS; field, hash         |
+//! |                    | function: strings; extension degree: u32;                    |
+//! |                    | blowup, queries, folding: u64; grinding: u32; FRI max        |
+//! |                    | remainder size: u64                                          |
+//! | public inputs      | u64 length, then the bytes the proof is bound to             |
+//! | proof              | u64 length, then the serialized proof                        |
+//! | checksum           | u64 FNV-1a of every preceding byte                           |
+//!
+//! R1CS proofs carry the values of their public inputs and outputs (see
+//! [PublicIo::to_bytes](crate::public::PublicIo::to_bytes)) and are verified against a verifier
+//! key file (see [keys](crate::keys)). AIR proofs leave the public inputs empty: the verifier
+//! rebuilds the winterfell example from the header, which recomputes them.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::air::{AirError, AirOptions, AIR_FRI_MAX_REMAINDER_SIZE};
+use crate::binary::{read_u64, read_u8, write_u64, ChecksumReader, ChecksumWriter};
+use crate::pipeline::FractalPipelineOptions;
+use crate::registry::{RegistryError, SystemTag};
+
+pub const MAGIC: [u8; 4] = *b"APRF";
+pub const VERSION: u16 = 3;
+
+#[derive(Debug, Error)]
+pub enum ProofFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error(transparent)]
+    Air(#[from] AirError),
+    #[error("Not a {0} file")]
+    BadMagic(&'static str),
+    #[error("Unsupported {kind} version {found}, expected {expected}")]
+    UnsupportedVersion {
+        kind: &'static str,
+        found: u16,
+        expected: u16,
+    },
+    #[error("Checksum mismatch: file is corrupted")]
+    ChecksumMismatch,
+    #[error("Malformed file: {0}")]
+    Malformed(String),
+    #[error("Failed to deserialize {0}")]
+    Deserialization(String),
+    #[error("Verifier key was generated for {key}, but the proof is for {proof}")]
+    HeaderMismatch { key: String, proof: String },
+    #[error("Stale key: generated for a circuit with fingerprint {found:016x}, but this circuit has {expected:016x}")]
+    StaleKey { expected: u64, found: u64 },
+    #[error("Failed to verify proof: {0}")]
+    Verification(String),
+}
+
+// HEADER
+// ================================================================================================
+
+/// What a proof is for and the parameters it was generated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofHeader {
+    pub system: String,
+    pub program: String,
+    pub size: u64,
+    pub iterations: Option<u64>,
+    /// The AIR layout the program was proven under; `None` for R1CS.
+    pub layout: Option<String>,
+    pub field: String,
+    pub hash_fn: String,
+    pub field_extension: u32,
+    pub blowup_factor: usize,
+    pub num_queries: usize,
+    pub folding_factor: usize,
+    pub grinding_factor: u32,
+    /// Largest size of the FRI remainder; fair mode changes it for R1CS to match AIR.
+    pub max_remainder_size: usize,
+}
+
+impl ProofHeader {
+    /// The header of an AIR proof of `program` under `layout`, over the AIR's `field`.
+    pub fn for_air(
+        program: &str,
+        layout: &str,
+        size: u64,
+        iterations: Option<u64>,
+        field: &str,
+        options: &AirOptions,
+    ) -> Self {
+        ProofHeader {
+            system: SystemTag::AIR.name().to_string(),
+            program: program.to_string(),
+            size,
+            iterations,
+            layout: Some(layout.to_string()),
+            field: field.to_string(),
+            hash_fn: options.hash_fn.clone(),
+            field_extension: options.field_extension,
+            blowup_factor: options.blowup_factor,
+            num_queries: options.num_queries,
+            folding_factor: options.folding_factor,
+            grinding_factor: options.grinding_factor,
+            max_remainder_size: AIR_FRI_MAX_REMAINDER_SIZE,
+        }
+    }
+
+    /// The header of a Fractal proof of `program`.
+    pub fn for_r1cs(
+        program: &str,
+        size: u64,
+        iterations: Option<u64>,
+        options: &FractalPipelineOptions,
+    ) -> Self {
+        ProofHeader {
+            system: SystemTag::R1CS.name().to_string(),
+            program: program.to_string(),
+            size,
+            iterations,
+            layout: None,
+            field: options.field.clone(),
+            hash_fn: options.hash_fn.clone(),
+            field_extension: options.field_extension,
+            blowup_factor: options.lde_blowup,
+            num_queries: options.num_queries,
+            folding_factor: options.folding_factor,
+            grinding_factor: 0,
+            max_remainder_size: options.max_remainder_size,
+        }
+    }
+
+    /// The AIR options the proof was generated with.
+    pub fn air_options(&self) -> AirOptions {
+        AirOptions {
+            hash_fn: self.hash_fn.clone(),
+            num_queries: self.num_queries,
+            blowup_factor: self.blowup_factor,
+            grinding_factor: self.grinding_factor,
+            field_extension: self.field_extension,
+            folding_factor: self.folding_factor,
+        }
+    }
+
+    /// The Fractal options the proof was generated with.
+    pub fn r1cs_options(&self, verbose: bool) -> FractalPipelineOptions {
+        FractalPipelineOptions {
+            lde_blowup: self.blowup_factor,
+            num_queries: self.num_queries,
+            folding_factor: self.folding_factor,
+            max_remainder_size: self.max_remainder_size,
+            field: self.field.clone(),
+            hash_fn: self.hash_fn.clone(),
+            field_extension: self.field_extension,
+            verbose,
+            ..FractalPipelineOptions::default()
+        }
+    }
+
+    /// A one-line description, for messages.
+    pub fn describe(&self) -> String {
+        let layout = match &self.layout {
+            Some(layout) => format!(" laid out as {}", layout),
+            None => String::new(),
+        };
+        format!(
+            "{} {}{} at size 2^{} over {} (hash {}, field extension {}, blowup {}, {} queries, folding {}, grinding {} bits)",
+            self.system,
+            self.program,
+            layout,
+            self.size,
+            self.field,
+            self.hash_fn,
+            self.field_extension,
+            self.blowup_factor,
+            self.num_queries,
+            self.folding_factor,
+            self.grinding_factor
+        )
+    }
+
+    pub(crate) fn write_into<W: Write>(&self, writer: &mut W) -> Result<(), ProofFileError> {
+        write_string(writer, &self.system)?;
+        write_string(writer, &self.program)?;
+        write_u64(writer, self.size as usize)?;
+        match self.iterations {
+            Some(iterations) => {
+                writer.write_all(&[1])?;
+                write_u64(writer, iterations as usize)?;
+            }
+            None => {
+                writer.write_all(&[0])?;
+                write_u64(writer, 0)?;
+            }
+        }
+        write_string(writer, self.layout.as_deref().unwrap_or_default())?;
+        write_string(writer, &self.field)?;
+        write_string(writer, &self.hash_fn)?;
+        writer.write_all(&self.field_extension.to_le_bytes())?;
+        write_u64(writer, self.blowup_factor)?;
+        write_u64(writer, self.num_queries)?;
+        write_u64(writer, self.folding_factor)?;
+        writer.write_all(&self.grinding_factor.to_le_bytes())?;
+        write_u64(writer, self.max_remainder_size)?;
+        Ok(())
+    }
+
+    pub(crate) fn read_from<R: Read>(reader: &mut R) -> Result<Self, ProofFileError> {
+        let system = read_string(reader)?;
+        let program = read_string(reader)?;
+        let size = read_u64(reader)? as u64;
+        let has_iterations = read_u8(reader)? == 1;
+        let iterations = read_u64(reader)? as u64;
+        let layout = read_string(reader)?;
+        Ok(ProofHeader {
+            system,
+            program,
+            size,
+            iterations: has_iterations.then_some(iterations),
+            layout: (!layout.is_empty()).then_some(layout),
+            field: read_string(reader)?,
+            hash_fn: read_string(reader)?,
+            field_extension: read_u32(reader)?,
+            blowup_factor: read_u64(reader)?,
+            num_queries: read_u64(reader)?,
+            folding_factor: read_u64(reader)?,
+            grinding_factor: read_u32(reader)?,
+            max_remainder_size: read_u64(reader)?,
+        })
+    }
+}
+
+// PROOF FILE
+// ================================================================================================
+
+/// A serialized proof with its header and the public inputs it is bound to.
+#[derive(Debug, Clone)]
+pub struct ProofFile {
+    pub header: ProofHeader,
+    pub public_inputs: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+impl ProofFile {
+    pub fn write(&self, path: &str) -> Result<(), ProofFileError> {
+        let mut writer = ChecksumWriter::new(BufWriter::new(File::create(path)?));
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        self.header.write_into(&mut writer)?;
+        write_bytes(&mut writer, &self.public_inputs)?;
+        write_bytes(&mut writer, &self.proof)?;
+        finish_checksum(writer)
+    }
+
+    pub fn read(path: &str) -> Result<Self, ProofFileError> {
+        let mut reader = ChecksumReader::new(BufReader::new(File::open(path)?));
+        read_preamble(&mut reader, MAGIC, VERSION, "proof")?;
+        let header = ProofHeader::read_from(&mut reader)?;
+        let public_inputs = read_bytes(&mut reader)?;
+        let proof = read_bytes(&mut reader)?;
+        check_checksum(reader)?;
+        Ok(ProofFile {
+            header,
+            public_inputs,
+            proof,
+        })
+    }
+}
+
+/// The default verifier key path for a proof written to `proof_path`.
+pub fn default_vk_path(proof_path: &str) -> String {
+    Path::new(proof_path)
+        .with_extension("vk")
+        .to_string_lossy()
+        .into_owned()
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+pub(crate) fn read_preamble<R: Read>(
+    reader: &mut R,
+    magic: [u8; 4],
+    version: u16,
+    kind: &'static str,
+) -> Result<(), ProofFileError> {
+    let mut found = [0u8; 4];
+    reader.read_exact(&mut found)?;
+    if found != magic {
+        return Err(ProofFileError::BadMagic(kind));
+    }
+    let mut found = [0u8; 2];
+    reader.read_exact(&mut found)?;
+    let found = u16::from_le_bytes(found);
+    if found != version {
+        return Err(ProofFileError::UnsupportedVersion {
+            kind,
+            found,
+            expected: version,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn finish_checksum<W: Write>(writer: ChecksumWriter<W>) -> Result<(), ProofFileError> {
+    let checksum = writer.checksum();
+    let mut inner = writer.into_inner();
+    inner.write_all(&checksum.to_le_bytes())?;
+    inner.flush()?;
+    Ok(())
+}
+
+pub(crate) fn check_checksum<R: Read>(reader: ChecksumReader<R>) -> Result<(), ProofFileError> {
+    let checksum = reader.checksum();
+    let mut stored = [0u8; 8];
+    reader.into_inner().read_exact(&mut stored)?;
+    if u64::from_le_bytes(stored) != checksum {
+        return Err(ProofFileError::ChecksumMismatch);
+    }
+    Ok(())
+}
+
+pub(crate) fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write_u64(writer, bytes.len())?;
+    writer.write_all(bytes)
+}
+
+pub(crate) fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; read_u64(reader)?];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<(), ProofFileError> {
+    let len = u8::try_from(value.len())
+        .map_err(|_| ProofFileError::Malformed(format!("{} is longer than 255 bytes", value)))?;
+    writer.write_all(&[len])?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, ProofFileError> {
+    let mut bytes = vec![0u8; read_u8(reader)? as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| ProofFileError::Malformed("invalid UTF-8".to_string()))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("proof-file-{}-{}.proof", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn r1cs_proof_file() -> ProofFile {
+        let options = FractalPipelineOptions {
+            max_remainder_size: AIR_FRI_MAX_REMAINDER_SIZE,
+            ..FractalPipelineOptions::default()
+        };
+        ProofFile {
+            header: ProofHeader::for_r1cs("ptrchase", 4, Some(10), &options),
+            public_inputs: vec![1, 2, 3],
+            proof: (0..=255).collect(),
+        }
+    }
+
+    /// Writes `file` to `path`, applies `corrupt` to its bytes and reads it back.
+    fn read_corrupted(
+        name: &str,
+        file: &ProofFile,
+        corrupt: impl FnOnce(&mut Vec<u8>),
+    ) -> Result<ProofFile, ProofFileError> {
+        let path = temp_path(name);
+        file.write(&path).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        corrupt(&mut bytes);
+        fs::write(&path, &bytes).unwrap();
+        let read = ProofFile::read(&path);
+        fs::remove_file(&path).unwrap();
+        read
+    }
+
+    #[test]
+    fn round_trip() {
+        let air_options = AirOptions::default();
+        let air = ProofFile {
+            header: ProofHeader::for_air("fft", "rows", 10, None, "f64", &air_options),
+            public_inputs: vec![],
+            proof: vec![7; 100],
+        };
+        for file in [air, r1cs_proof_file()] {
+            let path = temp_path("round-trip");
+            file.write(&path).unwrap();
+            let read = ProofFile::read(&path);
+            fs::remove_file(&path).unwrap();
+
+            let read = read.unwrap();
+            assert_eq!(read.header, file.header);
+            assert_eq!(read.public_inputs, file.public_inputs);
+            assert_eq!(read.proof, file.proof);
+        }
+    }
+
+    #[test]
+    fn header_restores_the_options() {
+        let air_options = AirOptions {
+            blowup_factor: 16,
+            grinding_factor: 0,
+            ..AirOptions::default()
+        };
+        let header = ProofHeader::for_air("fib", "mulfib8", 6, None, "f128", &air_options);
+        assert_eq!(header.air_options(), air_options);
+        assert_eq!(header.max_remainder_size, AIR_FRI_MAX_REMAINDER_SIZE);
+
+        let file = r1cs_proof_file();
+        let options = file.header.r1cs_options(false);
+        assert_eq!(options.max_remainder_size, AIR_FRI_MAX_REMAINDER_SIZE);
+        assert_eq!(
+            ProofHeader::for_r1cs("ptrchase", 4, Some(10), &options),
+            file.header
+        );
+    }
+
+    #[test]
+    fn rejects_corrupted_proof() {
+        let file = r1cs_proof_file();
+        // a byte of the proof, so that the file still parses and only the checksum catches it
+        let read = read_corrupted("corrupted", &file, |bytes| {
+            let last_proof_byte = bytes.len() - 8 - 1;
+            bytes[last_proof_byte] ^= 1;
+        });
+        assert!(matches!(read, Err(ProofFileError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn rejects_other_files_and_versions() {
+        let file = r1cs_proof_file();
+        let read = read_corrupted("magic", &file, |bytes| bytes[0] = b'X');
+        assert!(matches!(read, Err(ProofFileError::BadMagic("proof"))));
+
+        let read = read_corrupted("version", &file, |bytes| {
+            bytes[4..6].copy_from_slice(&(VERSION - 1).to_le_bytes())
+        });
+        assert!(matches!(
+            read,
+            Err(ProofFileError::UnsupportedVersion {
+                kind: "proof",
+                found,
+                expected: VERSION,
+            }) if found == VERSION - 1
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let file = r1cs_proof_file();
+        let read = read_corrupted("truncated", &file, |bytes| bytes.truncate(bytes.len() / 2));
+        assert!(matches!(read, Err(ProofFileError::Io(_))));
+    }
+}
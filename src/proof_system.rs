@@ -0,0 +1,900 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! A common interface over the proof systems we benchmark, so the binaries and benches can treat
+//! them as interchangeable trait objects.
+
+use std::path::Path;
+use std::time::Instant;
+
+use examples::Example;
+use fractal_examples::r1cs_orchestrator::ProofSystemOrchestrator;
+use fractal_indexer::snark_keys::{ProverKey, VerifierKey};
+use fractal_proofs::{FractalOptions, FractalProverOptions, LayeredFractalProof, Serializable};
+use thiserror::Error;
+use winter_crypto::ElementHasher;
+use winter_math::{FieldElement, StarkField};
+use winterfell::StarkProof;
+
+use crate::air::{build_program_example, record_air_shape, AirError, AirOptions};
+use crate::binary::{binary_path, R1csInstance};
+use crate::extension::ExtensionError;
+use crate::fair::air_field;
+use crate::field::{canonical_ints, FieldError};
+use crate::jsnark::{read_public_wires, JsnarkError};
+use crate::keys::{write_verifier_key, KeyCache};
+use crate::pipeline::{
+    FractalPipeline, FractalPipelineOptions, FractalProverKey, FractalVerifierKey, PipelineError,
+};
+use crate::proof_file::{ProofFile, ProofFileError, ProofHeader};
+use crate::public::PublicIo;
+use crate::registry::{AirLayout, ProgramSpec, RegistryError, SystemTag};
+use crate::report::RunReport;
+use crate::security::SecurityReport;
+
+macro_rules! println_if {
+    ($verbose:expr, $($x:tt)*) => { if $verbose { println!($($x)*) } }
+}
+
+#[derive(Debug, Error)]
+pub enum ProofSystemError {
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error(transparent)]
+    Air(#[from] AirError),
+    #[error(transparent)]
+    Field(#[from] FieldError),
+    #[error(transparent)]
+    Extension(#[from] ExtensionError),
+    #[error(transparent)]
+    Jsnark(#[from] JsnarkError),
+    #[error(transparent)]
+    Pipeline(#[from] PipelineError),
+    #[error(transparent)]
+    ProofFile(#[from] ProofFileError),
+    #[error("{0} only runs on the jsnark fixtures")]
+    FixturesOnly(&'static str),
+    #[error("{0} cannot write proof files")]
+    NoProofFiles(&'static str),
+    #[error("{0} must be preprocessed before proving")]
+    NotPreprocessed(&'static str),
+    #[error("{0} has no proof to verify")]
+    NoProof(&'static str),
+    #[error("Failed to verify proof: {0}")]
+    Verification(String),
+    #[error("{0} proof also verified against wrong public inputs")]
+    WrongPublicInputsAccepted(&'static str),
+}
+
+/// A proof system instantiated for one program at one size.
+///
+/// Proofs are kept by the implementation: [ProofSystem::prove] replaces the current proof, and
+/// [ProofSystem::verify] and [ProofSystem::proof_bytes] act on it.
+pub trait ProofSystem {
+    /// Name of the proof system, as accepted by `--system`.
+    fn name(&self) -> &'static str;
+
+    /// Work done once per program and size, such as indexing and key generation.
+    fn preprocess(&mut self) -> Result<(), ProofSystemError>;
+
+    /// Generates a proof, replacing the previous one.
+    fn prove(&mut self) -> Result<(), ProofSystemError>;
+
+    /// Verifies the current proof against the public inputs and outputs of the program.
+    fn verify(&self) -> Result<(), ProofSystemError>;
+
+    /// Checks that the current proof is rejected against wrong public inputs or outputs. Kept
+    /// apart from [ProofSystem::verify] so that verifier timings cover a single verification;
+    /// systems which cannot check it do nothing.
+    fn check_wrong_claim_rejected(&self) -> Result<(), ProofSystemError> {
+        Ok(())
+    }
+
+    /// The serialized current proof.
+    fn proof_bytes(&self) -> Result<Vec<u8>, ProofSystemError>;
+
+    /// Writes the current proof to `out`, under a header naming the program and the parameters
+    /// it was generated with. Systems which verify proofs against a key also write the key to
+    /// `vk_out`.
+    fn write_proof(&self, _out: &str, _vk_out: &str) -> Result<(), ProofSystemError> {
+        Err(ProofSystemError::NoProofFiles(self.name()))
+    }
+
+    /// Human-readable proof parameters, such as the hash function and number of queries.
+    fn describe_params(&self) -> String;
+
+    /// Records the shape of the proven statement, e.g. the trace width for AIR, in `report`.
+    fn record_shape(&self, _report: &mut RunReport) {}
+
+    /// The security of the current proof, if the proof system can report it.
+    fn security(&self) -> Option<SecurityReport> {
+        None
+    }
+
+    /// The public outputs of the program as canonical integers in the base field, once
+    /// preprocessed, if the proof system exposes them.
+    fn outputs(&self) -> Option<Vec<u128>> {
+        None
+    }
+}
+
+/// Where an R1CS run takes its instance from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum R1csSource {
+    /// The program's jsnark fixture: its binary file if there is one, and its `.arith` and
+    /// `.wires` (or `.in`) files otherwise.
+    #[default]
+    Fixture,
+    /// The `.arith` file of the program's fixture, with the witness computed from this jsnark
+    /// `.in` file.
+    Inputs(String),
+    /// The native circuit builder, which accepts any size.
+    Native,
+}
+
+/// Instantiates `system` for `spec` at the given size and iteration count. `air_layout` and
+/// `air_options` only apply to AIR, which uses the program's default layout if none is named.
+/// R1CS runs on the instance from `r1cs_source` with all of `r1cs_options`; the batched
+/// orchestrator only reads the jsnark fixtures, and only takes the base field, hash function,
+/// extension degree and verbosity from the options, fixing the other parameters itself.
+#[allow(clippy::too_many_arguments)]
+pub fn build_proof_system(
+    spec: &'static ProgramSpec,
+    system: SystemTag,
+    size: u64,
+    iterations: Option<u64>,
+    air_layout: Option<&str>,
+    air_options: &AirOptions,
+    r1cs_source: &R1csSource,
+    r1cs_options: &FractalPipelineOptions,
+) -> Result<Box<dyn ProofSystem>, ProofSystemError> {
+    let native = system == SystemTag::R1CS && *r1cs_source == R1csSource::Native;
+    if !native {
+        spec.check_size(system, size)?;
+    }
+    let proof_system: Box<dyn ProofSystem> = match system {
+        SystemTag::AIR => Box::new(AirProofSystem::new(
+            spec,
+            spec.air_layout(air_layout)?,
+            size,
+            iterations,
+            air_options.clone(),
+        )),
+        SystemTag::R1CS => {
+            let instance = match r1cs_source {
+                R1csSource::Fixture => R1csInstanceSource::Fixture {
+                    path: spec.r1cs_path(size, iterations)?,
+                    inputs_file: None,
+                },
+                R1csSource::Inputs(inputs_file) => R1csInstanceSource::Fixture {
+                    path: spec.r1cs_path(size, iterations)?,
+                    inputs_file: Some(inputs_file.clone()),
+                },
+                R1csSource::Native => R1csInstanceSource::Native(spec),
+            };
+            let mut stages = RunReport::new(spec.name, system.name(), size);
+            stages.iterations = iterations;
+            let (field, hash_fn) = (r1cs_options.field.as_str(), r1cs_options.hash_fn.as_str());
+            flatten_dispatch(crate::with_r1cs_field!(field, hash_fn, B, H => {
+                crate::with_field_extension!(r1cs_options.field_extension, B, E => {
+                    FractalPipeline::<B, E, H>::new(r1cs_options.clone())
+                        .map(|pipeline| {
+                            Box::new(PipelineProofSystem::new(pipeline, instance, stages))
+                                as Box<dyn ProofSystem>
+                        })
+                        .map_err(ProofSystemError::from)
+                })
+            }))?
+        }
+        SystemTag::R1CSPolyBatched => {
+            if *r1cs_source != R1csSource::Fixture {
+                return Err(ProofSystemError::FixturesOnly(system.name()));
+            }
+            let (arith_file, wires_file) = spec.r1cs_files(size, iterations)?;
+            let verbose = r1cs_options.verbose;
+            let (field, hash_fn) = (r1cs_options.field.as_str(), r1cs_options.hash_fn.as_str());
+            flatten_dispatch(crate::with_r1cs_field!(field, hash_fn, B, H => {
+                crate::with_field_extension!(r1cs_options.field_extension, B, E => {
+                    Ok(Box::new(OrchestratorProofSystem::<B, E, H, 1>::new(
+                        arith_file, wires_file, true, verbose,
+                    )) as Box<dyn ProofSystem>)
+                })
+            }))?
+        }
+    };
+    Ok(proof_system)
+}
+
+/// Collapses the results of dispatching on the field and hash function with
+/// [with_r1cs_field](crate::with_r1cs_field) and on the extension degree with
+/// [with_field_extension](crate::with_field_extension) around a body which can fail itself.
+fn flatten_dispatch<T>(
+    dispatched: Result<Result<Result<T, ProofSystemError>, ExtensionError>, FieldError>,
+) -> Result<T, ProofSystemError> {
+    match dispatched {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => Err(err.into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Preprocesses, proves and, if `verify` is set, verifies with `system`, recording timings and
+/// the proof size in `report`.
+pub fn run_proof_system(
+    system: &mut dyn ProofSystem,
+    verify: bool,
+    report: &mut RunReport,
+) -> Result<(), ProofSystemError> {
+    let now = Instant::now();
+    system.preprocess()?;
+    report.prep_ms = Some(now.elapsed().as_millis());
+    report.outputs = system.outputs();
+    println!("Total prep time {} ms", now.elapsed().as_millis());
+
+    let now = Instant::now();
+    system.prove()?;
+    report.prove_ms = Some(now.elapsed().as_millis());
+    println!(
+        "---------------------\nProof generated in {} ms",
+        now.elapsed().as_millis()
+    );
+    report.proof_size_bytes = Some(system.proof_bytes()?.len());
+    system.record_shape(report);
+    println!("Proof size: {}", report.proof_size_bytes.unwrap());
+    if let Some(security) = system.security() {
+        report.conjectured_security_bits = Some(security.conjectured_bits);
+        report.proven_security_bits = Some(security.proven_bits);
+        println!(
+            "Security: {} bits conjectured, {} bits proven",
+            security.conjectured_bits, security.proven_bits
+        );
+    }
+    println!("Proof parameters: {}", system.describe_params());
+
+    if !verify {
+        return Ok(());
+    }
+
+    let now = Instant::now();
+    system.verify()?;
+    report.verify_ms = Some(now.elapsed().as_millis());
+    println!(
+        "---------------------\nProof verified in {} ms",
+        now.elapsed().as_millis()
+    );
+    system.check_wrong_claim_rejected()
+}
+
+// AIR
+// ================================================================================================
+
+/// The winterfell STARK prover, run on the AIR example of a program under one of its layouts.
+pub struct AirProofSystem {
+    spec: &'static ProgramSpec,
+    layout: &'static AirLayout,
+    size: u64,
+    iterations: Option<u64>,
+    options: AirOptions,
+    example: Option<Box<dyn Example>>,
+    proof: Option<StarkProof>,
+}
+
+impl AirProofSystem {
+    pub fn new(
+        spec: &'static ProgramSpec,
+        layout: &'static AirLayout,
+        size: u64,
+        iterations: Option<u64>,
+        options: AirOptions,
+    ) -> Self {
+        AirProofSystem {
+            spec,
+            layout,
+            size,
+            iterations,
+            options,
+            example: None,
+            proof: None,
+        }
+    }
+}
+
+impl ProofSystem for AirProofSystem {
+    fn name(&self) -> &'static str {
+        SystemTag::AIR.name()
+    }
+
+    /// Builds the example, with its blowup raised to cover its constraint degrees; AIR has no
+    /// other preprocessing.
+    fn preprocess(&mut self) -> Result<(), ProofSystemError> {
+        self.example = Some(build_program_example(
+            self.spec,
+            self.layout,
+            self.size,
+            self.iterations,
+            &self.options,
+            true,
+        )?);
+        Ok(())
+    }
+
+    fn prove(&mut self) -> Result<(), ProofSystemError> {
+        let example = self
+            .example
+            .as_ref()
+            .ok_or(ProofSystemError::NotPreprocessed(self.name()))?;
+        self.proof = Some(example.prove());
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<(), ProofSystemError> {
+        let (example, proof) = match (&self.example, &self.proof) {
+            (Some(example), Some(proof)) => (example, proof),
+            _ => return Err(ProofSystemError::NoProof(self.name())),
+        };
+        example
+            .verify(proof.clone())
+            .map_err(|err| ProofSystemError::Verification(err.to_string()))
+    }
+
+    fn check_wrong_claim_rejected(&self) -> Result<(), ProofSystemError> {
+        let (example, proof) = match (&self.example, &self.proof) {
+            (Some(example), Some(proof)) => (example, proof),
+            _ => return Err(ProofSystemError::NoProof(self.name())),
+        };
+        match example.verify_with_wrong_inputs(proof.clone()) {
+            Ok(()) => Err(ProofSystemError::WrongPublicInputsAccepted(self.name())),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn proof_bytes(&self) -> Result<Vec<u8>, ProofSystemError> {
+        self.proof
+            .as_ref()
+            .map(|proof| proof.to_bytes())
+            .ok_or(ProofSystemError::NoProof(self.name()))
+    }
+
+    /// Writes the proof alone: the verifier rebuilds the example from the header.
+    fn write_proof(&self, out: &str, _vk_out: &str) -> Result<(), ProofSystemError> {
+        let header = ProofHeader::for_air(
+            self.spec.name,
+            self.layout.name,
+            self.size,
+            self.iterations,
+            air_field(self.layout),
+            &self.options,
+        );
+        ProofFile {
+            header,
+            public_inputs: vec![],
+            proof: self.proof_bytes()?,
+        }
+        .write(out)?;
+        println!("Proof written to {}", out);
+        Ok(())
+    }
+
+    /// The blowup factor is taken from the proof once there is one, since it is raised to cover
+    /// the constraint degrees of the example.
+    fn describe_params(&self) -> String {
+        let options = &self.options;
+        let blowup_factor = self.proof.as_ref().map_or(options.blowup_factor, |proof| {
+            proof.options().blowup_factor()
+        });
+        format!(
+            "hash {}, {} queries, blowup {}, grinding {} bits, field extension {}, folding {}",
+            options.hash_fn,
+            options.num_queries,
+            blowup_factor,
+            options.grinding_factor,
+            options.field_extension,
+            options.folding_factor
+        )
+    }
+
+    fn record_shape(&self, report: &mut RunReport) {
+        if let Some(proof) = &self.proof {
+            let degrees = self
+                .spec
+                .air_constraint_degrees(self.layout, self.size, self.iterations)
+                .ok()
+                .flatten();
+            record_air_shape(proof, Some(self.layout), degrees.as_deref(), report);
+        }
+    }
+
+    fn security(&self) -> Option<SecurityReport> {
+        self.proof.as_ref().map(|proof| SecurityReport {
+            conjectured_bits: proof.security_level(true),
+            proven_bits: proof.security_level(false),
+        })
+    }
+
+    /// Recomputed from the parameters of the example, since winterfell examples do not expose
+    /// their public inputs.
+    fn outputs(&self) -> Option<Vec<u128>> {
+        self.spec
+            .air_output(self.layout, self.size, self.iterations)
+            .ok()
+            .flatten()
+    }
+}
+
+// FRACTAL PIPELINE
+// ================================================================================================
+
+/// The instance a [PipelineProofSystem] proves.
+pub enum R1csInstanceSource {
+    /// The jsnark fixture at `path` (without extension), see
+    /// [load_instance](crate::binary::load_instance).
+    Fixture {
+        path: String,
+        inputs_file: Option<String>,
+    },
+    /// The circuit of a program built natively, at the size and iteration count of the run.
+    Native(&'static ProgramSpec),
+}
+
+/// The Fractal prover run through the [pipeline](crate::pipeline), as `arith-bench -y r1cs` runs
+/// it: the public wires are exposed as Fractal's public input, keys are reused from the key
+/// directory and indexes from the index cache, and the field, hash function, extension degree and proof parameters all come from
+/// the pipeline options.
+pub struct PipelineProofSystem<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    pipeline: FractalPipeline<B, E, H>,
+    instance: R1csInstanceSource,
+    /// The instance shape and the timings of the preprocessing stages, which
+    /// [ProofSystem::record_shape] copies into the report of a run. Its program, size and
+    /// iteration count name the saved keys and proof files.
+    stages: RunReport,
+    wires: Vec<B>,
+    public: Option<PublicIo<B>>,
+    keys: Option<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>)>,
+    proof: Option<LayeredFractalProof<B, E>>,
+}
+
+impl<B, E, H> PipelineProofSystem<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    pub fn new(
+        pipeline: FractalPipeline<B, E, H>,
+        instance: R1csInstanceSource,
+        stages: RunReport,
+    ) -> Self {
+        PipelineProofSystem {
+            pipeline,
+            instance,
+            stages,
+            wires: vec![],
+            public: None,
+            keys: None,
+            proof: None,
+        }
+    }
+
+    fn claim(
+        &self,
+    ) -> Result<
+        (
+            &FractalVerifierKey<B, H>,
+            &LayeredFractalProof<B, E>,
+            &PublicIo<B>,
+        ),
+        ProofSystemError,
+    > {
+        match (&self.keys, &self.proof, &self.public) {
+            (Some((_, verifier_key)), Some(proof), Some(public)) => {
+                Ok((verifier_key, proof, public))
+            }
+            _ => Err(ProofSystemError::NoProof(self.name())),
+        }
+    }
+
+    /// Parses or builds the instance, timing it as the parse stage.
+    fn load_instance(&mut self) -> Result<R1csInstance<B>, ProofSystemError> {
+        let verbose = self.pipeline.options().verbose;
+        let now = Instant::now();
+        let instance = match &self.instance {
+            R1csInstanceSource::Fixture { path, inputs_file } => {
+                let binary_file = binary_path(path);
+                if let Some(inputs_file) = inputs_file {
+                    println_if!(
+                        verbose,
+                        "Step 1: Parse {}.arith and generate the witness from {}",
+                        path,
+                        inputs_file
+                    );
+                } else if Path::new(&binary_file).exists() {
+                    println_if!(verbose, "Step 1: Load binary instance {}", binary_file);
+                } else {
+                    println_if!(
+                        verbose,
+                        "Step 1: Parse jsnark files {}.{{arith,wires}}",
+                        path
+                    );
+                }
+                self.pipeline.parse(path, inputs_file.as_deref())?
+            }
+            R1csInstanceSource::Native(spec) => {
+                println_if!(
+                    verbose,
+                    "Step 1: Build the {} circuit of size 2^{}",
+                    spec.name,
+                    self.stages.size
+                );
+                self.pipeline
+                    .build(spec, self.stages.size, self.stages.iterations)?
+            }
+        };
+        println_if!(
+            verbose,
+            "Loaded instance with {} constraints and {} wires in {} ms, {:.1} bytes per constraint",
+            instance.r1cs.num_rows(),
+            instance.r1cs.num_cols(),
+            now.elapsed().as_millis(),
+            instance.r1cs.bytes_per_constraint()
+        );
+        self.stages.parse_ms = Some(now.elapsed().as_millis());
+        self.stages.parse_bytes_per_constraint = Some(instance.r1cs.bytes_per_constraint());
+        Ok(instance)
+    }
+
+    /// The keys of `instance`: the ones saved in the key directory if they can be reused, and
+    /// newly generated ones otherwise.
+    fn prepare_keys(
+        &mut self,
+        instance: &R1csInstance<B>,
+    ) -> Result<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>), ProofSystemError> {
+        match self.load_saved_keys(instance) {
+            Some(keys) => Ok(keys),
+            None => self.generate_keys(instance),
+        }
+    }
+
+    /// The keys saved in the key directory for this instance, if there are some which were
+    /// generated for the same circuit and options. Keys which cannot be reused are reported and
+    /// regenerated.
+    fn load_saved_keys(
+        &mut self,
+        instance: &R1csInstance<B>,
+    ) -> Option<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>)> {
+        let cache = KeyCache::new(self.pipeline.options().key_dir.as_ref()?);
+        let header = self.header();
+        let now = Instant::now();
+        match cache.load(&header, instance.r1cs.fingerprint(), &self.pipeline) {
+            Ok(Some(keys)) => {
+                self.stages.key_load_ms = Some(now.elapsed().as_millis());
+                println!(
+                    "Loaded prover and verifier keys from {} in {} ms",
+                    cache.paths(&header).0.display(),
+                    now.elapsed().as_millis()
+                );
+                Some(keys)
+            }
+            Ok(None) => {
+                println_if!(
+                    self.pipeline.options().verbose,
+                    "No saved keys for {}",
+                    header.describe()
+                );
+                None
+            }
+            Err(err) => {
+                println!("Not reusing saved keys: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Indexes the instance and generates its keys, saving them to the key directory if there is
+    /// one.
+    fn generate_keys(
+        &mut self,
+        instance: &R1csInstance<B>,
+    ) -> Result<(FractalProverKey<B, E, H>, FractalVerifierKey<B, H>), ProofSystemError> {
+        let verbose = self.pipeline.options().verbose;
+        println_if!(verbose, "---------------------");
+        println_if!(verbose, "Step 2: Building the index");
+        let now = Instant::now();
+        let indexed = self.pipeline.index(&instance.r1cs)?;
+        self.stages.index_ms = Some(now.elapsed().as_millis());
+        self.stages.index_cache_hit = Some(indexed.from_cache);
+
+        println_if!(verbose, "---------------------");
+        println_if!(verbose, "Step 3: Generating keys");
+        let now = Instant::now();
+        let (prover_key, verifier_key) = self.pipeline.keygen(indexed)?;
+        self.stages.keygen_ms = Some(now.elapsed().as_millis());
+        println!("Prover and verifier keys generated");
+
+        if let Some(key_dir) = &self.pipeline.options().key_dir {
+            KeyCache::new(key_dir).save(&self.header(), &prover_key, &verifier_key)?;
+            println_if!(verbose, "Saved keys to {}", key_dir);
+        }
+        Ok((prover_key, verifier_key))
+    }
+
+    /// The header saved keys and proof files are recorded under: the program being run and the
+    /// pipeline's options.
+    fn header(&self) -> ProofHeader {
+        ProofHeader::for_r1cs(
+            &self.stages.program,
+            self.stages.size,
+            self.stages.iterations,
+            self.pipeline.options(),
+        )
+    }
+}
+
+impl<B, E, H> ProofSystem for PipelineProofSystem<B, E, H>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    fn name(&self) -> &'static str {
+        SystemTag::R1CS.name()
+    }
+
+    /// Loads the instance, exposes its public wires, and loads or generates its keys.
+    fn preprocess(&mut self) -> Result<(), ProofSystemError> {
+        let mut instance = self.load_instance()?;
+        let public = instance.expose_public_io();
+        println_if!(
+            self.pipeline.options().verbose,
+            "Exposed {} public inputs and {} public outputs",
+            public.inputs.len(),
+            public.outputs.len()
+        );
+        self.stages.num_constraints = Some(instance.r1cs.num_rows());
+        self.stages.num_variables = Some(instance.r1cs.num_cols());
+        self.stages.num_non_zero = Some(instance.r1cs.max_num_nonzero());
+
+        let keys = self.prepare_keys(&instance)?;
+        self.wires = instance.wires;
+        self.public = Some(public);
+        self.keys = Some(keys);
+        Ok(())
+    }
+
+    fn prove(&mut self) -> Result<(), ProofSystemError> {
+        let ((prover_key, _), public) = match (&self.keys, &self.public) {
+            (Some(keys), Some(public)) => (keys, public),
+            _ => return Err(ProofSystemError::NotPreprocessed(self.name())),
+        };
+        self.proof = Some(
+            self.pipeline
+                .prove(prover_key, self.wires.clone(), public)?,
+        );
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<(), ProofSystemError> {
+        let (verifier_key, proof, public) = self.claim()?;
+        self.pipeline
+            .verify(verifier_key, proof, public)
+            .map_err(|err| ProofSystemError::Verification(err.to_string()))
+    }
+
+    fn check_wrong_claim_rejected(&self) -> Result<(), ProofSystemError> {
+        let (verifier_key, proof, public) = self.claim()?;
+        match public.with_wrong_output() {
+            Some(wrong_claim)
+                if self
+                    .pipeline
+                    .verify(verifier_key, proof, &wrong_claim)
+                    .is_ok() =>
+            {
+                Err(ProofSystemError::WrongPublicInputsAccepted(self.name()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn proof_bytes(&self) -> Result<Vec<u8>, ProofSystemError> {
+        self.proof
+            .as_ref()
+            .map(|proof| proof.to_bytes())
+            .ok_or(ProofSystemError::NoProof(self.name()))
+    }
+
+    /// Writes the proof with the public inputs and outputs it is bound to, and the verifier key.
+    fn write_proof(&self, out: &str, vk_out: &str) -> Result<(), ProofSystemError> {
+        let (verifier_key, proof, public) = self.claim()?;
+        let header = self.header();
+        ProofFile {
+            header: header.clone(),
+            public_inputs: public.to_bytes(),
+            proof: proof.to_bytes(),
+        }
+        .write(out)?;
+        write_verifier_key(vk_out, &header, verifier_key)?;
+        println!("Proof written to {}, verifier key to {}", out, vk_out);
+        Ok(())
+    }
+
+    fn describe_params(&self) -> String {
+        let options = self.pipeline.options();
+        format!(
+            "field {}, hash {}, field extension {}, lde blowup {}, {} queries, folding {}",
+            options.field,
+            options.hash_fn,
+            options.field_extension,
+            options.lde_blowup,
+            options.num_queries,
+            options.folding_factor
+        )
+    }
+
+    fn record_shape(&self, report: &mut RunReport) {
+        let stages = &self.stages;
+        report.parse_ms = stages.parse_ms;
+        report.parse_bytes_per_constraint = stages.parse_bytes_per_constraint;
+        report.index_ms = stages.index_ms;
+        report.index_cache_hit = stages.index_cache_hit;
+        report.keygen_ms = stages.keygen_ms;
+        report.key_load_ms = stages.key_load_ms;
+        report.num_constraints = stages.num_constraints;
+        report.num_variables = stages.num_variables;
+        report.num_non_zero = stages.num_non_zero;
+    }
+
+    fn security(&self) -> Option<SecurityReport> {
+        self.keys
+            .as_ref()
+            .map(|(_, verifier_key)| self.pipeline.security(verifier_key))
+    }
+
+    fn outputs(&self) -> Option<Vec<u128>> {
+        self.public
+            .as_ref()
+            .map(|public| canonical_ints(&public.outputs))
+    }
+}
+
+// FRACTAL ORCHESTRATOR
+// ================================================================================================
+
+/// What [ProofSystemOrchestrator::prepare] returns: the keys, the verifier options, the witness
+/// and the prover options.
+type Prepared<B, E, H> = (
+    ProverKey<B, E, H>,
+    VerifierKey<B, H>,
+    FractalOptions<B>,
+    Vec<B>,
+    FractalProverOptions<B>,
+);
+
+/// The Fractal prover driven by the `fractal_examples` orchestrator, which reads the jsnark
+/// `.arith` and `.wires` files itself. With `batched` set, it batches the polynomial commitments;
+/// only the batched system still runs this way.
+///
+/// The orchestrator indexes the fixture as is, so proofs are only bound to the public inputs and
+/// outputs declared in the `.arith` file through the Fiat-Shamir transcript; see
+/// [public](crate::public) for how the pipeline exposes them to Fractal. The orchestrator panics instead of
+/// returning verification failures, so unlike the other systems this one cannot check that a
+/// wrong claim is rejected.
+pub struct OrchestratorProofSystem<
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+    const N: usize,
+> {
+    orchestrator: ProofSystemOrchestrator<B, E, H, N>,
+    arith_file: String,
+    batched: bool,
+    prepared: Option<Prepared<B, E, H>>,
+    public: Option<PublicIo<B>>,
+    proof: Option<LayeredFractalProof<B, E>>,
+}
+
+impl<B, E, H, const N: usize> OrchestratorProofSystem<B, E, H, N>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    pub fn new(arith_file: String, wires_file: String, batched: bool, verbose: bool) -> Self {
+        OrchestratorProofSystem {
+            orchestrator: ProofSystemOrchestrator::new(
+                arith_file.clone(),
+                wires_file,
+                batched,
+                verbose,
+            ),
+            arith_file,
+            batched,
+            prepared: None,
+            public: None,
+            proof: None,
+        }
+    }
+}
+
+impl<B, E, H, const N: usize> ProofSystem for OrchestratorProofSystem<B, E, H, N>
+where
+    B: StarkField,
+    E: FieldElement<BaseField = B>,
+    H: ElementHasher<BaseField = B>,
+{
+    fn name(&self) -> &'static str {
+        if self.batched {
+            SystemTag::R1CSPolyBatched.name()
+        } else {
+            SystemTag::R1CS.name()
+        }
+    }
+
+    /// Parses the jsnark files, indexes the R1CS instance and generates the keys. The public
+    /// wires are read from the `.arith` file separately, since the orchestrator does not expose
+    /// them.
+    fn preprocess(&mut self) -> Result<(), ProofSystemError> {
+        let prepared = self.orchestrator.prepare();
+        let (_, _, _, wires, _) = &prepared;
+        self.public = Some(read_public_wires::<B>(&self.arith_file)?.values(wires));
+        self.prepared = Some(prepared);
+        Ok(())
+    }
+
+    fn prove(&mut self) -> Result<(), ProofSystemError> {
+        let ((prover_key, _, _, wires, prover_options), public) =
+            match (&self.prepared, &self.public) {
+                (Some(prepared), Some(public)) => (prepared, public),
+                _ => return Err(ProofSystemError::NotPreprocessed(self.name())),
+            };
+        self.proof = Some(self.orchestrator.prove(
+            &public.to_bytes(),
+            prover_key.clone(),
+            wires,
+            prover_options,
+        ));
+        Ok(())
+    }
+
+    /// The orchestrator checks the proof itself and does not return a result.
+    fn verify(&self) -> Result<(), ProofSystemError> {
+        let ((_, verifier_key, fractal_options, _, _), public, proof) =
+            match (&self.prepared, &self.public, &self.proof) {
+                (Some(prepared), Some(public), Some(proof)) => (prepared, public, proof),
+                _ => return Err(ProofSystemError::NoProof(self.name())),
+            };
+        self.orchestrator
+            .verify(proof, &public.to_bytes(), verifier_key, fractal_options);
+        Ok(())
+    }
+
+    fn proof_bytes(&self) -> Result<Vec<u8>, ProofSystemError> {
+        self.proof
+            .as_ref()
+            .map(|proof| proof.to_bytes())
+            .ok_or(ProofSystemError::NoProof(self.name()))
+    }
+
+    fn describe_params(&self) -> String {
+        match &self.prepared {
+            Some((_, _, options, _, _)) => format!(
+                "batched {}, lde blowup {}, {} queries, folding {}",
+                self.batched,
+                options.fri_options.blowup_factor(),
+                options.num_queries,
+                options.fri_options.folding_factor()
+            ),
+            None => format!("batched {}", self.batched),
+        }
+    }
+
+    fn outputs(&self) -> Option<Vec<u128>> {
+        self.public
+            .as_ref()
+            .map(|public| canonical_ints(&public.outputs))
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Public inputs and outputs of R1CS instances, and how Fractal proofs are bound to them.
+//!
+//! jsnark declares the wires a verifier sees with `input` and `output` lines (`nizkinput` wires
+//! stay private), and the native circuits record theirs in the same way. Fractal takes its public
+//! input as the first variables of the instance, starting with the constant wire 0, so prover and
+//! verifier both get the values `[1, inputs.., outputs..]` of [PublicIo::to_elements]. This is
+//! what rules out the all-zero assignment, which satisfies every R1CS, as well as witnesses
+//! carrying other public values. jsnark scatters its public wires across the assignment, so
+//! [PublicWires::expose] copies each of them into a column at the front, with one constraint
+//! tying copy and wire. The copies only depend on the layout of the circuit, not on the claimed
+//! values, so indexes and keys are shared by every claim on the same circuit. The values,
+//! serialized by [PublicIo::to_bytes], are also absorbed into the Fiat-Shamir transcript.
+
+use std::iter;
+
+use winter_math::StarkField;
+use winter_utils::{ByteReader, Deserializable, DeserializationError, Serializable, SliceReader};
+
+use crate::sparse::SparseR1CS;
+
+/// The wires of an instance which are public, in the order they are declared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicWires {
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+}
+
+impl PublicWires {
+    /// The values of the public wires in the assignment `wires`.
+    pub fn values<B: StarkField>(&self, wires: &[B]) -> PublicIo<B> {
+        let value = |wire: &usize| wires.get(*wire).copied().unwrap_or(B::ZERO);
+        PublicIo {
+            inputs: self.inputs.iter().map(value).collect(),
+            outputs: self.outputs.iter().map(value).collect(),
+        }
+    }
+
+    /// Copies the public wires, inputs then outputs, into new columns `1..=n` of `r1cs` and
+    /// `wires`, right after the constant wire 0, where Fractal reads its public input. The other
+    /// columns move `n` to the right, and one constraint `copy * 1 = wire` is appended per public
+    /// wire. `wires` must hold the constant wire. Returns the public wires of the result.
+    pub fn expose<B: StarkField>(&self, r1cs: &mut SparseR1CS<B>, wires: &mut Vec<B>) -> Self {
+        let public: Vec<usize> = self
+            .inputs
+            .iter()
+            .chain(self.outputs.iter())
+            .copied()
+            .collect();
+        let count = public.len();
+        let values = self.values(wires);
+        wires.splice(1..1, values.inputs.into_iter().chain(values.outputs));
+        r1cs.insert_columns(1, count);
+        for (copy, wire) in (1..=count).zip(public) {
+            let moved = if wire == 0 { 0 } else { wire + count };
+            r1cs.push_constraint(&[(copy, B::ONE)], &[(0, B::ONE)], &[(moved, B::ONE)]);
+        }
+        PublicWires {
+            inputs: (1..=self.inputs.len()).collect(),
+            outputs: (self.inputs.len() + 1..=count).collect(),
+        }
+    }
+}
+
+/// The values of the public inputs and outputs a proof is generated and verified for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicIo<B: StarkField> {
+    pub inputs: Vec<B>,
+    pub outputs: Vec<B>,
+}
+
+impl<B: StarkField> PublicIo<B> {
+    /// Fractal's public input for this claim: the constant wire, then the inputs and outputs, as
+    /// [PublicWires::expose] lays them out.
+    pub fn to_elements(&self) -> Vec<B> {
+        iter::once(B::ONE)
+            .chain(self.inputs.iter().copied())
+            .chain(self.outputs.iter().copied())
+            .collect()
+    }
+
+    /// The public input bytes of a proof: the number of inputs and outputs as little-endian u64,
+    /// followed by the inputs and then the outputs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + (self.inputs.len() + self.outputs.len()) * 8);
+        bytes.extend_from_slice(&(self.inputs.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.outputs.len() as u64).to_le_bytes());
+        for value in self.inputs.iter().chain(self.outputs.iter()) {
+            bytes.extend_from_slice(&Serializable::to_bytes(value));
+        }
+        bytes
+    }
+
+    /// Reads public inputs and outputs serialized by [PublicIo::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializationError> {
+        let mut reader = SliceReader::new(bytes);
+        let num_inputs = reader.read_u64()? as usize;
+        let num_outputs = reader.read_u64()? as usize;
+        let inputs = (0..num_inputs)
+            .map(|_| B::read_from(&mut reader))
+            .collect::<Result<Vec<B>, _>>()?;
+        let outputs = (0..num_outputs)
+            .map(|_| B::read_from(&mut reader))
+            .collect::<Result<Vec<B>, _>>()?;
+        if reader.has_more_bytes() {
+            return Err(DeserializationError::UnconsumedBytes);
+        }
+        Ok(PublicIo { inputs, outputs })
+    }
+
+    /// The same claim with its first output off by one, or `None` if there are no outputs. A
+    /// proof must not verify against it.
+    pub fn with_wrong_output(&self) -> Option<Self> {
+        let mut claim = self.clone();
+        let output = claim.outputs.first_mut()?;
+        *output += B::ONE;
+        Some(claim)
+    }
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use winter_math::fields::f64::BaseElement;
+    use winter_math::FieldElement;
+
+    use super::*;
+    use crate::binary::R1csInstance;
+    use crate::check::check_instance;
+
+    /// `x * x = y` with `x` public input and `y` public output, assigned `x = 3`. Wire 0 is also
+    /// declared as an input, as jsnark does with its one-input wire.
+    fn square_instance() -> R1csInstance<BaseElement> {
+        let one = BaseElement::ONE;
+        let mut r1cs = SparseR1CS::new(4);
+        r1cs.push_constraint(&[(1, one)], &[(1, one)], &[(2, one)]);
+        r1cs.push_constraint(&[(2, one)], &[(0, one)], &[(3, one)]);
+        R1csInstance {
+            r1cs,
+            wires: vec![
+                one,
+                BaseElement::new(3),
+                BaseElement::new(9),
+                BaseElement::new(9),
+            ],
+            public_wires: PublicWires {
+                inputs: vec![0, 1],
+                outputs: vec![3],
+            },
+        }
+    }
+
+    fn is_satisfied(instance: &R1csInstance<BaseElement>, wires: &[BaseElement]) -> bool {
+        check_instance(&instance.r1cs, wires, 1)
+            .unwrap()
+            .is_satisfied()
+    }
+
+    #[test]
+    fn expose_moves_public_wires_to_the_front() {
+        let mut instance = square_instance();
+        let public = instance.expose_public_io();
+        assert_eq!(public.inputs, vec![BaseElement::ONE, BaseElement::new(3)]);
+        assert_eq!(public.outputs, vec![BaseElement::new(9)]);
+
+        let elements = public.to_elements();
+        assert_eq!(instance.wires[..elements.len()], elements[..]);
+        assert_eq!(instance.public_io(), public);
+        assert_eq!(instance.r1cs.num_cols(), instance.wires.len());
+        assert!(is_satisfied(&instance, &instance.wires));
+    }
+
+    #[test]
+    fn exposed_copies_are_tied_to_their_wires() {
+        let mut instance = square_instance();
+        let public = instance.expose_public_io();
+        let wrong_claim = public.with_wrong_output().unwrap();
+        let mut wires = instance.wires.clone();
+        let elements = wrong_claim.to_elements();
+        wires[..elements.len()].copy_from_slice(&elements);
+        assert!(!is_satisfied(&instance, &wires));
+    }
+
+    #[test]
+    fn exposed_circuit_does_not_depend_on_the_claim() {
+        let mut instance = square_instance();
+        let mut other = square_instance();
+        other.wires[1] = BaseElement::new(4);
+        other.wires[2] = BaseElement::new(16);
+        other.wires[3] = BaseElement::new(16);
+        assert_ne!(instance.expose_public_io(), other.expose_public_io());
+        assert_eq!(instance.r1cs.fingerprint(), other.r1cs.fingerprint());
+    }
+
+    #[test]
+    fn public_io_round_trip() {
+        let public = square_instance().public_io();
+        let read = PublicIo::<BaseElement>::from_bytes(&public.to_bytes()).unwrap();
+        assert_eq!(read, public);
+    }
+}
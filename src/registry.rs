@@ -0,0 +1,801 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! The single list of benchmark programs, their R1CS fixtures, AIR examples and sizes.
+//!
+//! Every binary and bench looks programs up here, so adding a program only means adding a
+//! [ProgramSpec] to [ProgramRegistry::new].
+
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+use examples::{Example, ExampleType};
+use lazy_static::lazy_static;
+use thiserror::Error;
+use winter_math::FieldElement;
+use winterfell::{ProofOptions, TransitionConstraintDegree};
+
+use crate::air::{fft_rows, pointer_chasing};
+use crate::binary::binary_path;
+use crate::circuit::CircuitKind;
+use crate::field::{canonical_ints, f128};
+
+/// Directory holding the jsnark `.arith`/`.wires`/`.in` fixtures.
+pub const JSNARK_OUTPUTS_DIR: &str = "src/jsnark_outputs";
+
+lazy_static! {
+    pub static ref PROGRAM_REGISTRY: ProgramRegistry = ProgramRegistry::new();
+}
+
+// Programs to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgramTag {
+    FFT,
+    Fibonacci,
+    PtrChase,
+    Sample,
+}
+
+// Proof systems to choose from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemTag {
+    AIR,
+    R1CS,
+    R1CSPolyBatched,
+}
+
+impl SystemTag {
+    pub const ALL: [SystemTag; 3] = [SystemTag::AIR, SystemTag::R1CS, SystemTag::R1CSPolyBatched];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemTag::AIR => "air",
+            SystemTag::R1CS => "r1cs",
+            SystemTag::R1CSPolyBatched => "r1cs-batched",
+        }
+    }
+
+    pub fn is_r1cs(&self) -> bool {
+        matches!(self, SystemTag::R1CS | SystemTag::R1CSPolyBatched)
+    }
+}
+
+impl fmt::Display for SystemTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SystemTag {
+    type Err = RegistryError;
+
+    fn from_str(provided_name: &str) -> Result<Self, Self::Err> {
+        match provided_name {
+            "air" => Ok(SystemTag::AIR),
+            "r1cs" | "r1" | "r" => Ok(SystemTag::R1CS),
+            "r1cs-batched" => Ok(SystemTag::R1CSPolyBatched),
+            other => Err(RegistryError::UnknownSystem(other.to_string())),
+        }
+    }
+}
+
+/// How a logical program size `s` maps onto the AIR example parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeRule {
+    /// The AIR parameter is `2^s`, e.g. `fftexample_7` is equivalent to an AIR FFT of degree 128.
+    PowerOfTwo,
+    /// The program has a single fixed size.
+    Fixed,
+}
+
+impl SizeRule {
+    pub fn air_parameter(&self, size: u64) -> usize {
+        match self {
+            SizeRule::PowerOfTwo => 1 << size,
+            SizeRule::Fixed => size as usize,
+        }
+    }
+}
+
+/// How the AIR side of a program is instantiated.
+#[derive(Debug, Clone, Copy)]
+pub enum AirConstructor {
+    /// One of the winterfell examples, built from the AIR parameter given by the size rule.
+    Example(fn(usize) -> ExampleType),
+    /// An AIR implemented in this crate, built from the AIR parameter, the log2 iteration count
+    /// and the proof options.
+    Native(fn(usize, u64, ProofOptions) -> Box<dyn Example>),
+}
+
+/// The transition constraints of an AIR layout, which the blowup factor of its proofs has to
+/// cover; see [crate::air::min_blowup_factor].
+#[derive(Debug, Clone, Copy)]
+pub enum AirConstraints {
+    /// Degrees of the transition constraints over the main and auxiliary trace segments, from the
+    /// AIR parameter and the log2 iteration count. The AIRs in this crate are built with the same
+    /// function, so the registry and the AIR cannot disagree.
+    Degrees(fn(usize, u64) -> Vec<TransitionConstraintDegree>),
+    /// The AIR keeps its constraints private, so only the smallest blowup factor winterfell
+    /// accepts for it is recorded, from the AIR parameter.
+    MinBlowup(fn(usize) -> usize),
+}
+
+/// The program output an AIR layout proves, as canonical integers in the field of the layout,
+/// computed from the AIR parameter and the log2 iteration count.
+#[derive(Debug, Clone, Copy)]
+pub enum AirOutput {
+    /// The AIR proves the computation of the program's R1CS circuit.
+    Computed(fn(usize, u64) -> Vec<u128>),
+    /// The AIR proves a different computation than the R1CS circuit, described by `note`, so
+    /// its output is not expected to match the circuit's.
+    Differs {
+        output: fn(usize, u64) -> Vec<u128>,
+        note: &'static str,
+    },
+    /// The AIR keeps its output private, for the given reason, so it cannot be cross-checked.
+    Private(&'static str),
+}
+
+impl AirOutput {
+    /// The function computing the output, unless the AIR keeps it private.
+    pub fn function(&self) -> Option<fn(usize, u64) -> Vec<u128>> {
+        match self {
+            AirOutput::Computed(output) | AirOutput::Differs { output, .. } => Some(*output),
+            AirOutput::Private(_) => None,
+        }
+    }
+
+    /// Why the output cannot match that of the R1CS circuit, if there is a reason.
+    pub fn note(&self) -> Option<&'static str> {
+        match self {
+            AirOutput::Computed(_) => None,
+            AirOutput::Differs { note, .. } | AirOutput::Private(note) => Some(note),
+        }
+    }
+}
+
+/// One way of laying out a program's computation as an AIR trace, e.g. few wide rows or many
+/// narrow ones.
+#[derive(Debug, Clone)]
+pub struct AirLayout {
+    /// Name used with `--layout` and in reports.
+    pub name: &'static str,
+    pub air: AirConstructor,
+    /// Sizes the layout supports.
+    pub sizes: Range<u64>,
+    /// The layout's transition constraints. Those of the winterfell examples are restated here,
+    /// and `air::tests` checks them against what winterfell accepts.
+    pub constraints: AirConstraints,
+    /// The program output the AIR proves, which `compare` checks against the other runs.
+    pub output: AirOutput,
+}
+
+impl AirLayout {
+    pub fn check_size(&self, program: &'static str, size: u64) -> Result<(), RegistryError> {
+        if self.sizes.contains(&size) {
+            Ok(())
+        } else {
+            Err(RegistryError::UnsupportedLayoutSize {
+                program,
+                layout: self.name,
+                size,
+            })
+        }
+    }
+}
+
+/// A program at one logical size, as every proof system instantiates it. All sizes go through
+/// [ProgramSpec::instance], so runs of the same instance prove the same computation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramInstance {
+    /// Log2 of the problem size.
+    pub size: u64,
+    pub iterations: Option<u64>,
+    /// Path of the jsnark fixtures without extension, if there are fixtures at this size.
+    pub r1cs_path: Option<String>,
+    /// Parameter of the AIR: the FFT degree, the Fibonacci sequence length or the memory size.
+    pub air_parameter: usize,
+}
+
+impl fmt::Display for ProgramInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "size 2^{}", self.size)?;
+        if let Some(iterations) = self.iterations {
+            write!(f, ", 2^{} iterations", iterations)?;
+        }
+        if let Some(r1cs_path) = &self.r1cs_path {
+            write!(f, ", R1CS fixture {}", r1cs_path)?;
+        }
+        write!(f, ", AIR parameter {}", self.air_parameter)
+    }
+}
+
+/// Everything the benchmarks need to know about one program.
+#[derive(Debug, Clone)]
+pub struct ProgramSpec {
+    pub tag: ProgramTag,
+    /// Canonical name used on the command line and in reports.
+    pub name: &'static str,
+    /// Other accepted spellings of the name.
+    pub aliases: &'static [&'static str],
+    /// Stem of the jsnark fixtures, i.e. `{stem}_{size}.arith`.
+    pub r1cs_stem: Option<&'static str>,
+    /// Sizes R1CS fixtures are shipped for. A range may have gaps; [ProgramSpec::r1cs_path]
+    /// checks that the fixture is on disk.
+    pub r1cs_sizes: Range<u64>,
+    /// Log2 iteration counts, for programs which take a second size parameter. Fixtures for
+    /// these live in `{iterations_dir}_{iterations}_iters/{stem}_{size}`.
+    pub iterations: Option<Range<u64>>,
+    pub iterations_dir: Option<&'static str>,
+    /// Circuit which builds the R1CS instance in Rust instead of reading the fixtures.
+    pub r1cs_circuit: Option<CircuitKind>,
+    /// AIR layouts of the program; the first is the default.
+    pub air_layouts: Vec<AirLayout>,
+    pub size_rule: SizeRule,
+}
+
+impl ProgramSpec {
+    /// Sizes this program supports under `system`; empty if it has no implementation there.
+    /// Under AIR, these are the sizes supported by any of its layouts.
+    pub fn supported_sizes(&self, system: SystemTag) -> Range<u64> {
+        match system {
+            SystemTag::AIR if !self.air_layouts.is_empty() => {
+                let start = self.air_layouts.iter().map(|layout| layout.sizes.start);
+                let end = self.air_layouts.iter().map(|layout| layout.sizes.end);
+                start.min().unwrap()..end.max().unwrap()
+            }
+            SystemTag::R1CS | SystemTag::R1CSPolyBatched if self.r1cs_stem.is_some() => {
+                self.r1cs_sizes.clone()
+            }
+            _ => 0..0,
+        }
+    }
+
+    pub fn check_size(&self, system: SystemTag, size: u64) -> Result<(), RegistryError> {
+        if self.supported_sizes(system).contains(&size) {
+            Ok(())
+        } else {
+            Err(RegistryError::UnsupportedSize {
+                program: self.name,
+                system,
+                size,
+            })
+        }
+    }
+
+    /// Checks the iteration count against the program; programs with a second size parameter
+    /// default to their smallest iteration count.
+    pub fn resolve_iterations(
+        &self,
+        iterations: Option<u64>,
+    ) -> Result<Option<u64>, RegistryError> {
+        match (&self.iterations, iterations) {
+            (Some(range), Some(iterations)) if range.contains(&iterations) => Ok(Some(iterations)),
+            (Some(_), None) => Ok(self.default_iterations()),
+            (None, None) => Ok(None),
+            (_, Some(iterations)) => Err(RegistryError::UnsupportedIterations {
+                program: self.name,
+                iterations,
+            }),
+        }
+    }
+
+    /// Iteration count used when none is given.
+    pub fn default_iterations(&self) -> Option<u64> {
+        self.iterations.as_ref().map(|range| range.start)
+    }
+
+    /// The program at `size`: the R1CS fixture and the AIR parameter which compute the same
+    /// output. The `n`-th term of the Fibonacci sequence, for instance, is fixture
+    /// `fibonacciexample_s` and AIR sequence length `n` for `n = 2^s`.
+    pub fn instance(
+        &self,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<ProgramInstance, RegistryError> {
+        let iterations = self.resolve_iterations(iterations)?;
+        let r1cs_path = if self.has_r1cs_fixture(size, iterations) {
+            Some(self.r1cs_path(size, iterations)?)
+        } else {
+            None
+        };
+        Ok(ProgramInstance {
+            size,
+            iterations,
+            r1cs_path,
+            air_parameter: self.size_rule.air_parameter(size),
+        })
+    }
+
+    /// The program output `layout` proves at `size`, unless the layout keeps it private; see
+    /// [AirOutput].
+    pub fn air_output(
+        &self,
+        layout: &AirLayout,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<Option<Vec<u128>>, RegistryError> {
+        layout.check_size(self.name, size)?;
+        let instance = self.instance(size, iterations)?;
+        Ok(layout
+            .output
+            .function()
+            .map(|output| output(instance.air_parameter, instance.iterations.unwrap_or(0))))
+    }
+
+    /// The degrees of the transition constraints of `layout` at `size`, if the layout exposes
+    /// them; see [AirConstraints].
+    pub fn air_constraint_degrees(
+        &self,
+        layout: &AirLayout,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<Option<Vec<TransitionConstraintDegree>>, RegistryError> {
+        layout.check_size(self.name, size)?;
+        let instance = self.instance(size, iterations)?;
+        Ok(match layout.constraints {
+            AirConstraints::Degrees(degrees) => Some(degrees(
+                instance.air_parameter,
+                instance.iterations.unwrap_or(0),
+            )),
+            AirConstraints::MinBlowup(_) => None,
+        })
+    }
+
+    /// Path of the jsnark fixtures without extension, e.g. `src/jsnark_outputs/fftexample_7`.
+    /// Fails if there is no `.arith` or converted binary file at that path.
+    pub fn r1cs_path(&self, size: u64, iterations: Option<u64>) -> Result<String, RegistryError> {
+        let path = self.expected_r1cs_path(size, iterations)?;
+        if fixture_exists(&path) {
+            Ok(path)
+        } else {
+            Err(RegistryError::MissingFixture {
+                program: self.name,
+                size,
+                path,
+            })
+        }
+    }
+
+    /// True if the R1CS fixture of this program at `size` is on disk.
+    pub fn has_r1cs_fixture(&self, size: u64, iterations: Option<u64>) -> bool {
+        self.expected_r1cs_path(size, iterations)
+            .map_or(false, |path| fixture_exists(&path))
+    }
+
+    /// Sizes among [supported_sizes](Self::supported_sizes) whose R1CS fixtures are on disk,
+    /// with the default iteration count.
+    pub fn r1cs_fixture_sizes(&self) -> Vec<u64> {
+        let iterations = self.default_iterations();
+        self.supported_sizes(SystemTag::R1CS)
+            .filter(|size| self.has_r1cs_fixture(*size, iterations))
+            .collect()
+    }
+
+    /// Path the jsnark fixtures of this program at `size` are expected at, without extension,
+    /// whether or not they are on disk.
+    pub fn expected_r1cs_path(
+        &self,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<String, RegistryError> {
+        self.check_size(SystemTag::R1CS, size)?;
+        let stem = self.r1cs_stem.unwrap();
+        match self.resolve_iterations(iterations)? {
+            Some(iterations) => {
+                let dir = self.iterations_dir.unwrap_or(stem);
+                Ok(format!(
+                    "{JSNARK_OUTPUTS_DIR}/{dir}_{iterations}_iters/{stem}_{size}"
+                ))
+            }
+            None => Ok(format!("{JSNARK_OUTPUTS_DIR}/{stem}_{size}")),
+        }
+    }
+
+    /// The `.arith` and `.wires` files for this program at `size`.
+    pub fn r1cs_files(
+        &self,
+        size: u64,
+        iterations: Option<u64>,
+    ) -> Result<(String, String), RegistryError> {
+        let program_path = self.r1cs_path(size, iterations)?;
+        Ok((
+            format!("{program_path}.arith"),
+            format!("{program_path}.wires"),
+        ))
+    }
+
+    /// The AIR layout called `name`, or the default one if no name is given.
+    pub fn air_layout(&self, name: Option<&str>) -> Result<&AirLayout, RegistryError> {
+        match name {
+            None => self
+                .air_layouts
+                .first()
+                .ok_or(RegistryError::NoAirLayout(self.name)),
+            Some(name) => self
+                .air_layouts
+                .iter()
+                .find(|layout| layout.name == name)
+                .ok_or_else(|| RegistryError::UnknownLayout {
+                    program: self.name,
+                    layout: name.to_string(),
+                    available: self.layout_names().join(", "),
+                }),
+        }
+    }
+
+    /// The AIR layouts in the comma-separated list `names`, every layout for `all`, or the
+    /// default one if no list is given.
+    pub fn air_layouts_named(&self, names: Option<&str>) -> Result<Vec<&AirLayout>, RegistryError> {
+        match names {
+            None => Ok(vec![self.air_layout(None)?]),
+            Some("all") if !self.air_layouts.is_empty() => Ok(self.air_layouts.iter().collect()),
+            Some(names) => names
+                .split(',')
+                .map(|name| self.air_layout(Some(name)))
+                .collect(),
+        }
+    }
+
+    pub fn layout_names(&self) -> Vec<&'static str> {
+        self.air_layouts.iter().map(|layout| layout.name).collect()
+    }
+
+    /// The winterfell example equivalent to the R1CS fixture of the same `size`, under `layout`.
+    /// Fails for layouts implemented in this crate rather than in winterfell.
+    pub fn air_example_type(
+        &self,
+        layout: &AirLayout,
+        size: u64,
+    ) -> Result<ExampleType, RegistryError> {
+        layout.check_size(self.name, size)?;
+        match layout.air {
+            AirConstructor::Example(build) => Ok(build(self.instance(size, None)?.air_parameter)),
+            AirConstructor::Native(_) => Err(RegistryError::NotAWinterfellExample(self.name)),
+        }
+    }
+}
+
+fn fft_example(degree: usize) -> ExampleType {
+    ExampleType::FFT { degree }
+}
+
+/// The winterfell FFT example proves an FFT of size `degree` with a blowup factor of `degree`,
+/// the one the original benchmarks used for it from 64 on; its constraint degrees grow with the
+/// size.
+fn fft_columns_min_blowup(degree: usize) -> usize {
+    degree
+}
+
+fn fft_rows_example(size: usize, _iterations: u64, options: ProofOptions) -> Box<dyn Example> {
+    Box::new(fft_rows::FftRowsExample::new(size, options))
+}
+
+fn fft_rows_output(size: usize, _iterations: u64) -> Vec<u128> {
+    canonical_ints(&fft_rows::fft_outputs(size))
+}
+
+fn fft_rows_degrees(size: usize, _iterations: u64) -> Vec<TransitionConstraintDegree> {
+    fft_rows::transition_constraint_degrees(size)
+}
+
+fn fib_example(sequence_length: usize) -> ExampleType {
+    ExampleType::Fib { sequence_length }
+}
+
+fn mulfib8_example(sequence_length: usize) -> ExampleType {
+    ExampleType::Mulfib8 { sequence_length }
+}
+
+/// How the winterfell mulfib examples differ from the jsnark Fibonacci circuit.
+const MULFIB_NOTE: &str = "the winterfell mulfib examples prove x_i = x_(i-1) * x_(i-2) from 1, 2 \
+    over f128, the jsnark circuit x_i = 20 * x_(i-1) * x_(i-2) from its inputs (1, 1 in the .in \
+    files)";
+
+/// The last of the `sequence_length` terms of the multiplicative Fibonacci sequence of the
+/// winterfell mulfib examples, which starts with 1, 2 over the 128-bit field. Both layouts hold
+/// the whole sequence in their trace, so this is the last cell of the trace, as `v_(n-1)` is the
+/// output wire of the jsnark circuit.
+fn mulfib_output(sequence_length: usize, _iterations: u64) -> Vec<u128> {
+    let (mut t0, mut t1) = (f128::BaseElement::ONE, f128::BaseElement::new(2));
+    for _ in 2..sequence_length {
+        (t0, t1) = (t1, t0 * t1);
+    }
+    canonical_ints(&[t1])
+}
+
+/// Both terms of a row of winterfell's mulfib2 AIR are the product of two cells of the previous
+/// row.
+fn mulfib2_degrees(_sequence_length: usize, _iterations: u64) -> Vec<TransitionConstraintDegree> {
+    vec![TransitionConstraintDegree::new(2); 2]
+}
+
+/// As [mulfib2_degrees], for the eight terms of a row of winterfell's mulfib8 AIR.
+fn mulfib8_degrees(_sequence_length: usize, _iterations: u64) -> Vec<TransitionConstraintDegree> {
+    vec![TransitionConstraintDegree::new(2); 8]
+}
+
+fn ptrchase_example(
+    memory_size: usize,
+    iterations: u64,
+    options: ProofOptions,
+) -> Box<dyn Example> {
+    Box::new(pointer_chasing::PointerChasingExample::new(
+        memory_size,
+        1 << iterations,
+        options,
+    ))
+}
+
+fn ptrchase_output(memory_size: usize, iterations: u64) -> Vec<u128> {
+    canonical_ints(&[pointer_chasing::chase_result(memory_size, 1 << iterations)])
+}
+
+fn ptrchase_degrees(memory_size: usize, iterations: u64) -> Vec<TransitionConstraintDegree> {
+    pointer_chasing::transition_constraint_degrees(memory_size, 1 << iterations)
+}
+
+/// True if the fixture at `path` (without extension) can be loaded: its `.arith` file, or the
+/// binary file converted from it, exists.
+fn fixture_exists(path: &str) -> bool {
+    Path::new(&format!("{path}.arith")).exists() || Path::new(&binary_path(path)).exists()
+}
+
+/// Lookup table over all supported programs.
+#[derive(Debug, Clone)]
+pub struct ProgramRegistry {
+    programs: Vec<ProgramSpec>,
+}
+
+impl ProgramRegistry {
+    pub fn new() -> Self {
+        let programs = vec![
+            ProgramSpec {
+                tag: ProgramTag::FFT,
+                name: "fft",
+                aliases: &["fftexample"],
+                r1cs_stem: Some("fftexample"),
+                // there is no fixture for size 6
+                r1cs_sizes: 5..13,
+                iterations: None,
+                iterations_dir: None,
+                r1cs_circuit: Some(CircuitKind::Fft),
+                air_layouts: vec![
+                    // Every value of the FFT in its own column. Wider FFTs exceed the 511 column
+                    // limit of winterfell.
+                    AirLayout {
+                        name: "columns",
+                        air: AirConstructor::Example(fft_example),
+                        sizes: 2..8,
+                        constraints: AirConstraints::MinBlowup(fft_columns_min_blowup),
+                        output: AirOutput::Private(
+                            "the winterfell FFT example keeps its inputs and outputs private",
+                        ),
+                    },
+                    // One butterfly per row of a four column trace; see air::fft_rows.
+                    AirLayout {
+                        name: "rows",
+                        air: AirConstructor::Native(fft_rows_example),
+                        sizes: 1..21,
+                        constraints: AirConstraints::Degrees(fft_rows_degrees),
+                        output: AirOutput::Computed(fft_rows_output),
+                    },
+                ],
+                size_rule: SizeRule::PowerOfTwo,
+            },
+            ProgramSpec {
+                tag: ProgramTag::Fibonacci,
+                name: "fib",
+                aliases: &["fibonacciexample"],
+                r1cs_stem: Some("fibonacciexample"),
+                // sizes from 16 on ship without their .arith files; `arith-bench generate`
+                // writes them
+                r1cs_sizes: 5..21,
+                iterations: None,
+                iterations_dir: None,
+                r1cs_circuit: Some(CircuitKind::MulFib),
+                air_layouts: vec![
+                    // Two terms per row.
+                    AirLayout {
+                        name: "mulfib2",
+                        air: AirConstructor::Example(fib_example),
+                        sizes: 3..26,
+                        constraints: AirConstraints::Degrees(mulfib2_degrees),
+                        output: AirOutput::Differs {
+                            output: mulfib_output,
+                            note: MULFIB_NOTE,
+                        },
+                    },
+                    // Eight terms per row, for a trace a quarter as long; the trace needs at
+                    // least 8 rows.
+                    AirLayout {
+                        name: "mulfib8",
+                        air: AirConstructor::Example(mulfib8_example),
+                        sizes: 6..26,
+                        constraints: AirConstraints::Degrees(mulfib8_degrees),
+                        output: AirOutput::Differs {
+                            output: mulfib_output,
+                            note: MULFIB_NOTE,
+                        },
+                    },
+                ],
+                size_rule: SizeRule::PowerOfTwo,
+            },
+            // The size is log2 of the memory size, the iteration count is log2 of the number of
+            // pointer dereferences.
+            ProgramSpec {
+                tag: ProgramTag::PtrChase,
+                name: "ptrchase",
+                aliases: &["ptrchasingexample", "ptrchaseexample"],
+                r1cs_stem: Some("ptrchasingexample"),
+                r1cs_sizes: 3..9,
+                iterations: Some(10..13),
+                iterations_dir: Some("pointer_chasing"),
+                r1cs_circuit: Some(CircuitKind::PointerChasing),
+                air_layouts: vec![
+                    // Memory accesses next to a copy sorted by address and timestamp; see
+                    // air::pointer_chasing.
+                    AirLayout {
+                        name: "sorted",
+                        air: AirConstructor::Native(ptrchase_example),
+                        sizes: 3..9,
+                        constraints: AirConstraints::Degrees(ptrchase_degrees),
+                        output: AirOutput::Computed(ptrchase_output),
+                    },
+                ],
+                size_rule: SizeRule::PowerOfTwo,
+            },
+            ProgramSpec {
+                tag: ProgramTag::Sample,
+                name: "sample",
+                aliases: &["", "default"],
+                r1cs_stem: Some("sample"),
+                r1cs_sizes: 1..2,
+                iterations: None,
+                iterations_dir: None,
+                r1cs_circuit: None,
+                air_layouts: vec![],
+                size_rule: SizeRule::Fixed,
+            },
+        ];
+        ProgramRegistry { programs }
+    }
+
+    pub fn programs(&self) -> &[ProgramSpec] {
+        &self.programs
+    }
+
+    /// Finds a program by its name or one of its aliases.
+    pub fn lookup(&self, provided_name: &str) -> Result<&ProgramSpec, RegistryError> {
+        self.programs
+            .iter()
+            .find(|spec| spec.name == provided_name || spec.aliases.contains(&provided_name))
+            .ok_or_else(|| RegistryError::UnknownProgram(provided_name.to_string()))
+    }
+
+    pub fn get(&self, tag: ProgramTag) -> &ProgramSpec {
+        self.programs
+            .iter()
+            .find(|spec| spec.tag == tag)
+            .expect("every program tag is registered")
+    }
+}
+
+impl Default for ProgramRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for ProgramTag {
+    type Err = RegistryError;
+
+    fn from_str(provided_name: &str) -> Result<Self, Self::Err> {
+        PROGRAM_REGISTRY.lookup(provided_name).map(|spec| spec.tag)
+    }
+}
+
+impl fmt::Display for ProgramTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", PROGRAM_REGISTRY.get(*self).name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RegistryError {
+    #[error("Unsupported program: {0}")]
+    UnknownProgram(String),
+    #[error("Unsupported proof system: {0}")]
+    UnknownSystem(String),
+    #[error("Unsupported program size for {program}/{system}: {size}")]
+    UnsupportedSize {
+        program: &'static str,
+        system: SystemTag,
+        size: u64,
+    },
+    #[error("Unsupported iteration count for {program}: {iterations}")]
+    UnsupportedIterations {
+        program: &'static str,
+        iterations: u64,
+    },
+    #[error("Program {0} has no winterfell example")]
+    NotAWinterfellExample(&'static str),
+    #[error("Program {0} has no AIR layout")]
+    NoAirLayout(&'static str),
+    #[error("Unknown AIR layout for {program}: {layout} (available: {available})")]
+    UnknownLayout {
+        program: &'static str,
+        layout: String,
+        available: String,
+    },
+    #[error("Unsupported program size for {program} under AIR layout {layout}: {size}")]
+    UnsupportedLayoutSize {
+        program: &'static str,
+        layout: &'static str,
+        size: u64,
+    },
+    #[error("Program {0} has no native R1CS circuit")]
+    NoNativeCircuit(&'static str),
+    #[error("No R1CS fixture for {program} at size {size}: {path}.arith does not exist")]
+    MissingFixture {
+        program: &'static str,
+        size: u64,
+        path: String,
+    },
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::load_instance;
+    use crate::field::f64;
+
+    #[test]
+    fn power_of_two_sizes_map_to_their_air_parameter() {
+        // the README runs `-s=20` as the 2^20th Fibonacci number
+        assert_eq!(SizeRule::PowerOfTwo.air_parameter(20), 1_048_576);
+        let fib = PROGRAM_REGISTRY.get(ProgramTag::Fibonacci);
+        assert_eq!(fib.instance(20, None).unwrap().air_parameter, 1 << 20);
+        let fixture = fib.instance(5, None).unwrap();
+        assert_eq!(fixture.air_parameter, 32);
+        assert_eq!(
+            fixture.r1cs_path.as_deref(),
+            Some("src/jsnark_outputs/fibonacciexample_5")
+        );
+        assert_eq!(SizeRule::Fixed.air_parameter(1), 1);
+    }
+
+    #[test]
+    fn mulfib_layouts_prove_a_different_sequence_than_the_fixtures() {
+        let fib = PROGRAM_REGISTRY.get(ProgramTag::Fibonacci);
+        let path = fib.r1cs_path(5, None).unwrap();
+
+        // the fixture's output wire, as shipped over f64
+        let shipped = load_instance::<f64::BaseElement>(&path, None).unwrap();
+        let shipped = canonical_ints(&shipped.public_io().outputs);
+        assert_eq!(shipped, vec![16402542771308629211]);
+
+        // the same circuit over f128 computes 20 * x_(i-1) * x_(i-2) from 1, 1
+        let inputs = format!("{path}.in");
+        let circuit = load_instance::<f128::BaseElement>(&path, Some(&inputs)).unwrap();
+        let (mut t0, mut t1) = (f128::BaseElement::ONE, f128::BaseElement::ONE);
+        for _ in 2..32 {
+            (t0, t1) = (t1, f128::BaseElement::new(20) * t0 * t1);
+        }
+        let circuit = canonical_ints(&circuit.public_io().outputs);
+        assert_eq!(circuit, canonical_ints(&[t1]));
+
+        for layout in fib.air_layouts.iter() {
+            assert!(layout.output.note().is_some());
+            let output = fib.air_output(layout, 5, None).unwrap().unwrap();
+            assert_eq!(output, mulfib_output(32, 0));
+            assert_ne!(output, circuit);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Machine-readable summaries of a single prove/verify run.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// Timings and sizes collected while running one program under one proof system.
+///
+/// Fields which do not apply to a proof system (e.g. trace width for R1CS) are left as `None`
+/// and serialized as `null` in JSON and as an empty cell in CSV.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunReport {
+    pub program: String,
+    pub system: String,
+    pub size: u64,
+    pub iterations: Option<u64>,
+    pub field: Option<String>,
+    pub hash_fn: Option<String>,
+    pub field_extension: Option<u32>,
+
+    /// Time to load the instance from its binary or jsnark files, or to build the circuit with
+    /// `--native`.
+    pub parse_ms: Option<u128>,
+    /// Heap memory held by the sparse matrices per constraint, as parsed or built.
+    pub parse_bytes_per_constraint: Option<f64>,
+    pub index_ms: Option<u128>,
+    /// Whether the index was read from the index cache, in which case `index_ms` is the time to
+    /// read it.
+    pub index_cache_hit: Option<bool>,
+    pub keygen_ms: Option<u128>,
+    /// Time to read saved prover and verifier keys, when they are reused instead of generated.
+    pub key_load_ms: Option<u128>,
+    pub prep_ms: Option<u128>,
+    pub prove_ms: Option<u128>,
+    pub verify_ms: Option<u128>,
+    pub proof_size_bytes: Option<usize>,
+
+    // R1CS shape
+    pub num_constraints: Option<usize>,
+    pub num_variables: Option<usize>,
+    pub num_non_zero: Option<usize>,
+
+    // AIR shape
+    pub air_layout: Option<String>,
+    pub trace_width: Option<usize>,
+    /// Columns of the auxiliary trace segment of a randomized AIR.
+    pub aux_trace_width: Option<usize>,
+    pub trace_length: Option<usize>,
+    pub num_transition_constraints: Option<usize>,
+    pub max_constraint_degree: Option<usize>,
+
+    // Security achieved, in bits; see the security module for how it is estimated
+    pub conjectured_security_bits: Option<u32>,
+    pub proven_security_bits: Option<u32>,
+
+    /// Public outputs of the program as canonical integers in `field`, if the proof system
+    /// exposes them. Only used to cross-check the runs of a comparison; FFT outputs alone would
+    /// dwarf the rest of the report, so they are not written out.
+    #[serde(skip)]
+    pub outputs: Option<Vec<u128>>,
+}
+
+/// Output formats accepted by `--report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Picks the format from the extension of the report path.
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(ReportFormat::Json),
+            Some("csv") => Ok(ReportFormat::Csv),
+            _ => Err(format!(
+                "Cannot infer report format for {}: use a .json or .csv extension",
+                path
+            )),
+        }
+    }
+}
+
+const CSV_HEADER: &str =
+    "program,system,size,iterations,field,hash_fn,field_extension,parse_ms,parse_bytes_per_constraint,index_ms,index_cache_hit,keygen_ms,\
+key_load_ms,prep_ms,prove_ms,verify_ms,proof_size_bytes,num_constraints,num_variables,num_non_zero,\
+air_layout,trace_width,aux_trace_width,trace_length,num_transition_constraints,max_constraint_degree,\
+conjectured_security_bits,proven_security_bits";
+
+fn csv_cell<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl RunReport {
+    pub fn new(program: &str, system: &str, size: u64) -> Self {
+        RunReport {
+            program: program.to_string(),
+            system: system.to_string(),
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// The proof system of the run, with its AIR layout if it has one, e.g. `air:rows`.
+    pub fn label(&self) -> String {
+        match &self.air_layout {
+            Some(layout) => format!("{}:{}", self.system, layout),
+            None => self.system.clone(),
+        }
+    }
+
+    fn to_csv_row(&self) -> String {
+        [
+            self.program.clone(),
+            self.system.clone(),
+            self.size.to_string(),
+            csv_cell(&self.iterations),
+            csv_cell(&self.field),
+            csv_cell(&self.hash_fn),
+            csv_cell(&self.field_extension),
+            csv_cell(&self.parse_ms),
+            csv_cell(&self.parse_bytes_per_constraint),
+            csv_cell(&self.index_ms),
+            csv_cell(&self.index_cache_hit),
+            csv_cell(&self.keygen_ms),
+            csv_cell(&self.key_load_ms),
+            csv_cell(&self.prep_ms),
+            csv_cell(&self.prove_ms),
+            csv_cell(&self.verify_ms),
+            csv_cell(&self.proof_size_bytes),
+            csv_cell(&self.num_constraints),
+            csv_cell(&self.num_variables),
+            csv_cell(&self.num_non_zero),
+            csv_cell(&self.air_layout),
+            csv_cell(&self.trace_width),
+            csv_cell(&self.aux_trace_width),
+            csv_cell(&self.trace_length),
+            csv_cell(&self.num_transition_constraints),
+            csv_cell(&self.max_constraint_degree),
+            csv_cell(&self.conjectured_security_bits),
+            csv_cell(&self.proven_security_bits),
+        ]
+        .join(",")
+    }
+}
+
+/// Writes `reports` to `path`, as a JSON array or as CSV with one row per report depending on
+/// the file extension. An existing file is overwritten.
+pub fn write_reports(reports: &[RunReport], path: &str) -> io::Result<()> {
+    let format = ReportFormat::from_path(path)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, reports)?;
+            writeln!(writer)?;
+        }
+        ReportFormat::Csv => {
+            writeln!(writer, "{}", CSV_HEADER)?;
+            for report in reports {
+                writeln!(writer, "{}", report.to_csv_row())?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+// OUTPUT CROSS-CHECK
+// ================================================================================================
+
+/// Why the outputs of two runs of a comparison could not be shown to agree.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OutputCheckError {
+    #[error("{program} at size 2^{size}: {run} disagrees with {reference} on the program output")]
+    Mismatch {
+        program: String,
+        size: u64,
+        reference: String,
+        run: String,
+    },
+    #[error("{program} at size 2^{size}: {run} does not expose its output, so it cannot be checked against {reference}")]
+    Unexposed {
+        program: String,
+        size: u64,
+        reference: String,
+        run: String,
+    },
+    #[error("{program} at size 2^{size}: the outputs of {reference} ({reference_field}) and {run} ({run_field}) are in different fields, so they cannot be checked")]
+    DifferentFields {
+        program: String,
+        size: u64,
+        reference: String,
+        reference_field: String,
+        run: String,
+        run_field: String,
+    },
+}
+
+/// Checks that `run` computed the same program output as `reference`, so that a comparison
+/// cannot silently pit different instances against each other. Runs whose outputs cannot be
+/// compared, because one of them does not expose its output or they are in different fields,
+/// fail the check as well.
+pub fn check_same_output(reference: &RunReport, run: &RunReport) -> Result<(), OutputCheckError> {
+    let (program, size) = (run.program.clone(), run.size);
+    let (expected, actual) = match (&reference.outputs, &run.outputs) {
+        (Some(expected), Some(actual)) => (expected, actual),
+        (None, _) => {
+            return Err(OutputCheckError::Unexposed {
+                program,
+                size,
+                reference: run.label(),
+                run: reference.label(),
+            })
+        }
+        (_, None) => {
+            return Err(OutputCheckError::Unexposed {
+                program,
+                size,
+                reference: reference.label(),
+                run: run.label(),
+            })
+        }
+    };
+    if reference.field != run.field {
+        return Err(OutputCheckError::DifferentFields {
+            program,
+            size,
+            reference: reference.label(),
+            reference_field: reference.field.clone().unwrap_or_else(|| "unknown".into()),
+            run: run.label(),
+            run_field: run.field.clone().unwrap_or_else(|| "unknown".into()),
+        });
+    }
+    if expected != actual {
+        return Err(OutputCheckError::Mismatch {
+            program,
+            size,
+            reference: reference.label(),
+            run: run.label(),
+        });
+    }
+    Ok(())
+}
@@ -1,24 +1,32 @@
 use log::debug;
-use std::time::Instant;
 use structopt::StructOpt;
-use winter_math::log2;
 
-use examples::{fast_fourier_transform, fibonacci, ExampleOptions, ExampleType};
+use examples::{ExampleOptions, ExampleType};
 #[cfg(feature = "std")]
 use winter_examples::{lamport, merkle};
 
-fn get_num_main_trace_rows(num_fft_inputs: usize) -> usize {
-    let log_num_fft_terms: usize = log2(num_fft_inputs).try_into().unwrap();
-    log_num_fft_terms + 2
-}
+use arithmetization_benchmarks::air::{
+    build_air_example, build_program_example, run_air_example, AirOptions,
+};
+use arithmetization_benchmarks::registry::PROGRAM_REGISTRY;
+use arithmetization_benchmarks::report::{write_reports, RunReport};
+use winter_math::log2;
 
-fn get_num_cols(num_fft_inputs: usize) -> usize {
-    let log_num_fft_terms: usize = log2(num_fft_inputs).try_into().unwrap();
-    // the first num_fft_inputs are for keeping the actual values at eachs step
-    // the next value is for keeping the local omegas
-    // Then, we store log_num_fft_terms + 1 bits which help select the function to apply
-    // Finally, the additional position is to keep the power of 2 represented by the aforementioned bits
-    num_fft_inputs + 1 + 1 + (log_num_fft_terms + 1) + 2
+#[derive(StructOpt, Debug)]
+#[structopt(name = "stark-orchestrator", about = "Winterfell AIR examples")]
+struct StarkOptions {
+    #[structopt(flatten)]
+    example_options: ExampleOptions,
+
+    /// Write a machine-readable run report to this .json or .csv file.
+    #[structopt(long = "report")]
+    report: Option<String>,
+
+    /// Trace layout to prove the program under instead of the example's own, e.g. `rows` for an
+    /// FFT with one butterfly per row of a four column trace, which supports sizes beyond 128.
+    /// `arith-bench list` shows the layouts of each program.
+    #[structopt(long = "layout")]
+    layout: Option<String>,
 }
 
 // EXAMPLE RUNNER
@@ -26,47 +34,66 @@ fn get_num_cols(num_fft_inputs: usize) -> usize {
 
 fn main() {
     // read command-line args
-    let options = ExampleOptions::from_args();
+    let StarkOptions {
+        example_options: options,
+        report: report_path,
+        layout,
+    } = StarkOptions::from_args();
 
     println!("============================================================");
 
     // instantiate and prepare the example
-    let example = match options.example {
-        ExampleType::Fib { sequence_length } => {
-            fibonacci::mulfib2::get_example(&options, sequence_length).unwrap()
-        }
-        ExampleType::FFT { degree } => {
-            let num_cols = get_num_cols(degree);
-            let num_rows = get_num_main_trace_rows(degree);
-            println!(
-                "FFT size {} has {} columns and {} rows",
-                degree, num_cols, num_rows
-            );
-            fast_fourier_transform::get_example(&options, degree).unwrap()
-        }
-        _ => {
-            println!("Example type for STARKs not supported");
-            return;
+    let (program, example_layout, size) = match options.example {
+        ExampleType::Fib { sequence_length } => ("fib", "mulfib2", sequence_length as u64),
+        ExampleType::Mulfib8 { sequence_length } => ("fib", "mulfib8", sequence_length as u64),
+        ExampleType::FFT { degree } => ("fft", "columns", degree as u64),
+        _ => ("", "", 0),
+    };
+    let spec = (!program.is_empty()).then(|| PROGRAM_REGISTRY.lookup(program).unwrap());
+    let air_layout = spec.map(|spec| {
+        let name = layout.as_deref().unwrap_or(example_layout);
+        spec.air_layout(Some(name))
+            .unwrap_or_else(|err| panic!("{}", err))
+    });
+    let example = match (spec, air_layout, layout.is_some()) {
+        // other layouts come from the registry, which also holds the AIRs implemented in this
+        // crate
+        (Some(spec), Some(air_layout), true) => {
+            let air_options = AirOptions::from_example_options(&options);
+            let log_size = log2(size as usize) as u64;
+            build_program_example(spec, air_layout, log_size, None, &air_options, false)
+                .unwrap_or_else(|err| panic!("{}", err))
         }
+        (None, _, true) => panic!("Example type has no AIR layouts"),
+        _ => match build_air_example(&options) {
+            Some(example) => example,
+            None => {
+                println!("Example type for STARKs not supported");
+                return;
+            }
+        },
     };
 
-    // generate stark proof
-    let now = Instant::now();
-    let proof = example.prove();
-    println!(
-        "---------------------\nProof generated in {} ms",
-        now.elapsed().as_millis()
-    );
-
-    // verify the proof
-    println!("---------------------");
-    let now = Instant::now();
-    match example.verify(proof) {
-        Ok(_) => println!(
-            "Proof verified in {:.1} ms",
-            now.elapsed().as_micros() as f64 / 1000f64
-        ),
-        Err(msg) => debug!("Failed to verify proof: {}", msg),
+    // generate and verify the stark proof
+    let mut report = RunReport::new(program, "air", size);
+    let degrees = spec.zip(air_layout).and_then(|(spec, air_layout)| {
+        let log_size = log2(size as usize) as u64;
+        spec.air_constraint_degrees(air_layout, log_size, None)
+            .ok()
+            .flatten()
+    });
+    if let Err(msg) = run_air_example(
+        example.as_ref(),
+        air_layout,
+        degrees.as_deref(),
+        true,
+        &mut report,
+    ) {
+        debug!("Failed to verify proof: {}", msg);
     }
     println!("============================================================");
+
+    if let Some(path) = report_path {
+        write_reports(&[report], &path).unwrap();
+    }
 }
@@ -3,47 +3,15 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use core::num;
-use nohash_hasher::NoHashHasher;
-use std::cmp::max;
-use std::collections::HashMap;
-use std::hash::BuildHasherDefault;
-use std::time::Instant;
-
-// use fractal_indexer::index::get_max_degree;
-use fractal_proofs::{fft, FractalProverOptions, Serializable};
-use fractal_prover::LayeredProver;
-use fractal_prover::{prover::FractalProver, LayeredSubProver};
-use fractal_utils::FractalOptions;
-use fractal_verifier::verifier::verify_layered_fractal_proof_from_top;
-use winter_fri::FriOptions;
-use winter_models::r1cs::Matrix;
-
 use structopt::StructOpt;
 
-use fractal_indexer::{
-    index::{build_index_domains, Index, IndexParams},
-    indexed_matrix::index_matrix,
-    snark_keys::*,
-};
+use arithmetization_benchmarks::air::AirOptions;
+use arithmetization_benchmarks::pipeline::FractalPipelineOptions;
+use arithmetization_benchmarks::proof_system::{build_proof_system, run_proof_system, R1csSource};
+use arithmetization_benchmarks::registry::{SystemTag, PROGRAM_REGISTRY};
+use arithmetization_benchmarks::report::{write_reports, RunReport};
 
 use reports::reporter::generate_flame_report;
-use winter_models::jsnark_arith_parser::JsnarkArithReaderParser;
-use winter_models::jsnark_wire_parser::JsnarkWireReaderParser;
-use winter_models::utils::{print_vec, print_vec_bits};
-
-use winter_crypto::hashers::{Blake3_256, Rp64_256};
-use winter_crypto::ElementHasher;
-
-use winter_math::fields::f64::BaseElement;
-use winter_math::fields::QuadExtension;
-use winter_math::FieldElement;
-use winter_math::StarkField;
-use winter_math::*;
-
-macro_rules! println_if {
-    ($verbose:expr, $($x:tt)*) => { if $verbose { println!($($x)*) } }
-}
 
 #[cfg(feature = "flame_it")]
 extern crate flame;
@@ -59,36 +27,53 @@ fn main() {
         println!("Program {}, size {}", options.program, options.size);
     }
 
-    let program_name = match options.program.as_str() {
-        "fft" | "fftexample" => "fftexample",
-        "fib" | "fibonacciexample" => "fibonacciexample",
-        "" | "default" | "sample" => "sample",
-        other => panic!("Unsupported program: {}", other),
+    let spec = PROGRAM_REGISTRY
+        .lookup(options.program.as_str())
+        .unwrap_or_else(|err| panic!("{}", err));
+    let program_name = spec.r1cs_stem.unwrap();
+    let mut report = RunReport::new(program_name, "r1cs", options.size);
+    report.field = Some(options.field.clone());
+    report.hash_fn = Some(options.hash_fn.clone());
+    report.field_extension = Some(options.field_extension);
+    let r1cs_options = FractalPipelineOptions {
+        field: options.field.clone(),
+        hash_fn: options.hash_fn.clone(),
+        field_extension: options.field_extension,
+        verbose: options.verbose,
+        key_dir: options.key_dir.clone(),
+        index_cache_dir: (!options.no_index_cache).then(|| options.index_cache_dir.clone()),
+        index_cache_limit: options.index_cache_limit_mib << 20,
+        ..FractalPipelineOptions::default()
     };
-
-    let supported_sizes = match program_name {
-        "fftexample" => 5u64..15u64,
-        "fibonacciexample" => 5u64..21u64,
-        "sample" => 1u64..2u64,
-        other => panic!("Unsupported program: {}", other),
+    let r1cs_source = match (&options.inputs_file, options.native) {
+        (Some(_), true) => panic!("--inputs and --native cannot be combined"),
+        (Some(inputs_file), false) => R1csSource::Inputs(inputs_file.clone()),
+        (None, true) => R1csSource::Native,
+        (None, false) => R1csSource::Fixture,
     };
-    assert!(
-        supported_sizes.contains(&options.size),
-        "Unsupported program size: {}",
-        &options.size
-    );
-
-    let program_path = format!("src/jsnark_outputs/{program_name}_{}", options.size);
-
-    let arith_file = format!("{program_path}.arith");
-    let wires_file = format!("{program_path}.wires");
+    report.iterations = if options.native {
+        options.iterations.or_else(|| spec.default_iterations())
+    } else {
+        spec.resolve_iterations(options.iterations).unwrap()
+    };
+    let mut proof_system = build_proof_system(
+        spec,
+        SystemTag::R1CS,
+        options.size,
+        report.iterations,
+        None,
+        &AirOptions::default(),
+        &r1cs_source,
+        &r1cs_options,
+    )
+    .unwrap_or_else(|err| panic!("{}", err));
+    run_proof_system(proof_system.as_mut(), true, &mut report)
+        .unwrap_or_else(|err| panic!("{}", err));
+    println!("Success!");
 
-    //orchestrate_r1cs_example::<BaseElement, QuadExtension<BaseElement>, Rp64_256, 1>(
-    orchestrate_r1cs_example::<BaseElement, BaseElement, Blake3_256<BaseElement>, 1>(
-        &arith_file,
-        &wires_file,
-        options.verbose,
-    );
+    if let Some(path) = &options.report {
+        write_reports(&[report], path).unwrap();
+    }
 
     #[cfg(feature = "flame_it")]
     {
@@ -101,203 +86,6 @@ fn main() {
     }
 }
 
-#[cfg_attr(feature = "flame_it", flame)]
-pub(crate) fn orchestrate_r1cs_example<
-    B: StarkField,
-    E: FieldElement<BaseField = B>,
-    H: ElementHasher + ElementHasher<BaseField = B>,
-    const N: usize,
->(
-    arith_file: &str,
-    wire_file: &str,
-    verbose: bool,
-) {
-    println_if!(
-        verbose,
-        "============================================================"
-    );
-    println_if!(verbose, "Getting setup");
-    println_if!(
-        verbose,
-        "Step 1: Parse jsnark files {}, {}",
-        arith_file,
-        wire_file
-    );
-
-    let now = Instant::now();
-    let mut arith_parser = JsnarkArithReaderParser::<B>::new().unwrap();
-    arith_parser.parse_arith_file(&arith_file, false /* verbose */);
-    println_if!(
-        verbose,
-        "Parsed arith file in {} ms",
-        now.elapsed().as_millis()
-    );
-    let mut r1cs = arith_parser.r1cs_instance;
-
-    // if (verbose) {
-    //     println!("R1CS:");
-    //     r1cs.debug_print_bits();
-    // }
-
-    let mut wires_parser = JsnarkWireReaderParser::<B>::new().unwrap();
-    wires_parser.parse_wire_file(&wire_file, false /* verbose */);
-    println_if!(verbose, "Parsed the wire file");
-    let wires = wires_parser.wires;
-    println_if!(verbose, "---------------------");
-    println_if!(verbose, "Step 2: Computing the various parameters");
-
-    // 0. Compute num_non_zero by counting max(number of non-zero elts across A, B, C).
-
-    // let num_input_variables = r1cs.clone().num_cols();
-    // let num_constraints = r1cs.clone().num_rows();
-    // let num_non_zero = max(max(r1cs.A.l0_norm(), r1cs.B.l0_norm()), r1cs.C.l0_norm());
-    // 1. Index this R1CS
-    let num_input_variables = r1cs.num_cols().next_power_of_two();
-    let num_non_zero = r1cs.max_num_nonzero().next_power_of_two();
-    let num_constraints =
-        max(max(r1cs.A.num_rows(), r1cs.B.num_rows()), r1cs.C.num_rows()).next_power_of_two();
-    let max_degree = FractalProver::<B, E, H>::get_max_degree_constraint(
-        num_input_variables,
-        num_non_zero,
-        num_constraints,
-    );
-    // TODO: make the calculation of eta automated
-    let eta = B::GENERATOR.exp(B::PositiveInteger::from(2 * B::TWO_ADICITY));
-    let eta_k = B::GENERATOR.exp(B::PositiveInteger::from(1337 * B::TWO_ADICITY));
-    // if num_non_zero <= num_vars {
-    //     num_non_zero = num_non_zero * 2;
-    // }
-    let index_params = IndexParams::<B> {
-        num_input_variables,
-        num_constraints,
-        num_non_zero,
-        max_degree,
-        eta,
-        eta_k,
-    };
-
-    let now_prep = Instant::now();
-
-    let degree_fs = r1cs.num_cols();
-    println_if!(verbose, "---------------------");
-    println_if!(verbose, "Step 3: Building Index Domains");
-    let index_domains = build_index_domains::<B>(index_params.clone());
-    println_if!(verbose, "built index domains");
-
-    println_if!(verbose, "---------------------");
-    println_if!(verbose, "Step 3: Building Indexes");
-
-    let now = Instant::now();
-    let indexed_a = index_matrix::<B>(&mut r1cs.A, &index_domains);
-    println_if!(verbose, "Indexed A in {} ms", now.elapsed().as_millis());
-
-    let now = Instant::now();
-    let indexed_b = index_matrix::<B>(&mut r1cs.B, &index_domains);
-    println_if!(verbose, "Indexed B in {} ms", now.elapsed().as_millis());
-
-    let now = Instant::now();
-    let indexed_c = index_matrix::<B>(&mut r1cs.C, &index_domains);
-    println_if!(verbose, "Indexed C in {} ms", now.elapsed().as_millis());
-
-    // This is the index i.e. the pre-processed data for this r1cs
-    let index = Index::new(index_params.clone(), indexed_a, indexed_b, indexed_c);
-
-    // TODO: the IndexDomains should already guarantee powers of two, so why add extraneous bit or use next_power_of_two?
-
-    let size_subgroup_h = index_domains.h_field.len().next_power_of_two();
-    let size_subgroup_k = index_domains.k_field.len().next_power_of_two();
-
-    let evaluation_domain = get_power_series(index_domains.l_field_base, index_domains.l_field_len);
-
-    let summing_domain = index_domains.k_field;
-
-    let h_domain = index_domains.h_field;
-    let lde_blowup = 4;
-    let num_queries = 16;
-    let fri_options = FriOptions::new(lde_blowup, 4, 32);
-    //println!("h_domain: {:?}, summing_domain: {:?}, evaluation_domain: {:?}", &h_domain, &summing_domain, &evaluation_domain);
-    let options: FractalOptions<B> = FractalOptions::<B> {
-        degree_fs,
-        size_subgroup_h,
-        size_subgroup_k,
-        summing_domain: summing_domain.clone(),
-        evaluation_domain: evaluation_domain.clone(),
-        h_domain: h_domain.clone(),
-        eta,
-        eta_k,
-        fri_options: fri_options.clone(),
-        num_queries,
-    };
-
-    println!("Prover size subgroup h = {:?}", size_subgroup_h);
-    println!("Prover size subgroup h = {:?}", size_subgroup_k);
-
-    let h_domain_twiddles = fft::get_twiddles(size_subgroup_h);
-    let h_domain_inv_twiddles = fft::get_inv_twiddles(size_subgroup_h);
-    let k_domain_twiddles = fft::get_twiddles(size_subgroup_k);
-    let k_domain_inv_twiddles = fft::get_inv_twiddles(size_subgroup_k);
-    let l_domain_twiddles = fft::get_twiddles(evaluation_domain.len());
-    let l_domain_inv_twiddles = fft::get_inv_twiddles(evaluation_domain.len());
-    let prover_options: FractalProverOptions<B> = FractalProverOptions::<B> {
-        degree_fs,
-        size_subgroup_h,
-        size_subgroup_k,
-        summing_domain,
-        evaluation_domain,
-        h_domain,
-        h_domain_twiddles,
-        h_domain_inv_twiddles,
-        k_domain_twiddles,
-        k_domain_inv_twiddles,
-        l_domain_twiddles,
-        l_domain_inv_twiddles,
-        eta,
-        eta_k,
-        fri_options: fri_options.clone(),
-        num_queries,
-    };
-
-    let (prover_key, verifier_key) =
-        generate_prover_and_verifier_keys::<B, E, H>(index, &options).unwrap();
-    println!("Prover and verifier keys generated");
-    println!("Total prep time {} ms", now_prep.elapsed().as_millis());
-    let pub_inputs_bytes = vec![0u8, 1u8, 2u8];
-    //let pub_inputs_bytes = vec![];
-    let mut prover =
-        FractalProver::<B, E, H>::new(prover_key.into(), vec![], wires, pub_inputs_bytes.clone());
-    let now = Instant::now();
-    let proof = prover
-        .generate_proof(&None, pub_inputs_bytes.clone(), &prover_options)
-        .unwrap();
-    println!(
-        "---------------------\nProof generated in {} ms",
-        now.elapsed().as_millis()
-    );
-
-    let proof_bytes = proof.to_bytes();
-    println_if!(verbose, "Proof size: {}", proof_bytes.len());
-
-    let now = Instant::now();
-    verify_layered_fractal_proof_from_top(&verifier_key, &proof, &pub_inputs_bytes, &options)
-        .unwrap();
-    println_if!(
-        verbose,
-        "---------------------\nProof verified in {} ms",
-        now.elapsed().as_millis()
-    );
-    println!("Success!");
-
-    // println!(
-    //     "Verified: {:?}",
-    //     fractal_verifier::verifier::verify_fractal_proof::<B, E, H>(
-    //         verifier_key,
-    //         proof,
-    //         pub_inputs_bytes,
-    //         options
-    //     )
-    // );
-}
-
 #[derive(StructOpt, Debug)]
 #[structopt(name = "jsnark-parser", about = "Jsnark file parsing")]
 struct ExampleOptions {
@@ -328,4 +116,51 @@ struct ExampleOptions {
     /// Size of the program instance
     #[structopt(short = "s", long = "size", default_value = "5")]
     size: u64,
+
+    /// Log2 of the number of iterations, for programs which take one (ptrchase).
+    #[structopt(short = "i", long = "iterations")]
+    iterations: Option<u64>,
+
+    /// Build the R1CS instance in Rust instead of reading the jsnark fixtures. Any size is
+    /// accepted.
+    #[structopt(long = "native")]
+    native: bool,
+
+    /// Jsnark .in file to compute the witness from, instead of the fixture's .wires file.
+    #[structopt(long = "inputs")]
+    inputs_file: Option<String>,
+
+    /// Base field: f62, f64 or f128.
+    #[structopt(long = "field", default_value = "f64")]
+    field: String,
+
+    /// Hash function: blake3_256, blake3_192, sha3_256, rp62_248 (over f62) or rp64_256 (over f64).
+    #[structopt(long = "hash", default_value = "blake3_256")]
+    hash_fn: String,
+
+    /// Degree of the field extension: 1 (none), 2 or 3.
+    #[structopt(long = "field-extension", default_value = "1")]
+    field_extension: u32,
+
+    /// Save the prover and verifier keys in this directory, and reuse them on later runs of the
+    /// same circuit with the same options instead of indexing it again.
+    #[structopt(long = "keys")]
+    key_dir: Option<String>,
+
+    /// Directory R1CS indexes are cached in across runs.
+    #[structopt(long = "index-cache", default_value = "target/index-cache")]
+    index_cache_dir: String,
+
+    /// Always index the R1CS instance instead of using the index cache.
+    #[structopt(long = "no-index-cache")]
+    no_index_cache: bool,
+
+    /// Size limit of the index cache in MiB, beyond which the least recently used indexes are
+    /// evicted.
+    #[structopt(long = "index-cache-limit", default_value = "4096")]
+    index_cache_limit_mib: u64,
+
+    /// Write a machine-readable run report to this .json or .csv file.
+    #[structopt(long = "report")]
+    report: Option<String>,
 }
@@ -0,0 +1,327 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Proof parameters derived from a target security level, and the security a run achieves.
+//!
+//! Both proof systems end in FRI, so their soundness is governed by the same parameters: the LDE
+//! blowup factor, the number of queries and the proof-of-work grinding bits. Deriving them from a
+//! single target puts AIR and R1CS runs at equal security, as long as the field and the hash
+//! function can carry it: no number of queries makes up for a field which is too small, so a
+//! target above the [SecurityCeiling] of a run is an error rather than silently capped.
+//!
+//! AIR proofs report their security through winterfell's `StarkProof::security_level`. For
+//! Fractal proofs, [estimate_security] computes
+//!
+//! * the conjectured security with winterfell's formula: each query contributes `log2(blowup)`
+//!   bits, plus the grinding bits, capped by the size of the field relative to the LDE domain and
+//!   by the collision resistance of the hash function;
+//! * a proven security in the unique-decoding regime of FRI, where each query contributes only
+//!   `-log2((1 + 1 / blowup) / 2)` bits. This is more conservative than winterfell's proven
+//!   estimate, so proven numbers favour AIR slightly.
+
+use std::cmp::min;
+
+use thiserror::Error;
+use winter_crypto::Hasher;
+use winter_math::StarkField;
+
+use crate::field::FieldError;
+
+/// Blowup factor used for derived parameters; each query then contributes 3 bits.
+pub const SECURITY_BLOWUP_FACTOR: usize = 8;
+/// Folding factor used for derived parameters, supported by both FRI implementations.
+pub const SECURITY_FOLDING_FACTOR: usize = 4;
+/// Grinding bits used for derived parameters, for proof systems which support grinding.
+pub const SECURITY_GRINDING_FACTOR: u32 = 16;
+/// Largest number of queries winterfell accepts.
+pub const MAX_NUM_QUERIES: usize = 128;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SecurityError {
+    #[error("Target security level must be positive")]
+    ZeroTarget,
+    #[error("{target} bits of security need {num_queries} queries, more than the {MAX_NUM_QUERIES} supported")]
+    TooManyQueries { target: u32, num_queries: usize },
+    #[error("{target} bits of security need a larger field than {field_bits} bits, which give at most {max_bits}; use a larger field extension or base field")]
+    FieldTooSmall {
+        target: u32,
+        field_bits: u32,
+        max_bits: u32,
+    },
+    #[error("{target} bits of security need a hash function with more than {collision_resistance} bits of collision resistance")]
+    HashTooWeak {
+        target: u32,
+        collision_resistance: u32,
+    },
+    #[error(
+        "{label} reached {achieved} bits of conjectured security, below the target of {target}"
+    )]
+    TargetNotReached {
+        label: String,
+        target: u32,
+        achieved: u32,
+    },
+}
+
+/// The most security proofs over a field and with a hash function can have, whatever their
+/// queries and grinding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityCeiling {
+    /// Bits of the field the proof's challenges are drawn from, the base field times the
+    /// extension degree.
+    pub field_bits: u32,
+    pub collision_resistance: u32,
+}
+
+impl SecurityCeiling {
+    /// The ceiling of proofs over the base field named `field`, extended to degree
+    /// `field_extension`, and hashed with the hash function named `hash_fn`.
+    pub fn of(field: &str, field_extension: u32, hash_fn: &str) -> Result<Self, FieldError> {
+        crate::with_r1cs_field!(field, hash_fn, B, H => SecurityCeiling {
+            field_bits: B::MODULUS_BITS * field_extension,
+            collision_resistance: <H as Hasher>::COLLISION_RESISTANCE,
+        })
+    }
+
+    /// The largest target the field can carry: the conjectured security of a proof is capped by
+    /// the field bits less those of the LDE domain, which has at least `blowup_factor` points.
+    /// Larger instances have larger domains, so a run can still fall short of this, which
+    /// [check_target_reached] reports.
+    pub fn max_field_bits(&self, blowup_factor: usize) -> u32 {
+        self.field_bits
+            .saturating_sub(log2(blowup_factor))
+            .saturating_sub(1)
+    }
+}
+
+/// FRI parameters which determine the soundness of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityParams {
+    pub blowup_factor: usize,
+    pub num_queries: usize,
+    pub grinding_factor: u32,
+    pub folding_factor: usize,
+}
+
+impl SecurityParams {
+    /// The parameters reaching `target_bits` of conjectured query security. Proof systems without
+    /// grinding make up for it with more queries. Fails if the target is above `ceiling`.
+    pub fn for_target(
+        target_bits: u32,
+        supports_grinding: bool,
+        ceiling: &SecurityCeiling,
+    ) -> Result<Self, SecurityError> {
+        if target_bits == 0 {
+            return Err(SecurityError::ZeroTarget);
+        }
+        let max_bits = ceiling.max_field_bits(SECURITY_BLOWUP_FACTOR);
+        if target_bits > max_bits {
+            return Err(SecurityError::FieldTooSmall {
+                target: target_bits,
+                field_bits: ceiling.field_bits,
+                max_bits,
+            });
+        }
+        if target_bits > ceiling.collision_resistance {
+            return Err(SecurityError::HashTooWeak {
+                target: target_bits,
+                collision_resistance: ceiling.collision_resistance,
+            });
+        }
+        let grinding_factor = if supports_grinding {
+            min(SECURITY_GRINDING_FACTOR, target_bits)
+        } else {
+            0
+        };
+        // the estimates take one bit off the query security, so the queries cover one bit more
+        let query_bits = target_bits + 1 - grinding_factor;
+        let bits_per_query = log2(SECURITY_BLOWUP_FACTOR);
+        let num_queries = ((query_bits + bits_per_query - 1) / bits_per_query).max(1) as usize;
+        if num_queries > MAX_NUM_QUERIES {
+            return Err(SecurityError::TooManyQueries {
+                target: target_bits,
+                num_queries,
+            });
+        }
+        Ok(SecurityParams {
+            blowup_factor: SECURITY_BLOWUP_FACTOR,
+            num_queries,
+            grinding_factor,
+            folding_factor: SECURITY_FOLDING_FACTOR,
+        })
+    }
+}
+
+/// The security achieved by a run, in bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityReport {
+    pub conjectured_bits: u32,
+    pub proven_bits: u32,
+}
+
+/// Estimates the security of a proof with `params` over a field of `field_bits` bits (including
+/// the extension degree), an LDE domain of `lde_domain_size` points, and a hash function with
+/// `collision_resistance` bits.
+pub fn estimate_security(
+    params: &SecurityParams,
+    field_bits: u32,
+    lde_domain_size: usize,
+    collision_resistance: u32,
+) -> SecurityReport {
+    let domain_bits = log2(lde_domain_size);
+
+    let field_security = field_bits.saturating_sub(domain_bits);
+    let query_security = log2(params.blowup_factor) * params.num_queries as u32;
+    let conjectured_bits = min(
+        min(field_security, query_security + params.grinding_factor).saturating_sub(1),
+        collision_resistance,
+    );
+
+    let rate = 1.0 / params.blowup_factor as f64;
+    let bits_per_query = -((1.0 + rate) / 2.0).log2();
+    let query_security = (bits_per_query * params.num_queries as f64).floor() as u32;
+    let field_security = field_bits.saturating_sub(2 * domain_bits);
+    let proven_bits = min(
+        min(field_security, query_security + params.grinding_factor).saturating_sub(1),
+        collision_resistance,
+    );
+
+    SecurityReport {
+        conjectured_bits,
+        proven_bits,
+    }
+}
+
+/// Checks that the run `label`, which reached `conjectured_bits` of security, meets the target of
+/// `target_bits`.
+pub fn check_target_reached(
+    label: &str,
+    conjectured_bits: u32,
+    target_bits: u32,
+) -> Result<(), SecurityError> {
+    if conjectured_bits >= target_bits {
+        Ok(())
+    } else {
+        Err(SecurityError::TargetNotReached {
+            label: label.to_string(),
+            target: target_bits,
+            achieved: conjectured_bits,
+        })
+    }
+}
+
+fn log2(n: usize) -> u32 {
+    usize::BITS - 1 - n.max(1).leading_zeros()
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LARGE_CEILING: SecurityCeiling = SecurityCeiling {
+        field_bits: 192,
+        collision_resistance: 128,
+    };
+
+    #[test]
+    fn derives_queries_and_grinding() {
+        let air = SecurityParams::for_target(96, true, &LARGE_CEILING).unwrap();
+        assert_eq!(air.grinding_factor, SECURITY_GRINDING_FACTOR);
+        assert_eq!(air.num_queries, 27);
+        assert_eq!(air.blowup_factor, SECURITY_BLOWUP_FACTOR);
+        assert_eq!(air.folding_factor, SECURITY_FOLDING_FACTOR);
+
+        let r1cs = SecurityParams::for_target(96, false, &LARGE_CEILING).unwrap();
+        assert_eq!(r1cs.grinding_factor, 0);
+        assert_eq!(r1cs.num_queries, 33);
+    }
+
+    #[test]
+    fn grinding_never_exceeds_target() {
+        let params = SecurityParams::for_target(8, true, &LARGE_CEILING).unwrap();
+        assert_eq!(params.grinding_factor, 8);
+        assert_eq!(params.num_queries, 1);
+    }
+
+    #[test]
+    fn derived_params_reach_target() {
+        for target in [32, 80, 96, 100, 128] {
+            for supports_grinding in [false, true] {
+                let params =
+                    SecurityParams::for_target(target, supports_grinding, &LARGE_CEILING).unwrap();
+                let report = estimate_security(
+                    &params,
+                    LARGE_CEILING.field_bits,
+                    1 << 20,
+                    LARGE_CEILING.collision_resistance,
+                );
+                assert!(
+                    report.conjectured_bits >= target,
+                    "{} bits with grinding {}: {:?}",
+                    target,
+                    supports_grinding,
+                    report
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_targets() {
+        assert_eq!(
+            SecurityParams::for_target(0, true, &LARGE_CEILING),
+            Err(SecurityError::ZeroTarget)
+        );
+        let ceiling = SecurityCeiling {
+            field_bits: 1024,
+            collision_resistance: 1024,
+        };
+        assert_eq!(
+            SecurityParams::for_target(400, false, &ceiling),
+            Err(SecurityError::TooManyQueries {
+                target: 400,
+                num_queries: 134,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_target_above_field() {
+        let ceiling = SecurityCeiling::of("f64", 1, "blake3_256").unwrap();
+        assert_eq!(
+            SecurityParams::for_target(96, false, &ceiling),
+            Err(SecurityError::FieldTooSmall {
+                target: 96,
+                field_bits: 64,
+                max_bits: 60,
+            })
+        );
+        let ceiling = SecurityCeiling::of("f64", 2, "blake3_256").unwrap();
+        assert!(SecurityParams::for_target(96, false, &ceiling).is_ok());
+    }
+
+    #[test]
+    fn rejects_target_above_hash() {
+        let ceiling = SecurityCeiling::of("f64", 3, "blake3_192").unwrap();
+        assert_eq!(
+            SecurityParams::for_target(100, true, &ceiling),
+            Err(SecurityError::HashTooWeak {
+                target: 100,
+                collision_resistance: 96,
+            })
+        );
+    }
+
+    #[test]
+    fn checks_reached_target() {
+        assert!(check_target_reached("r1cs", 96, 96).is_ok());
+        assert!(matches!(
+            check_target_reached("r1cs", 95, 96),
+            Err(SecurityError::TargetNotReached { achieved: 95, .. })
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) Jasleen Malvai, Tom Yurek and Don Beaver.
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Sparse R1CS matrices, built one constraint at a time.
+//!
+//! Both the streaming jsnark parser and the native circuits produce a [SparseR1CS], whose size is
+//! proportional to the number of non-zero entries. It is only laid out as the
+//! `winter_models::r1cs` matrices expected by the Fractal indexer when it is handed over.
+
+use std::mem::size_of;
+
+use winter_math::StarkField;
+use winter_models::r1cs::{Matrix, R1CS};
+use winter_utils::Serializable;
+
+use crate::binary::{fnv1a, FNV_OFFSET_BASIS};
+
+// SPARSE MATRIX
+// ================================================================================================
+
+/// A matrix in compressed sparse row form: the entries of row `i` are
+/// `entries[row_offsets[i]..row_offsets[i + 1]]`, sorted by column.
+#[derive(Debug, Clone)]
+pub struct SparseMatrix<B: StarkField> {
+    name: &'static str,
+    row_offsets: Vec<usize>,
+    entries: Vec<(usize, B)>,
+}
+
+impl<B: StarkField> SparseMatrix<B> {
+    pub fn new(name: &'static str) -> Self {
+        SparseMatrix {
+            name,
+            row_offsets: vec![0],
+            entries: vec![],
+        }
+    }
+
+    /// Rebuilds a matrix from its row offsets and entries, checking that they are consistent.
+    pub fn from_parts(
+        name: &'static str,
+        row_offsets: Vec<usize>,
+        entries: Vec<(usize, B)>,
+    ) -> Result<Self, String> {
+        let offsets_valid = row_offsets.first() == Some(&0)
+            && row_offsets.last() == Some(&entries.len())
+            && row_offsets.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !offsets_valid {
+            return Err(format!("invalid row offsets for matrix {}", name));
+        }
+        Ok(SparseMatrix {
+            name,
+            row_offsets,
+            entries,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.row_offsets.len() - 1
+    }
+
+    pub fn num_non_zero(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Appends a row given as (column, coefficient) terms. Terms on the same column are summed
+    /// and zero coefficients are dropped.
+    pub fn push_row<I: IntoIterator<Item = (usize, B)>>(&mut self, terms: I) {
+        let start = self.entries.len();
+        self.entries.extend(terms);
+        self.entries[start..].sort_unstable_by_key(|(col, _)| *col);
+
+        // merge terms on the same column in place
+        let mut last = start;
+        for i in start..self.entries.len() {
+            let (col, coeff) = self.entries[i];
+            if i > start && self.entries[last].0 == col {
+                self.entries[last].1 += coeff;
+            } else {
+                if i > start && self.entries[last].1 != B::ZERO {
+                    last += 1;
+                }
+                self.entries[last] = (col, coeff);
+            }
+        }
+        if self.entries.len() > start && self.entries[last].1 != B::ZERO {
+            last += 1;
+        }
+        self.entries.truncate(last);
+        self.row_offsets.push(self.entries.len());
+    }
+
+    pub fn row_offsets(&self) -> &[usize] {
+        &self.row_offsets
+    }
+
+    pub fn entries(&self) -> &[(usize, B)] {
+        &self.entries
+    }
+
+    pub fn row(&self, row: usize) -> &[(usize, B)] {
+        &self.entries[self.row_offsets[row]..self.row_offsets[row + 1]]
+    }
+
+    /// The inner product of `row` with the wire assignment. Columns beyond the assignment are
+    /// treated as 0.
+    pub fn evaluate_row(&self, row: usize, wires: &[B]) -> B {
+        self.row(row).iter().fold(B::ZERO, |sum, (col, coeff)| {
+            sum + *coeff * wires.get(*col).copied().unwrap_or(B::ZERO)
+        })
+    }
+
+    /// Moves every column from `first` on `count` columns to the right, leaving columns
+    /// `first..first + count` empty. Rows stay sorted since their order is kept.
+    pub fn shift_columns(&mut self, first: usize, count: usize) {
+        for (col, _) in self.entries.iter_mut() {
+            if *col >= first {
+                *col += count;
+            }
+        }
+    }
+
+    /// Bytes of heap memory held by this matrix.
+    pub fn heap_bytes(&self) -> usize {
+        self.row_offsets.capacity() * size_of::<usize>()
+            + self.entries.capacity() * size_of::<(usize, B)>()
+    }
+
+    fn to_matrix(&self, num_cols: usize) -> Matrix<B> {
+        let rows = (0..self.num_rows())
+            .map(|row| {
+                let mut dense_row = vec![B::ZERO; num_cols];
+                for (col, coeff) in self.row(row) {
+                    dense_row[*col] = *coeff;
+                }
+                dense_row
+            })
+            .collect();
+        Matrix::new(self.name, rows).unwrap()
+    }
+}
+
+// SPARSE R1CS
+// ================================================================================================
+
+/// The A, B and C matrices of an R1CS instance in sparse form, with one column per wire.
+#[derive(Debug, Clone)]
+pub struct SparseR1CS<B: StarkField> {
+    pub a: SparseMatrix<B>,
+    pub b: SparseMatrix<B>,
+    pub c: SparseMatrix<B>,
+    num_cols: usize,
+}
+
+impl<B: StarkField> SparseR1CS<B> {
+    pub fn new(num_cols: usize) -> Self {
+        SparseR1CS {
+            a: SparseMatrix::new("A"),
+            b: SparseMatrix::new("B"),
+            c: SparseMatrix::new("C"),
+            num_cols,
+        }
+    }
+
+    /// Assembles an instance from matrices with the same number of rows.
+    pub fn from_matrices(
+        a: SparseMatrix<B>,
+        b: SparseMatrix<B>,
+        c: SparseMatrix<B>,
+        num_cols: usize,
+    ) -> Self {
+        assert!(
+            a.num_rows() == b.num_rows() && b.num_rows() == c.num_rows(),
+            "A, B and C must have the same number of rows"
+        );
+        SparseR1CS { a, b, c, num_cols }
+    }
+
+    /// Appends the constraint `<a, z> * <b, z> = <c, z>`. Columns beyond the current number of
+    /// columns extend it.
+    pub fn push_constraint(&mut self, a: &[(usize, B)], b: &[(usize, B)], c: &[(usize, B)]) {
+        for (col, _) in a.iter().chain(b.iter()).chain(c.iter()) {
+            self.num_cols = self.num_cols.max(col + 1);
+        }
+        self.a.push_row(a.iter().copied());
+        self.b.push_row(b.iter().copied());
+        self.c.push_row(c.iter().copied());
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.a.num_rows()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn set_num_cols(&mut self, num_cols: usize) {
+        self.num_cols = self.num_cols.max(num_cols);
+    }
+
+    /// Inserts `count` empty columns before column `first`, see [SparseMatrix::shift_columns].
+    pub fn insert_columns(&mut self, first: usize, count: usize) {
+        for matrix in [&mut self.a, &mut self.b, &mut self.c] {
+            matrix.shift_columns(first, count);
+        }
+        self.num_cols = self.num_cols.max(first) + count;
+    }
+
+    /// Largest number of non-zero entries across A, B and C.
+    pub fn max_num_nonzero(&self) -> usize {
+        self.a
+            .num_non_zero()
+            .max(self.b.num_non_zero())
+            .max(self.c.num_non_zero())
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.a.heap_bytes() + self.b.heap_bytes() + self.c.heap_bytes()
+    }
+
+    pub fn bytes_per_constraint(&self) -> f64 {
+        self.heap_bytes() as f64 / self.num_rows().max(1) as f64
+    }
+
+    /// An FNV-1a hash of the number of columns and of every row offset and entry of A, B and C.
+    /// Keys are generated for one circuit, and this tells whether saved keys are stale.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = fnv1a(FNV_OFFSET_BASIS, &(self.num_cols as u64).to_le_bytes());
+        for matrix in [&self.a, &self.b, &self.c] {
+            for offset in matrix.row_offsets() {
+                hash = fnv1a(hash, &(*offset as u64).to_le_bytes());
+            }
+            for (col, value) in matrix.entries() {
+                hash = fnv1a(hash, &(*col as u64).to_le_bytes());
+                hash = fnv1a(hash, &Serializable::to_bytes(value));
+            }
+        }
+        hash
+    }
+
+    /// Lays the matrices out as the `winter_models` R1CS expected by the Fractal indexer.
+    pub fn to_r1cs(&self) -> R1CS<B> {
+        R1CS::new(
+            self.a.to_matrix(self.num_cols),
+            self.b.to_matrix(self.num_cols),
+            self.c.to_matrix(self.num_cols),
+        )
+        .unwrap()
+    }
+}